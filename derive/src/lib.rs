@@ -0,0 +1,285 @@
+//! Derive macros for the `rust-opengl` wrappers.
+//!
+//! Use these through the re-exports in `rust-opengl` (with the `derive`
+//! feature), since the generated code refers to items in that crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, LitInt, Type};
+
+/// Derives `rust_opengl::Vertex` for a `#[repr(C)]` struct.
+///
+/// Each field becomes one vertex attribute. By default the attribute location
+/// is the field's position in the struct, and the component count and type
+/// come from the field's type. Fields can be adjusted with `#[vertex(...)]`:
+///
+/// * `location = N` uses attribute location `N` for this field.
+/// * `normalized` sends integer fields as normalized floats (`0..=255` as
+///   `0.0..=1.0` for a `u8`) instead of as GLSL integers.
+/// * `skip` doesn't make an attribute for the field.
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn has_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut found = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                found = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                // Things like `align(16)` have arguments, which have to be
+                // skipped to get to the next item.
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+fn vertex(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`Vertex` can only be derived for structs",
+        ));
+    };
+    if !has_repr_c(&input)? {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`Vertex` needs a `#[repr(C)]` struct so that field offsets are stable",
+        ));
+    }
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(f) => f.named.iter().collect(),
+        Fields::Unnamed(f) => f.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let mut attributes = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let mut location = u32::try_from(index).unwrap();
+        let mut normalized = false;
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    location = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                } else if meta.path.is_ident("normalized") {
+                    normalized = true;
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("expected `location = N`, `normalized` or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        let ty = &field.ty;
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(index);
+                quote!(#index)
+            }
+        };
+        let name = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => index.to_string(),
+        };
+        let normalized = normalized.then(|| quote!(.normalized()));
+        // Spanned on the type so a field type without an attribute format
+        // is reported on that field.
+        attributes.push(quote_spanned! {ty.span()=>
+            ::rust_opengl::VertexAttribute::new::<#ty>(
+                #name,
+                #location,
+                ::core::mem::offset_of!(Self, #member),
+            )#normalized
+        });
+    }
+
+    let all_types = fields.iter().map(|f| &f.ty);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        unsafe impl #impl_generics ::rust_opengl::Vertex for #ident #ty_generics #where_clause {
+            fn attributes() -> ::std::vec::Vec<::rust_opengl::VertexAttribute> {
+                const {
+                    ::core::assert!(
+                        ::core::mem::size_of::<Self>() == 0 #(+ ::core::mem::size_of::<#all_types>())*,
+                        "`Vertex` structs can't have padding between fields",
+                    )
+                };
+                ::std::vec![#(#attributes),*]
+            }
+        }
+    })
+}
+
+/// Derives `rust_opengl::Std140` for a struct, so that it can fill a uniform
+/// block declared with `layout (std140)`.
+///
+/// The fields are laid out in order by the `std140` rules, no matter how the
+/// struct itself is laid out, so no padding fields are needed. Fields must
+/// match the block's members in order, name and type.
+///
+/// A field with an array type is a vector or matrix (`[f32; 3]` is a `vec3`)
+/// unless it's marked with `#[std140(array)]`, which makes it a GLSL array of
+/// the element type (`[f32; 3]` is a `float[3]`).
+#[proc_macro_derive(Std140, attributes(std140))]
+pub fn derive_std140(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    std140(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn std140(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`Std140` can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`Std140` needs named fields, to match them up with the block members",
+        ));
+    };
+
+    // Each field's alignment and size, and the code to write and describe it
+    // given its offset `o`.
+    let mut layouts = Vec::new();
+    let mut writes = Vec::new();
+    let mut members = Vec::new();
+    for field in &fields.named {
+        let mut array = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("std140")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("array") {
+                    array = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `array`"))
+                }
+            })?;
+        }
+        let ident = field.ident.as_ref().unwrap();
+        let name = ident.to_string();
+        let ty = &field.ty;
+        let member_name = quote! {
+            &if name.is_empty() {
+                ::std::string::String::from(#name)
+            } else {
+                ::std::format!("{}.{}", name, #name)
+            }
+        };
+        if array {
+            let Type::Array(array) = ty else {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "`#[std140(array)]` needs an array type",
+                ));
+            };
+            let elem = &array.elem;
+            let len = &array.len;
+            layouts.push(quote_spanned! {elem.span()=>
+                (
+                    <#elem as ::rust_opengl::Std140>::ARRAY_ALIGN,
+                    <#elem as ::rust_opengl::Std140>::ARRAY_STRIDE * (#len),
+                )
+            });
+            writes.push(quote! {
+                ::rust_opengl::write_std140_array::<#elem>(&self.#ident, &mut out[o..]);
+            });
+            members.push(quote! {
+                ::rust_opengl::std140_array_members::<#elem>(#member_name, offset + o, #len, out);
+            });
+        } else {
+            layouts.push(quote_spanned! {ty.span()=>
+                (
+                    <#ty as ::rust_opengl::Std140>::ALIGN,
+                    <#ty as ::rust_opengl::Std140>::SIZE,
+                )
+            });
+            writes.push(quote! {
+                ::rust_opengl::Std140::write_std140(&self.#ident, &mut out[o..]);
+            });
+            members.push(quote! {
+                <#ty as ::rust_opengl::Std140>::std140_members(#member_name, offset + o, out);
+            });
+        }
+    }
+
+    let count = layouts.len();
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            // The offset of each field, and the offset just past the last one.
+            #[doc(hidden)]
+            const STD140_OFFSETS: ([usize; #count], usize) = {
+                let layouts: &[(usize, usize)] = &[#(#layouts),*];
+                let mut offsets = [0; #count];
+                let mut end = 0;
+                let mut i = 0;
+                while i < layouts.len() {
+                    offsets[i] = ::rust_opengl::align_up(end, layouts[i].0);
+                    end = offsets[i] + layouts[i].1;
+                    i += 1;
+                }
+                (offsets, end)
+            };
+        }
+        impl #impl_generics ::rust_opengl::Std140 for #ident #ty_generics #where_clause {
+            const ALIGN: usize = {
+                let layouts: &[(usize, usize)] = &[#(#layouts),*];
+                let mut align = 16;
+                let mut i = 0;
+                while i < layouts.len() {
+                    if layouts[i].0 > align {
+                        align = layouts[i].0;
+                    }
+                    i += 1;
+                }
+                ::rust_opengl::align_up(align, 16)
+            };
+            const SIZE: usize = ::rust_opengl::align_up(Self::STD140_OFFSETS.1, Self::ALIGN);
+            const GLSL_TYPE: ::core::option::Option<::rust_opengl::GlslType> =
+                ::core::option::Option::None;
+
+            fn write_std140(&self, out: &mut [u8]) {
+                let mut offsets = Self::STD140_OFFSETS.0.iter().copied();
+                #(
+                    let o = offsets.next().unwrap();
+                    #writes
+                )*
+            }
+
+            fn std140_members(
+                name: &str,
+                offset: usize,
+                out: &mut ::std::vec::Vec<::rust_opengl::Std140Member>,
+            ) {
+                let mut offsets = Self::STD140_OFFSETS.0.iter().copied();
+                #(
+                    let o = offsets.next().unwrap();
+                    #members
+                )*
+            }
+        }
+    })
+}
@@ -1,48 +1,25 @@
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
-use std::time::Duration;
+use rust_opengl::{clear_color, App, GlWindow, Runner, Time};
 
-fn main() -> Result<(), String> {
-    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
-
-    let gl_attr = video_subsystem.gl_attr();
-    gl_attr.set_context_profile(GLProfile::Core);
-    gl_attr.set_context_version(3, 3);
-
-    let window = video_subsystem
-        .window("Rust SDL2 OpenGL", 800, 600)
-        .opengl()
-        .build()
-        .map_err(|e| e.to_string())?;
+struct BasicWindow;
 
-    let _gl_context = window.gl_create_context()?;
-    let _gl: () =
-        gl::load_with(|s| video_subsystem.gl_get_proc_address(s) as *const std::os::raw::c_void);
-
-    unsafe {
-        gl::ClearColor(0., 0., 0., 1.0);
+impl App for BasicWindow {
+    fn init(_window: &mut GlWindow) -> Result<Self, String> {
+        clear_color(0., 0., 0., 1.0);
+        Ok(BasicWindow)
     }
 
-    let mut event_pump = sdl_context.event_pump()?;
-
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. } => break 'running,
-                _ => {}
-            }
-        }
-
+    fn render(&mut self, _window: &mut GlWindow, _time: &Time) -> Result<(), String> {
         unsafe {
             gl::Clear(gl::COLOR_BUFFER_BIT);
         }
-
-        window.gl_swap_window();
-
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+        Ok(())
     }
+}
 
-    Ok(())
+fn main() -> Result<(), String> {
+    let window = GlWindow::builder()
+        .title("Rust SDL2 OpenGL")
+        .resizable(true)
+        .build()?;
+    Runner::new().run::<BasicWindow>(window)
 }
@@ -0,0 +1,117 @@
+use rust_opengl::{
+    clear_color, vec3, App, GlWindow, Mat4, Mesh, Runner, ShaderProgram, Time, Topology, Vec3,
+};
+
+type Vertex = [f32; 3];
+type TriIndexes = [u32; 3];
+
+const VERTICES: [Vertex; 8] = [
+    [-0.5, -0.5, -0.5],
+    [0.5, -0.5, -0.5],
+    [0.5, 0.5, -0.5],
+    [-0.5, 0.5, -0.5],
+    [-0.5, -0.5, 0.5],
+    [0.5, -0.5, 0.5],
+    [0.5, 0.5, 0.5],
+    [-0.5, 0.5, 0.5],
+];
+
+const INDICES: [TriIndexes; 12] = [
+    [0, 2, 1],
+    [0, 3, 2],
+    [4, 5, 6],
+    [4, 6, 7],
+    [0, 4, 7],
+    [0, 7, 3],
+    [1, 2, 6],
+    [1, 6, 5],
+    [0, 1, 5],
+    [0, 5, 4],
+    [3, 7, 6],
+    [3, 6, 2],
+];
+
+const VERTEX_SHADER_SOURCE: &str = r#"
+      #version 330 core
+      layout (location = 0) in vec3 pos;
+
+      uniform mat4 model;
+      uniform mat4 view;
+      uniform mat4 projection;
+
+      out vec3 frag_color;
+
+      void main() {
+        gl_Position = projection * view * model * vec4(pos, 1.0);
+        frag_color = pos + 0.5;
+      }
+    "#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+      #version 330 core
+
+      in vec3 frag_color;
+
+      out vec4 final_color;
+
+      void main() {
+        final_color = vec4(frag_color, 1.0);
+      }
+    "#;
+
+struct Transformations {
+    shader_program: ShaderProgram,
+    mesh: Mesh,
+}
+
+impl App for Transformations {
+    fn init(_window: &mut GlWindow) -> Result<Self, String> {
+        let shader_program =
+            ShaderProgram::from_vert_frag(VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE)
+                .map_err(|e| e.to_string())?;
+
+        let mesh = Mesh::indexed(&VERTICES, &INDICES, Topology::Triangles)
+            .ok_or("Couldn't make a mesh")?;
+
+        clear_color(0.1, 0.1, 0.1, 1.0);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
+
+        Ok(Transformations {
+            shader_program,
+            mesh,
+        })
+    }
+
+    fn render(&mut self, window: &mut GlWindow, time: &Time) -> Result<(), String> {
+        let angle = time.elapsed_secs();
+        let model = Mat4::from_axis_angle(vec3(0.5, 1.0, 0.0).normalize(), angle);
+        let view = Mat4::look_at(vec3(0.0, 1.0, 3.0), Vec3::ZERO, Vec3::Y);
+        let projection = Mat4::perspective(45_f32.to_radians(), window.aspect_ratio(), 0.1, 100.0);
+
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        let set = |name, value: Mat4| {
+            self.shader_program
+                .set_uniform(name, value)
+                .map_err(|e| e.to_string())
+        };
+        set("model", model)?;
+        set("view", view)?;
+        set("projection", projection)?;
+        self.mesh.draw();
+
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), String> {
+    let window = GlWindow::builder()
+        .title("Rust SDL2 OpenGL")
+        .resizable(true)
+        .build()?;
+    Runner::new().run::<Transformations>(window)
+}
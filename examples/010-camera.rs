@@ -0,0 +1,166 @@
+use rust_opengl::{
+    clear_color, vec3, App, Camera, Flow, FpsCamera, FreeCamera, GlWindow, Input, Mat4, Mesh,
+    OrbitCamera, Quat, Runner, ShaderProgram, Time, Topology, Vec3,
+};
+use sdl2::event::Event;
+use sdl2::keyboard::Scancode;
+
+type Vertex = [f32; 3];
+type TriIndexes = [u32; 3];
+
+const VERTICES: [Vertex; 8] = [
+    [-0.5, -0.5, -0.5],
+    [0.5, -0.5, -0.5],
+    [0.5, 0.5, -0.5],
+    [-0.5, 0.5, -0.5],
+    [-0.5, -0.5, 0.5],
+    [0.5, -0.5, 0.5],
+    [0.5, 0.5, 0.5],
+    [-0.5, 0.5, 0.5],
+];
+
+const INDICES: [TriIndexes; 12] = [
+    [0, 2, 1],
+    [0, 3, 2],
+    [4, 5, 6],
+    [4, 6, 7],
+    [0, 4, 7],
+    [0, 7, 3],
+    [1, 2, 6],
+    [1, 6, 5],
+    [0, 1, 5],
+    [0, 5, 4],
+    [3, 7, 6],
+    [3, 6, 2],
+];
+
+const VERTEX_SHADER_SOURCE: &str = r#"
+      #version 330 core
+      layout (location = 0) in vec3 pos;
+
+      uniform mat4 model;
+      uniform mat4 view_projection;
+
+      out vec3 frag_color;
+
+      void main() {
+        gl_Position = view_projection * model * vec4(pos, 1.0);
+        frag_color = pos + 0.5;
+      }
+    "#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+      #version 330 core
+
+      in vec3 frag_color;
+
+      out vec4 final_color;
+
+      void main() {
+        final_color = vec4(frag_color, 1.0);
+      }
+    "#;
+
+/// Press 1 for the orbit camera, 2 for the first person camera, and 3 for
+/// the free camera. F12 saves a screenshot.
+struct CameraDemo {
+    shader_program: ShaderProgram,
+    mesh: Mesh,
+    camera: Box<dyn Camera>,
+}
+
+impl App for CameraDemo {
+    fn init(_window: &mut GlWindow) -> Result<Self, String> {
+        let shader_program =
+            ShaderProgram::from_vert_frag(VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE)
+                .map_err(|e| e.to_string())?;
+
+        let mesh = Mesh::indexed(&VERTICES, &INDICES, Topology::Triangles)
+            .ok_or("Couldn't make a mesh")?;
+
+        clear_color(0.1, 0.1, 0.1, 1.0);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
+
+        Ok(CameraDemo {
+            shader_program,
+            mesh,
+            camera: Box::new(OrbitCamera::new(Vec3::ZERO, 8.0)),
+        })
+    }
+
+    fn event(&mut self, window: &mut GlWindow, event: &Event) -> Flow {
+        let scancode = match event {
+            Event::Quit { .. } => return Flow::Exit,
+            Event::KeyDown {
+                scancode: Some(scancode),
+                ..
+            } => Some(*scancode),
+            _ => None,
+        };
+        let position = self.camera.position();
+        match scancode {
+            Some(Scancode::Escape) => return Flow::Exit,
+            Some(Scancode::Num1) => {
+                self.camera = Box::new(OrbitCamera::new(Vec3::ZERO, position.length()));
+            }
+            Some(Scancode::Num2) => {
+                self.camera = Box::new(FpsCamera::looking_at(position, Vec3::ZERO));
+            }
+            Some(Scancode::Num3) => {
+                let fps = FpsCamera::looking_at(position, Vec3::ZERO);
+                let mut free = FreeCamera::new(position);
+                free.orientation = Quat::from_yaw_pitch_roll(fps.yaw, fps.pitch, 0.0);
+                self.camera = Box::new(free);
+            }
+            _ => {
+                self.camera.handle_event(event);
+                return Flow::Continue;
+            }
+        }
+        // The orbit camera is dragged with the cursor, and the others turn
+        // with the mouse wherever it is.
+        let relative = scancode != Some(Scancode::Num1);
+        window.set_relative_mouse_mode(relative);
+        Flow::Continue
+    }
+
+    fn update(&mut self, time: &Time, _input: &Input) {
+        self.camera.update(time.delta_secs());
+    }
+
+    fn render(&mut self, window: &mut GlWindow, _time: &Time) -> Result<(), String> {
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        self.shader_program
+            .set_uniform(
+                "view_projection",
+                self.camera.view_projection(window.aspect_ratio()),
+            )
+            .map_err(|e| e.to_string())?;
+        for x in -2..=2 {
+            for z in -2..=2 {
+                let model = Mat4::from_translation(vec3(x as f32, 0.0, z as f32) * 2.0);
+                self.shader_program
+                    .set_uniform("model", model)
+                    .map_err(|e| e.to_string())?;
+                self.mesh.draw();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), String> {
+    let window = GlWindow::builder()
+        .title("Rust SDL2 OpenGL")
+        .resizable(true)
+        .build()?;
+    Runner::new()
+        .screenshot_key(Scancode::F12)
+        .run::<CameraDemo>(window)
+}
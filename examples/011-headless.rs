@@ -0,0 +1,53 @@
+use rust_opengl::{clear_color, HeadlessContext, Mesh, ShaderProgram, Topology};
+
+const VERTEX_SHADER_SOURCE: &str = r#"
+      #version 330 core
+      layout (location = 0) in vec3 aPos;
+      void main() {
+        gl_Position = vec4(aPos.x, aPos.y, aPos.z, 1.0);
+      }
+    "#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+      #version 330 core
+      out vec4 FragColor;
+      void main() {
+        FragColor = vec4(1.0, 0.5, 0.2, 1.0);
+      }
+    "#;
+
+/// Draws the triangle from the second lesson with no window, and checks the
+/// pixels in the middle and the corner.
+fn main() -> Result<(), String> {
+    let context = HeadlessContext::builder().size(64, 64).build()?;
+
+    let shader_program =
+        ShaderProgram::from_vert_frag(VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE)
+            .map_err(|e| e.to_string())?;
+
+    type Vertex = [f32; 3];
+    let vertices: [Vertex; 3] = [[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.0, 0.5, 0.0]];
+
+    let mesh = Mesh::new(&vertices, Topology::Triangles).ok_or("Couldn't make a mesh")?;
+
+    clear_color(0., 0., 0., 1.0);
+    unsafe {
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+    shader_program.use_program();
+    mesh.draw();
+
+    let image = context.read_pixels();
+    let middle = image.get_pixel(32, 32);
+    let corner = image.get_pixel(0, 0);
+    println!("middle: {middle:?}, corner: {corner:?}");
+
+    if middle == corner {
+        return Err("The triangle wasn't drawn as expected".to_string());
+    }
+    // Pass a path to see the whole picture.
+    if let Some(path) = std::env::args().nth(1) {
+        context.save_screenshot(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,289 @@
+//! Running a program's main loop, with frame timing.
+
+use crate::{GlWindow, Input};
+use sdl2::event::Event;
+use sdl2::keyboard::Scancode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Whether the main loop should keep going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flow {
+    /// Keep running.
+    Continue,
+    /// Stop right away, skipping the rest of this frame (including any
+    /// events left, the update and the render), then call
+    /// [`App::shutdown`].
+    Exit,
+}
+
+/// How much time has passed, as seen by an [`App`].
+///
+/// During a fixed-timestep update, [`delta`](Self::delta) is the step and
+/// [`elapsed`](Self::elapsed) is the simulated time. Otherwise, they're the
+/// real time since the last frame and since the loop started.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+    alpha: f32,
+}
+impl Time {
+    /// The time since the last update or frame.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// The time since the last update or frame, in seconds.
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// The time since the loop started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The time since the loop started, in seconds.
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// The number of frames drawn before this one.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// How far the frame being drawn is between the last fixed update and
+    /// the next, from `0.0` to `1.0`.
+    ///
+    /// Drawing with the state interpolated this far from the previous update
+    /// to the latest one keeps motion smooth when updates and frames don't
+    /// line up. Without a fixed timestep this is always `1.0`.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+}
+
+/// A program run by a [`Runner`].
+///
+/// Each frame, the runner passes every pending event to
+/// [`GlWindow::handle_event`], [`Input::handle_event`] and then
+/// [`event`](Self::event), calls [`update`](Self::update) (once, or once per
+/// fixed step), then calls [`render`](Self::render) and swaps the window's
+/// buffers.
+pub trait App: Sized {
+    /// Makes the app, once the window and its GL context are ready.
+    fn init(window: &mut GlWindow) -> Result<Self, String>;
+
+    /// Handles an event.
+    ///
+    /// By default, this exits when the window is closed.
+    fn event(&mut self, window: &mut GlWindow, event: &Event) -> Flow {
+        let _ = window;
+        match event {
+            Event::Quit { .. } => Flow::Exit,
+            _ => Flow::Continue,
+        }
+    }
+
+    /// Moves the app's state forward by [`Time::delta`], given the state of
+    /// the keyboard, mouse and controllers.
+    ///
+    /// With a fixed timestep, every update in a frame sees the same input, so
+    /// something pressed that frame is seen by each of them.
+    fn update(&mut self, time: &Time, input: &Input) {
+        let _ = (time, input);
+    }
+
+    /// Draws a frame.
+    fn render(&mut self, window: &mut GlWindow, time: &Time) -> Result<(), String>;
+
+    /// Cleans up before the loop returns.
+    fn shutdown(&mut self, window: &mut GlWindow) {
+        let _ = window;
+    }
+}
+
+/// Runs an [`App`] in a window, keeping track of time.
+///
+/// By default, the app is updated once per frame, and the frame rate is left
+/// to vsync (see [`GlWindowBuilder::vsync`](crate::GlWindowBuilder::vsync)).
+#[derive(Debug, Clone)]
+pub struct Runner {
+    fixed_step: Option<Duration>,
+    frame_cap: Option<Duration>,
+    max_delta: Duration,
+    screenshot_key: Option<Scancode>,
+}
+impl Default for Runner {
+    fn default() -> Self {
+        Self {
+            fixed_step: None,
+            frame_cap: None,
+            max_delta: Duration::from_millis(250),
+            screenshot_key: None,
+        }
+    }
+}
+impl Runner {
+    /// Makes a runner with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the app in steps of a fixed length, as many as fit in the time
+    /// that has passed, instead of once per frame.
+    ///
+    /// Leftover time carries over to the next frame, and [`Time::alpha`]
+    /// says how much is left over.
+    pub fn fixed_step(mut self, step: Duration) -> Self {
+        self.fixed_step = (!step.is_zero()).then_some(step);
+        self
+    }
+
+    /// Updates the app this many times per second, with
+    /// [`fixed_step`](Self::fixed_step).
+    ///
+    /// A rate that isn't positive goes back to updating once per frame.
+    pub fn updates_per_second(mut self, rate: f64) -> Self {
+        self.fixed_step = (rate > 0.0)
+            .then(|| Duration::try_from_secs_f64(1.0 / rate).ok())
+            .flatten()
+            .filter(|step| !step.is_zero());
+        self
+    }
+
+    /// Limits the frame rate, by sleeping until this long after each frame
+    /// started.
+    ///
+    /// Since it's measured from the start of the frame, the time spent
+    /// handling events, updating and drawing counts toward it.
+    pub fn frame_cap(mut self, fps: f64) -> Self {
+        self.frame_cap = (fps > 0.0)
+            .then(|| Duration::try_from_secs_f64(1.0 / fps).ok())
+            .flatten();
+        self
+    }
+
+    /// Sets the most time that a single frame can account for, so that a
+    /// long stall (like a breakpoint, or dragging the window) doesn't cause
+    /// a burst of updates afterwards. Defaults to 250 milliseconds.
+    pub fn max_delta(mut self, max_delta: Duration) -> Self {
+        self.max_delta = max_delta;
+        self
+    }
+
+    /// Saves a screenshot whenever a key is pressed, as a PNG in the current
+    /// directory named for the time it was taken.
+    pub fn screenshot_key(mut self, key: Scancode) -> Self {
+        self.screenshot_key = Some(key);
+        self
+    }
+
+    /// Makes an app with [`App::init`] and runs it until it exits.
+    pub fn run<A: App>(self, mut window: GlWindow) -> Result<(), String> {
+        let mut app = A::init(&mut window)?;
+        let mut event_pump = window.event_pump()?;
+        // Controllers are optional, so the keyboard and mouse still work
+        // without them.
+        let mut input = Input::with_controllers(window.sdl()).unwrap_or_default();
+
+        let start = Instant::now();
+        let mut last_frame = start;
+        let mut simulated = Duration::ZERO;
+        let mut accumulator = Duration::ZERO;
+        let mut frame = 0;
+
+        let result = 'running: loop {
+            let frame_start = Instant::now();
+            let delta = (frame_start - last_frame).min(self.max_delta);
+            last_frame = frame_start;
+
+            input.begin_frame();
+            for event in event_pump.poll_iter() {
+                window.handle_event(&event);
+                input.handle_event(&event);
+                if app.event(&mut window, &event) == Flow::Exit {
+                    break 'running Ok(());
+                }
+            }
+
+            let alpha = match self.fixed_step {
+                Some(step) => {
+                    accumulator += delta;
+                    while accumulator >= step {
+                        app.update(
+                            &Time {
+                                delta: step,
+                                elapsed: simulated,
+                                frame,
+                                alpha: 0.0,
+                            },
+                            &input,
+                        );
+                        simulated += step;
+                        accumulator -= step;
+                    }
+                    accumulator.as_secs_f32() / step.as_secs_f32()
+                }
+                None => {
+                    simulated += delta;
+                    app.update(
+                        &Time {
+                            delta,
+                            elapsed: simulated,
+                            frame,
+                            alpha: 1.0,
+                        },
+                        &input,
+                    );
+                    1.0
+                }
+            };
+
+            let time = Time {
+                delta,
+                elapsed: frame_start - start,
+                frame,
+                alpha,
+            };
+            if let Err(e) = app.render(&mut window, &time) {
+                break 'running Err(e);
+            }
+            if self
+                .screenshot_key
+                .is_some_and(|key| input.key_pressed(key))
+            {
+                save_screenshot(&window);
+            }
+            window.swap();
+            frame += 1;
+
+            if let Some(cap) = self.frame_cap {
+                let next_frame = frame_start + cap;
+                let now = Instant::now();
+                if now < next_frame {
+                    std::thread::sleep(next_frame - now);
+                }
+            }
+        };
+
+        app.shutdown(&mut window);
+        result
+    }
+}
+
+/// Saves a screenshot of what was just drawn, and says where (or why it
+/// couldn't) on standard error, since a failed screenshot isn't worth
+/// stopping the app over.
+fn save_screenshot(window: &GlWindow) {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis());
+    let path = format!("screenshot-{millis}.png");
+    match window.save_screenshot(&path) {
+        Ok(()) => eprintln!("Saved a screenshot to {path}"),
+        Err(e) => eprintln!("Couldn't save a screenshot to {path}: {e}"),
+    }
+}
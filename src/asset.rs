@@ -0,0 +1,304 @@
+//! Finding asset files without depending on the working directory.
+
+use core::fmt;
+use image::DynamicImage;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// The environment variable that [`AssetLocator::with_default_roots`] reads extra
+/// asset directories from.
+///
+/// It can hold several directories, separated the way `PATH` is.
+pub const ASSET_DIR_VAR: &str = "RUST_OPENGL_ASSETS";
+
+/// The ways that finding or loading an asset can fail.
+#[derive(Debug)]
+pub enum AssetError {
+    /// The asset isn't in any of the roots.
+    NotFound {
+        /// The name that was looked for.
+        name: String,
+        /// Every path that was tried, in order.
+        tried: Vec<PathBuf>,
+    },
+    /// The asset was found but couldn't be read.
+    Io {
+        /// Where the asset was found.
+        path: PathBuf,
+        /// What went wrong.
+        error: std::io::Error,
+    },
+    /// The asset was read but couldn't be decoded as an image.
+    Image {
+        /// The name of the asset.
+        name: String,
+        /// What went wrong.
+        error: image::ImageError,
+    },
+}
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::NotFound { name, tried } if tried.is_empty() => {
+                write!(
+                    f,
+                    "couldn't find asset `{name}`, since there are no asset roots"
+                )
+            }
+            AssetError::NotFound { name, tried } => {
+                write!(f, "couldn't find asset `{name}`, tried:")?;
+                for path in tried {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            }
+            AssetError::Io { path, error } => {
+                write!(f, "couldn't read asset `{}`: {error}", path.display())
+            }
+            AssetError::Image { name, error } => {
+                write!(f, "couldn't decode asset `{name}`: {error}")
+            }
+        }
+    }
+}
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::NotFound { .. } => None,
+            AssetError::Io { error, .. } => Some(error),
+            AssetError::Image { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Finds asset files by looking in a list of root directories, in order.
+///
+/// Paths like `"logo.png"` are relative to the working directory, so they
+/// only work when the program is run from the right place. A locator looks
+/// for them in known places instead, and says where it looked if they
+/// aren't there.
+///
+/// With the `embed-assets` feature, assets can also be built into the program
+/// with [`embed_assets!`](crate::embed_assets), and those are used before any
+/// files.
+#[derive(Debug, Clone, Default)]
+pub struct AssetLocator {
+    roots: Vec<PathBuf>,
+    #[cfg(feature = "embed-assets")]
+    embedded: Vec<(&'static str, &'static [u8])>,
+}
+impl AssetLocator {
+    /// Makes a locator with no roots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes a locator that looks in the directories from [`ASSET_DIR_VAR`],
+    /// then the package directory (when run by `cargo`), then the directory
+    /// that the executable is in.
+    pub fn with_default_roots() -> Self {
+        Self::new().env_var(ASSET_DIR_VAR).manifest_dir().exe_dir()
+    }
+
+    /// Adds a root directory.
+    pub fn root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.roots.push(dir.into());
+        self
+    }
+
+    /// Adds the directories listed in an environment variable, if it's set.
+    pub fn env_var(mut self, name: &str) -> Self {
+        if let Some(dirs) = std::env::var_os(name) {
+            self.roots
+                .extend(std::env::split_paths(&dirs).filter(|d| !d.as_os_str().is_empty()));
+        }
+        self
+    }
+
+    /// Adds the directory of the package being run, if the program was
+    /// started by `cargo run`.
+    ///
+    /// This is read when the program runs, so it's the directory of the
+    /// package that has the binary (or example), not of this crate.
+    pub fn manifest_dir(self) -> Self {
+        self.env_var("CARGO_MANIFEST_DIR")
+    }
+
+    /// Adds the directory that the executable is in, for assets shipped
+    /// alongside it.
+    pub fn exe_dir(mut self) -> Self {
+        if let Some(dir) = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            self.roots.push(dir);
+        }
+        self
+    }
+
+    /// Adds assets that are built into the program, usually made with
+    /// [`embed_assets!`](crate::embed_assets).
+    #[cfg(feature = "embed-assets")]
+    pub fn embedded(mut self, assets: &[(&'static str, &'static [u8])]) -> Self {
+        self.embedded.extend_from_slice(assets);
+        self
+    }
+
+    /// The root directories, in the order they're searched.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Finds the file for an asset, in the first root that has it.
+    ///
+    /// This only looks for files, not embedded assets.
+    pub fn find(&self, name: impl AsRef<Path>) -> Result<PathBuf, AssetError> {
+        let name = name.as_ref();
+        let tried: Vec<PathBuf> = self.roots.iter().map(|root| root.join(name)).collect();
+        match tried.iter().find(|path| path.is_file()) {
+            Some(path) => Ok(path.clone()),
+            None => Err(AssetError::NotFound {
+                name: name.display().to_string(),
+                tried,
+            }),
+        }
+    }
+
+    /// Reads the bytes of an asset, from the embedded assets or else from
+    /// the first root that has it.
+    pub fn read(&self, name: impl AsRef<Path>) -> Result<Cow<'static, [u8]>, AssetError> {
+        let name = name.as_ref();
+        #[cfg(feature = "embed-assets")]
+        if let Some(&(_, bytes)) = self
+            .embedded
+            .iter()
+            .find(|(embedded, _)| Path::new(embedded) == name)
+        {
+            return Ok(Cow::Borrowed(bytes));
+        }
+        let path = self.find(name)?;
+        std::fs::read(&path)
+            .map(Cow::Owned)
+            .map_err(|error| AssetError::Io { path, error })
+    }
+
+    /// Reads and decodes an image asset, such as for
+    /// [`TextureBuilder::from_image`](crate::TextureBuilder::from_image).
+    pub fn load_image(&self, name: impl AsRef<Path>) -> Result<DynamicImage, AssetError> {
+        let name = name.as_ref();
+        let bytes = self.read(name)?;
+        image::load_from_memory(&bytes).map_err(|error| AssetError::Image {
+            name: name.display().to_string(),
+            error,
+        })
+    }
+}
+
+/// Builds files into the program, as a list for [`AssetLocator::embedded`].
+///
+/// Each name is a path relative to the directory of the package using the
+/// macro, such as `embed_assets!["logo.png", "shaders/basic.vert"]`, and is
+/// also the name that the asset is found by.
+#[cfg(feature = "embed-assets")]
+#[macro_export]
+macro_rules! embed_assets {
+    ($($name:literal),* $(,)?) => {
+        &[$((
+            $name,
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $name)) as &'static [u8],
+        )),*]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system's temporary directory, removed when
+    /// it's dropped.
+    struct TempDir(PathBuf);
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("rust-opengl-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn root(&self, name: &str) -> PathBuf {
+            let root = self.0.join(name);
+            std::fs::create_dir_all(&root).unwrap();
+            root
+        }
+    }
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn first_root_with_the_file_wins() {
+        let temp = TempDir::new("first-root");
+        let (a, b, c) = (temp.root("a"), temp.root("b"), temp.root("c"));
+        std::fs::write(b.join("shader.vert"), "from b").unwrap();
+        std::fs::write(c.join("shader.vert"), "from c").unwrap();
+        // A directory with the same name doesn't count.
+        std::fs::create_dir(a.join("shader.vert")).unwrap();
+
+        let locator = AssetLocator::new().root(&a).root(&b).root(&c);
+        assert_eq!(locator.find("shader.vert").unwrap(), b.join("shader.vert"));
+        assert_eq!(&*locator.read("shader.vert").unwrap(), b"from b");
+    }
+
+    #[test]
+    fn not_found_lists_every_path_tried() {
+        let temp = TempDir::new("not-found");
+        let (a, b) = (temp.root("a"), temp.root("b"));
+        let locator = AssetLocator::new().root(&a).root(&b);
+
+        let error = locator.read("textures/missing.png").unwrap_err();
+        let AssetError::NotFound { name, tried } = &error else {
+            panic!("expected NotFound, got {error:?}");
+        };
+        assert_eq!(name, "textures/missing.png");
+        assert_eq!(
+            tried,
+            &[
+                a.join("textures/missing.png"),
+                b.join("textures/missing.png")
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "couldn't find asset `textures/missing.png`, tried:\n  {}\n  {}",
+                tried[0].display(),
+                tried[1].display()
+            )
+        );
+
+        let error = AssetLocator::new().find("missing.png").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "couldn't find asset `missing.png`, since there are no asset roots"
+        );
+    }
+
+    #[test]
+    fn env_var_skips_empty_entries() {
+        let temp = TempDir::new("env-var");
+        let (a, b) = (temp.root("a"), temp.root("b"));
+        let dirs =
+            std::env::join_paths([Path::new(""), &a, Path::new(""), &b, Path::new("")]).unwrap();
+        // Nothing else in the tests reads this variable.
+        std::env::set_var(ASSET_DIR_VAR, dirs);
+        let locator = AssetLocator::new().env_var(ASSET_DIR_VAR);
+        std::env::remove_var(ASSET_DIR_VAR);
+        assert_eq!(locator.roots(), [a, b]);
+
+        let unset = AssetLocator::new().env_var("RUST_OPENGL_TEST_UNSET_VARIABLE");
+        assert!(unset.roots().is_empty());
+    }
+}
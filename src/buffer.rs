@@ -0,0 +1,502 @@
+//! Vertex array and buffer objects.
+
+use crate::{gl_version, has_extension, Fence, FenceStatus};
+use core::ops::{BitOr, BitOrAssign, Deref, DerefMut};
+use core::time::Duration;
+use gl::types::{GLbitfield, GLenum, GLintptr, GLsizeiptr, GLuint};
+use std::mem::ManuallyDrop;
+
+/// Basic wrapper for a [Vertex Array
+/// Object](https://www.khronos.org/opengl/wiki/Vertex_Specification#Vertex_Array_Object).
+///
+/// The vertex array is deleted when this value is dropped.
+pub struct VertexArray(GLuint);
+impl VertexArray {
+    /// Creates a new vertex array object
+    pub fn new() -> Option<Self> {
+        let mut vao = 0;
+        unsafe { gl::GenVertexArrays(1, &mut vao) };
+        if vao != 0 {
+            Some(Self(vao))
+        } else {
+            None
+        }
+    }
+
+    /// Bind this vertex array as the current vertex array object
+    pub fn bind(&self) {
+        unsafe { gl::BindVertexArray(self.0) }
+    }
+
+    /// Clear the current vertex array object binding.
+    pub fn clear_binding() {
+        unsafe { gl::BindVertexArray(0) }
+    }
+
+    /// The raw name of this vertex array.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the vertex array without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a vertex array name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a vertex array that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+}
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteVertexArrays(1, &self.0) }
+    }
+}
+
+/// The types of buffer object that you can have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferType {
+    /// Array Buffers holds arrays of vertex data for drawing.
+    Array = gl::ARRAY_BUFFER as isize,
+    /// Element Array Buffers hold indexes of what vertexes to use for drawing.
+    ElementArray = gl::ELEMENT_ARRAY_BUFFER as isize,
+    /// Uniform Buffers hold the data of uniform blocks.
+    Uniform = gl::UNIFORM_BUFFER as isize,
+    /// Copy Read Buffers are the source of buffer to buffer copies.
+    CopyRead = gl::COPY_READ_BUFFER as isize,
+    /// Copy Write Buffers are the destination of buffer to buffer copies.
+    CopyWrite = gl::COPY_WRITE_BUFFER as isize,
+    /// Pixel Pack Buffers receive pixels read back from textures and
+    /// framebuffers.
+    PixelPack = gl::PIXEL_PACK_BUFFER as isize,
+    /// Pixel Unpack Buffers are the source of pixels uploaded to textures.
+    PixelUnpack = gl::PIXEL_UNPACK_BUFFER as isize,
+    /// Texture Buffers hold the data of buffer textures.
+    Texture = gl::TEXTURE_BUFFER as isize,
+    /// Transform Feedback Buffers receive the vertex shader's outputs.
+    TransformFeedback = gl::TRANSFORM_FEEDBACK_BUFFER as isize,
+    /// Shader Storage Buffers hold data that shaders can read and write.
+    ///
+    /// Needs GL 4.3 or `GL_ARB_shader_storage_buffer_object`.
+    ShaderStorage = gl::SHADER_STORAGE_BUFFER as isize,
+    /// Draw Indirect Buffers hold the arguments of indirect draw calls.
+    ///
+    /// Needs GL 4.0 or `GL_ARB_draw_indirect`.
+    DrawIndirect = gl::DRAW_INDIRECT_BUFFER as isize,
+    /// Atomic Counter Buffers hold the values of atomic counters.
+    ///
+    /// Needs GL 4.2 or `GL_ARB_shader_atomic_counters`.
+    AtomicCounter = gl::ATOMIC_COUNTER_BUFFER as isize,
+}
+impl BufferType {
+    /// If this type has numbered binding points, which are set with
+    /// [`Buffer::bind_base`] and [`Buffer::bind_range`].
+    pub fn is_indexed(self) -> bool {
+        matches!(
+            self,
+            BufferType::Uniform
+                | BufferType::TransformFeedback
+                | BufferType::ShaderStorage
+                | BufferType::AtomicCounter
+        )
+    }
+
+    /// If the current context supports this type.
+    ///
+    /// Everything but the types marked as needing something newer is part of
+    /// GL 3.3 core.
+    pub fn is_available(self) -> bool {
+        let (version, extension) = match self {
+            BufferType::ShaderStorage => ((4, 3), "GL_ARB_shader_storage_buffer_object"),
+            BufferType::DrawIndirect => ((4, 0), "GL_ARB_draw_indirect"),
+            BufferType::AtomicCounter => ((4, 2), "GL_ARB_shader_atomic_counters"),
+            _ => return true,
+        };
+        gl_version().at_least(version.0, version.1) || has_extension(extension)
+    }
+}
+
+/// Basic wrapper for a [Buffer
+/// Object](https://www.khronos.org/opengl/wiki/Buffer_Object).
+///
+/// The buffer is deleted when this value is dropped.
+pub struct Buffer(GLuint);
+impl Buffer {
+    /// Makes a new vertex buffer
+    pub fn new() -> Option<Self> {
+        let mut vbo = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut vbo);
+        }
+        if vbo != 0 {
+            Some(Self(vbo))
+        } else {
+            None
+        }
+    }
+
+    /// Bind this vertex buffer for the given type
+    pub fn bind(&self, ty: BufferType) {
+        unsafe { gl::BindBuffer(ty as GLenum, self.0) }
+    }
+
+    /// Clear the current vertex buffer binding for the given type.
+    pub fn clear_binding(ty: BufferType) {
+        unsafe { gl::BindBuffer(ty as GLenum, 0) }
+    }
+
+    /// Bind this whole buffer to binding point `index` of an indexed type.
+    ///
+    /// This also binds it for the type in general, like [`bind`](Self::bind).
+    ///
+    /// ## Panics
+    /// If the type isn't [indexed](BufferType::is_indexed).
+    pub fn bind_base(&self, ty: BufferType, index: u32) {
+        assert!(ty.is_indexed(), "{ty:?} buffers don't have binding points");
+        unsafe { gl::BindBufferBase(ty as GLenum, index, self.0) }
+    }
+
+    /// Bind `size` bytes of this buffer, starting `offset` bytes in, to binding
+    /// point `index` of an indexed type.
+    ///
+    /// The offset usually has to be a multiple of some alignment, such as
+    /// `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT` for uniform buffers.
+    ///
+    /// ## Panics
+    /// If the type isn't [indexed](BufferType::is_indexed).
+    pub fn bind_range(&self, ty: BufferType, index: u32, offset: usize, size: usize) {
+        assert!(ty.is_indexed(), "{ty:?} buffers don't have binding points");
+        unsafe {
+            gl::BindBufferRange(
+                ty as GLenum,
+                index,
+                self.0,
+                GLintptr::try_from(offset).unwrap(),
+                GLsizeiptr::try_from(size).unwrap(),
+            )
+        }
+    }
+
+    /// Clear the buffer binding of binding point `index` of an indexed type.
+    ///
+    /// ## Panics
+    /// If the type isn't [indexed](BufferType::is_indexed).
+    pub fn clear_base(ty: BufferType, index: u32) {
+        assert!(ty.is_indexed(), "{ty:?} buffers don't have binding points");
+        unsafe { gl::BindBufferBase(ty as GLenum, index, 0) }
+    }
+
+    /// The raw name of this buffer.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the buffer without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a buffer name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a buffer that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+}
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.0) }
+    }
+}
+
+/// A hint for how the data in a buffer will be used, so that GL can decide
+/// where to keep it.
+///
+/// The first part is how often the data changes: *static* data is set once and
+/// used many times, *dynamic* data is changed now and then, and *stream* data
+/// is set once and only used a few times. The second part is who reads it:
+/// *draw* data is written by the program and read by GL, *read* data is written
+/// by GL and read by the program, and *copy* data is written and read by GL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    /// Set once, drawn many times.
+    StaticDraw = gl::STATIC_DRAW as isize,
+    /// Set once by GL, read back many times.
+    StaticRead = gl::STATIC_READ as isize,
+    /// Set once by GL, used by GL many times.
+    StaticCopy = gl::STATIC_COPY as isize,
+    /// Changed now and then, drawn many times.
+    DynamicDraw = gl::DYNAMIC_DRAW as isize,
+    /// Changed by GL now and then, read back many times.
+    DynamicRead = gl::DYNAMIC_READ as isize,
+    /// Changed by GL now and then, used by GL many times.
+    DynamicCopy = gl::DYNAMIC_COPY as isize,
+    /// Set once, drawn a few times.
+    StreamDraw = gl::STREAM_DRAW as isize,
+    /// Set once by GL, read back a few times.
+    StreamRead = gl::STREAM_READ as isize,
+    /// Set once by GL, used by GL a few times.
+    StreamCopy = gl::STREAM_COPY as isize,
+}
+
+/// Places a slice of data into a previously-bound buffer.
+///
+/// This replaces the buffer's storage, so it can also change the size.
+pub fn buffer_data(ty: BufferType, data: &[u8], usage: BufferUsage) {
+    unsafe {
+        gl::BufferData(
+            ty as GLenum,
+            GLsizeiptr::try_from(data.len()).unwrap(),
+            data.as_ptr().cast(),
+            usage as GLenum,
+        );
+    }
+}
+
+/// Overwrites part of a previously-bound buffer, starting `offset` bytes in.
+///
+/// The buffer keeps its size, so the data has to fit within it.
+pub fn buffer_sub_data(ty: BufferType, offset: usize, data: &[u8]) {
+    unsafe {
+        gl::BufferSubData(
+            ty as GLenum,
+            GLintptr::try_from(offset).unwrap(),
+            GLsizeiptr::try_from(data.len()).unwrap(),
+            data.as_ptr().cast(),
+        );
+    }
+}
+
+/// Gives a previously-bound buffer new, uninitialized storage of `size` bytes.
+///
+/// When the whole buffer is about to be rewritten, doing this first (called
+/// "orphaning") lets GL hand out fresh memory right away instead of waiting
+/// for draw calls that still read the old contents.
+pub fn orphan_buffer(ty: BufferType, size: usize, usage: BufferUsage) {
+    unsafe {
+        gl::BufferData(
+            ty as GLenum,
+            GLsizeiptr::try_from(size).unwrap(),
+            core::ptr::null(),
+            usage as GLenum,
+        );
+    }
+}
+
+/// The options for mapping a buffer range, combined with `|`.
+///
+/// A mapping needs at least one of [`READ`](Self::READ) or
+/// [`WRITE`](Self::WRITE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapAccess(pub GLbitfield);
+impl MapAccess {
+    /// The mapping will be read from.
+    pub const READ: Self = Self(gl::MAP_READ_BIT);
+    /// The mapping will be written to.
+    pub const WRITE: Self = Self(gl::MAP_WRITE_BIT);
+    /// The old contents of the range can be thrown away.
+    pub const INVALIDATE_RANGE: Self = Self(gl::MAP_INVALIDATE_RANGE_BIT);
+    /// The old contents of the whole buffer can be thrown away.
+    pub const INVALIDATE_BUFFER: Self = Self(gl::MAP_INVALIDATE_BUFFER_BIT);
+    /// Writes are only made visible by [`MappedRange::flush`], rather than all
+    /// at once when the range is unmapped.
+    pub const FLUSH_EXPLICIT: Self = Self(gl::MAP_FLUSH_EXPLICIT_BIT);
+    /// Don't wait for earlier commands that use the buffer. Making sure they
+    /// aren't still reading the range is then up to you, such as with a
+    /// [`Fence`](crate::Fence).
+    pub const UNSYNCHRONIZED: Self = Self(gl::MAP_UNSYNCHRONIZED_BIT);
+
+    /// If every flag in `other` is also set in `self`.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+impl BitOr for MapAccess {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+impl BitOrAssign for MapAccess {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl Buffer {
+    /// Maps `len` bytes of this buffer, starting `offset` bytes in, into
+    /// memory.
+    ///
+    /// This binds the buffer for the given type. The range stays mapped until
+    /// the returned guard is dropped, and the buffer can't be used for drawing
+    /// until then. Gives `None` if GL refuses the mapping, such as when the
+    /// range is past the end of the buffer.
+    pub fn map_range(
+        &self,
+        ty: BufferType,
+        offset: usize,
+        len: usize,
+        access: MapAccess,
+    ) -> Option<MappedRange<'_>> {
+        self.bind(ty);
+        let ptr = unsafe {
+            gl::MapBufferRange(
+                ty as GLenum,
+                GLintptr::try_from(offset).ok()?,
+                GLsizeiptr::try_from(len).ok()?,
+                access.0,
+            )
+        };
+        if ptr.is_null() {
+            return None;
+        }
+        Some(MappedRange {
+            buffer: self,
+            ty,
+            ptr: ptr.cast(),
+            len,
+        })
+    }
+}
+
+/// A range of a buffer that's mapped into memory.
+///
+/// This derefs to the mapped bytes. Reading them is only meaningful when the
+/// range was mapped with [`MapAccess::READ`], and writes only reach the buffer
+/// when it was mapped with [`MapAccess::WRITE`]. The range is unmapped when
+/// this value is dropped.
+pub struct MappedRange<'a> {
+    buffer: &'a Buffer,
+    ty: BufferType,
+    ptr: *mut u8,
+    len: usize,
+}
+impl MappedRange<'_> {
+    /// Makes writes to part of the range visible to GL, for ranges mapped with
+    /// [`MapAccess::FLUSH_EXPLICIT`].
+    ///
+    /// The offset is from the start of the mapped range, not of the buffer.
+    pub fn flush(&self, offset: usize, len: usize) {
+        assert!(offset.checked_add(len).is_some_and(|end| end <= self.len));
+        self.buffer.bind(self.ty);
+        unsafe {
+            gl::FlushMappedBufferRange(
+                self.ty as GLenum,
+                GLintptr::try_from(offset).unwrap(),
+                GLsizeiptr::try_from(len).unwrap(),
+            );
+        }
+    }
+}
+impl Deref for MappedRange<'_> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+impl DerefMut for MappedRange<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+impl Drop for MappedRange<'_> {
+    fn drop(&mut self) {
+        self.buffer.bind(self.ty);
+        // This only fails when the storage was lost while mapped (such as by a
+        // display mode change), which just leaves the contents undefined.
+        unsafe { gl::UnmapBuffer(self.ty as GLenum) };
+    }
+}
+
+/// A buffer for data that's rewritten every frame, such as streamed vertices.
+///
+/// The buffer is split into one region per frame in flight. Each frame writes
+/// into its own region without waiting on the GPU, and a fence is placed when
+/// the frame ends so that the region is only reused once the GPU is done
+/// reading it.
+pub struct RingBuffer {
+    buffer: Buffer,
+    ty: BufferType,
+    region_size: usize,
+    fences: Vec<Option<Fence>>,
+    region: usize,
+    used: usize,
+}
+impl RingBuffer {
+    /// Makes a ring buffer with `frames` regions of `region_size` bytes each.
+    ///
+    /// Three frames is usually enough to never wait on the GPU.
+    ///
+    /// ## Panics
+    /// If `frames` is zero.
+    pub fn new(ty: BufferType, region_size: usize, frames: usize) -> Option<Self> {
+        assert!(frames > 0, "a ring buffer needs at least one region");
+        let buffer = Buffer::new()?;
+        buffer.bind(ty);
+        orphan_buffer(ty, region_size * frames, BufferUsage::StreamDraw);
+        Some(Self {
+            buffer,
+            ty,
+            region_size,
+            fences: (0..frames).map(|_| None).collect(),
+            region: 0,
+            used: 0,
+        })
+    }
+
+    /// The underlying buffer, for binding it or pointing a vertex array at it.
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// The size of each frame's region, in bytes.
+    pub fn region_size(&self) -> usize {
+        self.region_size
+    }
+
+    /// The bytes left in this frame's region.
+    pub fn remaining(&self) -> usize {
+        self.region_size - self.used
+    }
+
+    /// Copies `data` into this frame's region, and gives the offset within the
+    /// whole buffer that it was written at.
+    ///
+    /// Gives `None` if there's not enough room left in the region, or the
+    /// range couldn't be mapped. This binds the buffer for its type.
+    pub fn push(&mut self, data: &[u8]) -> Option<usize> {
+        if data.len() > self.remaining() {
+            return None;
+        }
+        let offset = self.region * self.region_size + self.used;
+        if !data.is_empty() {
+            let mut mapped = self.buffer.map_range(
+                self.ty,
+                offset,
+                data.len(),
+                MapAccess::WRITE | MapAccess::INVALIDATE_RANGE | MapAccess::UNSYNCHRONIZED,
+            )?;
+            mapped.copy_from_slice(data);
+        }
+        self.used += data.len();
+        Some(offset)
+    }
+
+    /// Ends the current frame and moves on to the next region, waiting for
+    /// the GPU to finish with that region if it's still in use.
+    ///
+    /// Call this after the draw calls that read this frame's data.
+    pub fn next_frame(&mut self) {
+        self.fences[self.region] = Fence::new();
+        self.region = (self.region + 1) % self.fences.len();
+        self.used = 0;
+        if let Some(fence) = self.fences[self.region].take() {
+            while fence.wait(Duration::from_secs(1)) == FenceStatus::TimedOut {}
+        }
+    }
+}
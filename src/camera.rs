@@ -0,0 +1,534 @@
+//! Cameras that make view and projection matrices, moved around with SDL
+//! keyboard and mouse events.
+
+use crate::{vec3, Mat4, Quat, Vec3};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::MouseWheelDirection;
+
+/// The perspective projection of a camera, along with how far it can zoom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lens {
+    /// The vertical field of view.
+    pub fov_y: f32,
+    /// The narrowest that zooming in can make the field of view.
+    pub min_fov_y: f32,
+    /// The widest that zooming out can make the field of view.
+    pub max_fov_y: f32,
+    /// How much one notch of the mouse wheel changes the field of view.
+    pub zoom_step: f32,
+    /// The distance to the near plane.
+    pub near: f32,
+    /// The distance to the far plane.
+    pub far: f32,
+}
+impl Default for Lens {
+    /// A 45 degree field of view that zooms from 1 to 90 degrees, 2 degrees
+    /// at a time, seeing from 0.1 to 100 units away.
+    fn default() -> Self {
+        Self {
+            fov_y: 45_f32.to_radians(),
+            min_fov_y: 1_f32.to_radians(),
+            max_fov_y: 90_f32.to_radians(),
+            zoom_step: 2_f32.to_radians(),
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+impl Lens {
+    /// The projection matrix, for a viewport with this aspect ratio.
+    pub fn projection(&self, aspect_ratio: f32) -> Mat4 {
+        Mat4::perspective(self.fov_y, aspect_ratio, self.near, self.far)
+    }
+
+    /// Narrows the field of view by `notches` of the mouse wheel, or widens
+    /// it if that's negative, keeping it within the limits.
+    ///
+    /// If the limits are the wrong way around, it ends up at `max_fov_y`.
+    pub fn zoom(&mut self, notches: f32) {
+        self.fov_y = (self.fov_y - notches * self.zoom_step)
+            .max(self.min_fov_y)
+            .min(self.max_fov_y);
+    }
+}
+
+/// Something that can be looked through.
+///
+/// Each camera is moved by passing every event to
+/// [`handle_event`](Self::handle_event), then calling
+/// [`update`](Self::update) once per update, for the keys that are held.
+pub trait Camera {
+    /// The view matrix, which moves the world so that the camera is at the
+    /// origin looking down `-Z`.
+    fn view(&self) -> Mat4;
+
+    /// Where the camera is.
+    fn position(&self) -> Vec3;
+
+    /// The camera's projection.
+    fn lens(&self) -> &Lens;
+
+    /// Reacts to keyboard, mouse and mouse wheel events.
+    fn handle_event(&mut self, event: &Event);
+
+    /// Moves the camera for the keys that are held, over `dt` seconds.
+    fn update(&mut self, dt: f32);
+
+    /// The projection matrix, for a viewport with this aspect ratio.
+    fn projection(&self, aspect_ratio: f32) -> Mat4 {
+        self.lens().projection(aspect_ratio)
+    }
+
+    /// The projection matrix times the view matrix.
+    fn view_projection(&self, aspect_ratio: f32) -> Mat4 {
+        self.projection(aspect_ratio) * self.view()
+    }
+}
+
+/// The most that pitch can be, just short of straight up or down, where the
+/// yaw would stop making sense.
+const MAX_PITCH: f32 = 89_f32.to_radians();
+
+/// How far the mouse wheel scrolled, in notches, with scrolling up positive.
+fn wheel_notches(event: &Event) -> Option<f32> {
+    match *event {
+        Event::MouseWheel {
+            precise_y,
+            direction,
+            ..
+        } => Some(match direction {
+            MouseWheelDirection::Flipped => -precise_y,
+            _ => precise_y,
+        }),
+        _ => None,
+    }
+}
+
+/// How far the mouse moved, in pixels, and if the left button is held.
+fn mouse_motion(event: &Event) -> Option<(f32, f32, bool)> {
+    match *event {
+        Event::MouseMotion {
+            xrel,
+            yrel,
+            mousestate,
+            ..
+        } => Some((xrel as f32, yrel as f32, mousestate.left())),
+        _ => None,
+    }
+}
+
+/// The movement keys that are held.
+///
+/// These are tracked by scancode, so that they're in the same place on every
+/// keyboard layout: W, A, S and D to move, Space and Left Ctrl to go up and
+/// down, and Q and E to roll.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct MoveKeys {
+    forward: bool,
+    back: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+    roll_left: bool,
+    roll_right: bool,
+}
+impl MoveKeys {
+    fn handle_event(&mut self, event: &Event) {
+        let (scancode, pressed) = match *event {
+            Event::KeyDown {
+                scancode: Some(scancode),
+                ..
+            } => (scancode, true),
+            Event::KeyUp {
+                scancode: Some(scancode),
+                ..
+            } => (scancode, false),
+            // Keys released while another window has focus never send an
+            // event here.
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                *self = Self::default();
+                return;
+            }
+            _ => return,
+        };
+        let key = match scancode {
+            Scancode::W => &mut self.forward,
+            Scancode::S => &mut self.back,
+            Scancode::A => &mut self.left,
+            Scancode::D => &mut self.right,
+            Scancode::Space => &mut self.up,
+            Scancode::LCtrl => &mut self.down,
+            Scancode::Q => &mut self.roll_left,
+            Scancode::E => &mut self.roll_right,
+            _ => return,
+        };
+        *key = pressed;
+    }
+
+    /// The direction to move in, with `x` to the right, `y` up and `z`
+    /// forward.
+    fn direction(&self) -> Vec3 {
+        let axis = |positive: bool, negative: bool| f32::from(positive) - f32::from(negative);
+        vec3(
+            axis(self.right, self.left),
+            axis(self.up, self.down),
+            axis(self.forward, self.back),
+        )
+        .normalize_or_zero()
+    }
+
+    fn roll(&self) -> f32 {
+        f32::from(self.roll_left) - f32::from(self.roll_right)
+    }
+}
+
+/// A camera that circles around a target, always looking at it.
+///
+/// Dragging with the left mouse button orbits, and the mouse wheel moves in
+/// and out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCamera {
+    /// The point being looked at.
+    pub target: Vec3,
+    /// How far the camera is from the target.
+    pub distance: f32,
+    /// The closest that the camera can get.
+    pub min_distance: f32,
+    /// The farthest that the camera can get.
+    pub max_distance: f32,
+    /// The angle around `Y` that the camera looks at the target from, with
+    /// `0` looking down `-Z` and positive angles to the left.
+    pub yaw: f32,
+    /// The angle that the camera looks up at the target, so negative angles
+    /// look down on it. It's kept short of straight up or down.
+    pub pitch: f32,
+    /// How far the camera turns per pixel that the mouse moves.
+    pub sensitivity: f32,
+    /// How much one notch of the mouse wheel scales the distance by.
+    pub zoom_factor: f32,
+    /// The camera's projection.
+    pub lens: Lens,
+}
+impl OrbitCamera {
+    /// Makes a camera `distance` from `target`, looking down `-Z`.
+    pub fn new(target: Vec3, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            min_distance: 0.1,
+            max_distance: f32::INFINITY,
+            yaw: 0.0,
+            pitch: 0.0,
+            sensitivity: 0.005,
+            zoom_factor: 0.9,
+            lens: Lens::default(),
+        }
+    }
+
+    /// The direction that the camera is looking in.
+    pub fn front(&self) -> Vec3 {
+        Quat::from_yaw_pitch_roll(self.yaw, self.pitch, 0.0) * -Vec3::Z
+    }
+}
+impl Camera for OrbitCamera {
+    fn view(&self) -> Mat4 {
+        Mat4::look_at(self.position(), self.target, Vec3::Y)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.target - self.front() * self.distance
+    }
+
+    fn lens(&self) -> &Lens {
+        &self.lens
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Some((dx, dy, true)) = mouse_motion(event) {
+            self.yaw -= dx * self.sensitivity;
+            self.pitch = (self.pitch - dy * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+        if let Some(notches) = wheel_notches(event) {
+            // Unlike `clamp`, this doesn't panic if the limits are the wrong
+            // way around.
+            self.distance = (self.distance * self.zoom_factor.powf(notches))
+                .max(self.min_distance)
+                .min(self.max_distance);
+        }
+    }
+
+    fn update(&mut self, _dt: f32) {}
+}
+
+/// A first person camera that turns with yaw and pitch, and never rolls.
+///
+/// Moving the mouse looks around, and the mouse wheel zooms. W, A, S and D
+/// walk along the ground, whichever way the camera is tilted, and Space and
+/// Left Ctrl go straight up and down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FpsCamera {
+    /// Where the camera is.
+    pub position: Vec3,
+    /// The angle around `Y`, with `0` looking down `-Z` and positive angles
+    /// to the left.
+    pub yaw: f32,
+    /// The angle up from level, kept short of straight up or down.
+    pub pitch: f32,
+    /// How far the camera moves per second.
+    pub speed: f32,
+    /// How far the camera turns per pixel that the mouse moves.
+    pub sensitivity: f32,
+    /// The camera's projection.
+    pub lens: Lens,
+    keys: MoveKeys,
+}
+impl FpsCamera {
+    /// Makes a camera at `position`, looking down `-Z`.
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 2.5,
+            sensitivity: 0.002,
+            lens: Lens::default(),
+            keys: MoveKeys::default(),
+        }
+    }
+
+    /// Makes a camera at `position`, looking at `target`.
+    pub fn looking_at(position: Vec3, target: Vec3) -> Self {
+        let d = target - position;
+        let level = (d.x * d.x + d.z * d.z).sqrt();
+        Self {
+            yaw: (-d.x).atan2(-d.z),
+            pitch: d.y.atan2(level).clamp(-MAX_PITCH, MAX_PITCH),
+            ..Self::new(position)
+        }
+    }
+
+    /// The direction that the camera is looking in.
+    pub fn front(&self) -> Vec3 {
+        Quat::from_yaw_pitch_roll(self.yaw, self.pitch, 0.0) * -Vec3::Z
+    }
+}
+impl Camera for FpsCamera {
+    fn view(&self) -> Mat4 {
+        Mat4::look_to(self.position, self.front(), Vec3::Y)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn lens(&self) -> &Lens {
+        &self.lens
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        self.keys.handle_event(event);
+        if let Some((dx, dy, _)) = mouse_motion(event) {
+            self.yaw -= dx * self.sensitivity;
+            self.pitch = (self.pitch - dy * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+        if let Some(notches) = wheel_notches(event) {
+            self.lens.zoom(notches);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        let direction = self.keys.direction();
+        let level = Quat::from_rotation_y(self.yaw);
+        let (forward, right) = (level * -Vec3::Z, level * Vec3::X);
+        let velocity = right * direction.x + Vec3::Y * direction.y + forward * direction.z;
+        self.position += velocity * (self.speed * dt);
+    }
+}
+
+/// A camera that turns and moves freely in all six directions, including
+/// rolling, like a spaceship.
+///
+/// Moving the mouse turns the camera around its own axes, Q and E roll it,
+/// and the mouse wheel zooms. W, A, S and D move along where it's facing,
+/// and Space and Left Ctrl move along its own up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreeCamera {
+    /// Where the camera is.
+    pub position: Vec3,
+    /// Which way the camera is turned, from looking down `-Z` with `+Y` up.
+    pub orientation: Quat,
+    /// How far the camera moves per second.
+    pub speed: f32,
+    /// How far the camera turns per pixel that the mouse moves.
+    pub sensitivity: f32,
+    /// How fast the camera rolls, per second.
+    pub roll_speed: f32,
+    /// The camera's projection.
+    pub lens: Lens,
+    keys: MoveKeys,
+}
+impl FreeCamera {
+    /// Makes a camera at `position`, looking down `-Z` with `+Y` up.
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            orientation: Quat::IDENTITY,
+            speed: 2.5,
+            sensitivity: 0.002,
+            roll_speed: 1.5,
+            lens: Lens::default(),
+            keys: MoveKeys::default(),
+        }
+    }
+
+    /// The direction that the camera is looking in.
+    pub fn front(&self) -> Vec3 {
+        self.orientation * -Vec3::Z
+    }
+
+    /// The camera's own up, which tilts as it rolls.
+    pub fn up(&self) -> Vec3 {
+        self.orientation * Vec3::Y
+    }
+
+    /// The camera's own right.
+    pub fn right(&self) -> Vec3 {
+        self.orientation * Vec3::X
+    }
+}
+impl Camera for FreeCamera {
+    fn view(&self) -> Mat4 {
+        Mat4::from_quat(self.orientation.inverse()) * Mat4::from_translation(-self.position)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn lens(&self) -> &Lens {
+        &self.lens
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        self.keys.handle_event(event);
+        if let Some((dx, dy, _)) = mouse_motion(event) {
+            // Turning around the camera's own axes, rather than the world's,
+            // is what lets it loop all the way over.
+            let turn = Quat::from_rotation_y(-dx * self.sensitivity)
+                * Quat::from_rotation_x(-dy * self.sensitivity);
+            self.orientation = (self.orientation * turn).normalize();
+        }
+        if let Some(notches) = wheel_notches(event) {
+            self.lens.zoom(notches);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        let roll = self.keys.roll();
+        if roll != 0.0 {
+            let turn = Quat::from_rotation_z(roll * self.roll_speed * dt);
+            self.orientation = (self.orientation * turn).normalize();
+        }
+        let direction = self.keys.direction();
+        let velocity =
+            self.right() * direction.x + self.up() * direction.y + self.front() * direction.z;
+        self.position += velocity * (self.speed * dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wheel(notches: f32) -> Event {
+        Event::MouseWheel {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            x: 0,
+            y: notches as i32,
+            direction: MouseWheelDirection::Normal,
+            precise_x: 0.0,
+            precise_y: notches,
+            mouse_x: 0,
+            mouse_y: 0,
+        }
+    }
+
+    fn assert_vec3_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-4, "{a:?} is not near {b:?}");
+    }
+
+    #[test]
+    fn zoom_stays_within_the_limits() {
+        let mut lens = Lens::default();
+        lens.zoom(1.0);
+        assert!((lens.fov_y - 43_f32.to_radians()).abs() < 1e-6);
+        lens.zoom(100.0);
+        assert_eq!(lens.fov_y, lens.min_fov_y);
+        lens.zoom(-100.0);
+        assert_eq!(lens.fov_y, lens.max_fov_y);
+
+        // Limits the wrong way around, or missing, don't panic.
+        lens.min_fov_y = 1.0;
+        lens.max_fov_y = 0.5;
+        lens.zoom(1.0);
+        assert_eq!(lens.fov_y, 0.5);
+        lens.min_fov_y = f32::NAN;
+        lens.max_fov_y = f32::NAN;
+        lens.zoom(1.0);
+        assert!(lens.fov_y.is_finite());
+    }
+
+    #[test]
+    fn fps_camera_looks_at_the_target() {
+        let position = vec3(1.0, 2.0, 3.0);
+        for target in [
+            vec3(-4.0, 0.0, 1.0),
+            vec3(5.0, 3.0, 8.0),
+            vec3(1.0, 0.0, -6.0),
+        ] {
+            let camera = FpsCamera::looking_at(position, target);
+            assert_vec3_near(camera.front(), (target - position).normalize());
+            let seen = camera.view().transform_point3(target);
+            assert_vec3_near(seen, vec3(0.0, 0.0, -position.distance(target)));
+        }
+        // Straight down is kept just short of it.
+        let camera = FpsCamera::looking_at(position, position - Vec3::Y);
+        assert_eq!(camera.pitch, -MAX_PITCH);
+    }
+
+    #[test]
+    fn orbit_distance_stays_within_the_limits() {
+        let mut camera = OrbitCamera::new(Vec3::ZERO, 10.0);
+        camera.min_distance = 2.0;
+        camera.max_distance = 20.0;
+        camera.handle_event(&wheel(1.0));
+        assert!((camera.distance - 9.0).abs() < 1e-5);
+        camera.handle_event(&wheel(100.0));
+        assert_eq!(camera.distance, 2.0);
+        camera.handle_event(&wheel(-100.0));
+        assert_eq!(camera.distance, 20.0);
+        assert_vec3_near(camera.position(), vec3(0.0, 0.0, 20.0));
+
+        camera.min_distance = 30.0;
+        camera.handle_event(&wheel(1.0));
+        assert_eq!(camera.distance, 20.0);
+    }
+
+    #[test]
+    fn free_camera_view_moves_it_to_the_origin() {
+        let mut camera = FreeCamera::new(vec3(3.0, -1.0, 4.0));
+        camera.orientation = Quat::from_yaw_pitch_roll(0.4, -0.3, 1.2);
+        let view = camera.view();
+        assert_vec3_near(view.transform_point3(camera.position), Vec3::ZERO);
+        assert_vec3_near(view.transform_vector3(camera.front()), -Vec3::Z);
+        assert_vec3_near(view.transform_vector3(camera.up()), Vec3::Y);
+        assert_vec3_near(view.transform_vector3(camera.right()), Vec3::X);
+    }
+}
@@ -0,0 +1,66 @@
+//! Finding out what the current context supports.
+
+use core::fmt;
+use gl::types::{GLint, GLuint};
+use std::ffi::CStr;
+
+/// The version of GL that a context provides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlVersion {
+    /// The major version, such as `3` for GL 3.3.
+    pub major: u32,
+    /// The minor version, such as `3` for GL 3.3.
+    pub minor: u32,
+}
+impl GlVersion {
+    /// If this version is at least `major.minor`.
+    pub fn at_least(self, major: u32, minor: u32) -> bool {
+        self >= GlVersion { major, minor }
+    }
+}
+impl fmt::Display for GlVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+pub(crate) fn get_integer(pname: gl::types::GLenum) -> GLint {
+    let mut value = 0;
+    unsafe { gl::GetIntegerv(pname, &mut value) };
+    value
+}
+
+/// The GL version of the current context.
+pub fn gl_version() -> GlVersion {
+    GlVersion {
+        major: u32::try_from(get_integer(gl::MAJOR_VERSION)).unwrap_or(0),
+        minor: u32::try_from(get_integer(gl::MINOR_VERSION)).unwrap_or(0),
+    }
+}
+
+/// Lists the extensions that the current context supports, such as
+/// `"GL_ARB_draw_indirect"`.
+pub fn extensions() -> Vec<String> {
+    let count = GLuint::try_from(get_integer(gl::NUM_EXTENSIONS)).unwrap_or(0);
+    (0..count)
+        .filter_map(|i| {
+            let name = unsafe { gl::GetStringi(gl::EXTENSIONS, i) };
+            if name.is_null() {
+                return None;
+            }
+            let name = unsafe { CStr::from_ptr(name.cast()) };
+            Some(name.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// If the current context supports the named extension.
+///
+/// This asks GL every time, so save the answer if it's needed often.
+pub fn has_extension(name: &str) -> bool {
+    let count = GLuint::try_from(get_integer(gl::NUM_EXTENSIONS)).unwrap_or(0);
+    (0..count).any(|i| {
+        let ext = unsafe { gl::GetStringi(gl::EXTENSIONS, i) };
+        !ext.is_null() && unsafe { CStr::from_ptr(ext.cast()) }.to_bytes() == name.as_bytes()
+    })
+}
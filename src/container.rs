@@ -0,0 +1,403 @@
+//! Texture container files (KTX, KTX2 and DDS), which hold textures ready to
+//! upload, such as block compressed textures along with their mipmaps.
+
+use crate::dds::{parse_dds, DDS_MAGIC};
+use crate::ktx::{parse_ktx, parse_ktx2, KTX2_IDENTIFIER, KTX_IDENTIFIER};
+use crate::{
+    align_up, pixel_size, CubeFace, InternalFormat, PixelFormat, PixelType, Texture,
+    TextureBuilder, TextureError, TextureType,
+};
+use core::fmt;
+use gl::types::{GLenum, GLint, GLsizei};
+use std::borrow::Cow;
+
+/// The kinds of file that a [`TextureContainer`] can be read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContainerKind {
+    /// A [KTX](https://registry.khronos.org/KTX/specs/1.0/ktxspec.v1.html)
+    /// file.
+    Ktx,
+    /// A [KTX2](https://registry.khronos.org/KTX/specs/2.0/ktxspec.v2.html)
+    /// file.
+    Ktx2,
+    /// A DirectDraw Surface file, with or without the DX10 header.
+    Dds,
+}
+
+/// The ways that reading a texture container can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerError {
+    /// The file isn't a KTX, KTX2 or DDS file.
+    UnknownContainer,
+    /// The file ends before all of its data.
+    Truncated,
+    /// The file's header doesn't make sense.
+    Invalid(&'static str),
+    /// The file's format isn't one that this crate knows, such as
+    /// `"vkFormat 184"`.
+    UnknownFormat(String),
+    /// The file uses a feature that this crate can't read.
+    Unsupported(&'static str),
+    /// The file holds a different kind of texture than was asked for, such as
+    /// a cube map when making a 2D texture.
+    WrongShape,
+}
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerError::UnknownContainer => write!(f, "not a KTX, KTX2 or DDS file"),
+            ContainerError::Truncated => write!(f, "the file ends early"),
+            ContainerError::Invalid(what) => write!(f, "invalid header: {what}"),
+            ContainerError::UnknownFormat(format) => write!(f, "unknown format: {format}"),
+            ContainerError::Unsupported(what) => write!(f, "{what} aren't supported"),
+            ContainerError::WrongShape => {
+                write!(f, "the file doesn't hold that kind of texture")
+            }
+        }
+    }
+}
+impl std::error::Error for ContainerError {}
+
+/// A texture read from a container file, with all of its mip levels.
+///
+/// This borrows the images from the file's bytes, and checks that they're all
+/// there, but doesn't touch GL. Make a texture out of it with the builder of
+/// a texture type, such as `Texture2D::builder().from_container(&container)`.
+#[derive(Debug, Clone)]
+pub struct TextureContainer<'a> {
+    pub(crate) kind: ContainerKind,
+    pub(crate) internal_format: InternalFormat,
+    /// The format of the pixel data, or `None` if it's compressed.
+    pub(crate) pixels: Option<(PixelFormat, PixelType)>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) depth: u32,
+    pub(crate) layers: u32,
+    pub(crate) faces: u32,
+    pub(crate) is_array: bool,
+    /// The alignment of each row of uncompressed images.
+    pub(crate) row_alignment: usize,
+    /// The images of each level, by layer and then by face.
+    pub(crate) levels: Vec<Vec<&'a [u8]>>,
+}
+impl<'a> TextureContainer<'a> {
+    /// Reads a container from a file's bytes, working out the kind of file
+    /// from its first few bytes.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ContainerError> {
+        if bytes.starts_with(&KTX_IDENTIFIER) {
+            parse_ktx(bytes)
+        } else if bytes.starts_with(&KTX2_IDENTIFIER) {
+            parse_ktx2(bytes)
+        } else if bytes.starts_with(&DDS_MAGIC) {
+            parse_dds(bytes)
+        } else {
+            Err(ContainerError::UnknownContainer)
+        }
+    }
+
+    /// The kind of file this was read from.
+    pub fn kind(&self) -> ContainerKind {
+        self.kind
+    }
+
+    /// How the texels are stored.
+    pub fn internal_format(&self) -> InternalFormat {
+        self.internal_format
+    }
+
+    /// The format and type of the pixel data, or `None` if it's compressed.
+    pub fn pixel_format(&self) -> Option<(PixelFormat, PixelType)> {
+        self.pixels
+    }
+
+    /// The width of the base level in texels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the base level in texels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The depth of the base level in texels, which is `1` for anything but
+    /// 3D textures.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// The number of array layers, which is `1` for textures that aren't
+    /// arrays.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// The number of faces, which is `6` for cube maps and `1` otherwise.
+    pub fn faces(&self) -> u32 {
+        self.faces
+    }
+
+    /// If the file holds an array texture.
+    pub fn is_array(&self) -> bool {
+        self.is_array
+    }
+
+    /// The number of mip levels in the file, which is at least `1`.
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The `[width, height, depth]` of a mip level.
+    pub fn level_size(&self, level: usize) -> [u32; 3] {
+        let level = u32::try_from(level).unwrap_or(u32::MAX);
+        [self.width, self.height, self.depth].map(|n| n.checked_shr(level).unwrap_or(0).max(1))
+    }
+
+    /// The data of one image (one face of one layer) of a mip level.
+    pub fn image(&self, level: usize, layer: u32, face: u32) -> Option<&'a [u8]> {
+        if face >= self.faces {
+            return None;
+        }
+        let index = layer.checked_mul(self.faces)?.checked_add(face)?;
+        let index = usize::try_from(index).ok()?;
+        self.levels.get(level)?.get(index).copied()
+    }
+
+    /// The size in bytes of one image of a mip level.
+    pub(crate) fn image_len(&self, level: usize) -> usize {
+        let [width, height, depth] = self.level_size(level).map(|n| n as usize);
+        match (self.internal_format.block_size(), self.pixels) {
+            (Some(block), _) => width.div_ceil(4) * height.div_ceil(4) * depth * block,
+            (None, Some((format, ty))) => {
+                let row = align_up(width * pixel_size(format, ty), self.row_alignment);
+                row * height * depth
+            }
+            (None, None) => unreachable!("uncompressed containers have a pixel format"),
+        }
+    }
+}
+
+/// Checks that a texture's base level isn't empty or bigger than any GL
+/// implementation allows, which keeps the image sizes from overflowing.
+pub(crate) fn check_size(width: u32, height: u32, depth: u32) -> Result<(), ContainerError> {
+    const MAX_SIZE: u32 = 1 << 16;
+    if width == 0 {
+        Err(ContainerError::Invalid("the texture has no width"))
+    } else if width > MAX_SIZE || height > MAX_SIZE || depth > MAX_SIZE {
+        Err(ContainerError::Invalid("the texture is too big"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that a texture doesn't claim more mip levels than a texture of the
+/// biggest allowed size could have, before anything is allocated for them.
+pub(crate) fn check_levels(levels: u32) -> Result<(), ContainerError> {
+    const MAX_LEVELS: u32 = 32;
+    if levels > MAX_LEVELS {
+        Err(ContainerError::Invalid("there are too many mip levels"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Reads a little endian `u32` from `offset`.
+pub(crate) fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, ContainerError> {
+    let bytes = slice_at(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Reads a little endian `u64` from `offset`.
+pub(crate) fn u64_at(bytes: &[u8], offset: usize) -> Result<u64, ContainerError> {
+    let bytes = slice_at(bytes, offset, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Takes `len` bytes from `offset`.
+pub(crate) fn slice_at(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], ContainerError> {
+    offset
+        .checked_add(len)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or(ContainerError::Truncated)
+}
+
+impl<T> TextureBuilder<T> {
+    /// Makes a texture of type `ty` from a container, with the container's
+    /// mip levels, or generated ones if it only has the base level and isn't
+    /// compressed.
+    pub(crate) fn upload_container(
+        &self,
+        container: &TextureContainer<'_>,
+        ty: TextureType,
+    ) -> Result<(Texture, InternalFormat), TextureError> {
+        let flat = container.depth == 1 && container.faces == 1;
+        let fits = match ty {
+            TextureType::Texture2D => flat && !container.is_array,
+            TextureType::Texture2DArray => flat,
+            TextureType::Texture3D => container.faces == 1 && !container.is_array,
+            TextureType::CubeMap => {
+                container.depth == 1 && container.faces == 6 && !container.is_array
+            }
+        };
+        if !fits {
+            return Err(ContainerError::WrongShape.into());
+        }
+        let compressed = container.internal_format.is_compressed();
+        let internal_format =
+            self.internal_format
+                .filter(|_| !compressed)
+                .unwrap_or(if self.srgb {
+                    container.internal_format.to_srgb()
+                } else {
+                    container.internal_format
+                });
+        if !internal_format.is_supported() || (compressed && ty == TextureType::Texture3D) {
+            return Err(TextureError::UnsupportedFormat(internal_format));
+        }
+        let texture = Texture::new().ok_or(TextureError::Allocation)?;
+        let target = ty as GLenum;
+        texture.bind(ty);
+        self.apply_params(target);
+        self.apply_swizzle(target, None);
+        let levels = if self.mipmaps {
+            container.level_count()
+        } else {
+            1
+        };
+        let generate = self.mipmaps && levels == 1 && !compressed;
+        unsafe {
+            if !generate {
+                gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, levels as GLint - 1);
+            }
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, container.row_alignment as GLint);
+        }
+        for level in 0..levels {
+            let [width, height, depth] = container.level_size(level);
+            let images = &container.levels[level];
+            let upload = Upload {
+                level: level as GLint,
+                internal_format,
+                pixels: container.pixels,
+            };
+            match ty {
+                TextureType::Texture2D => upload.image_2d(target, [width, height], images[0]),
+                TextureType::CubeMap => {
+                    for (face, image) in CubeFace::ALL.into_iter().zip(images) {
+                        upload.image_2d(face as GLenum, [width, height], image);
+                    }
+                }
+                TextureType::Texture2DArray => {
+                    let data: Cow<'_, [u8]> = match images.as_slice() {
+                        [image] => Cow::Borrowed(image),
+                        images => Cow::Owned(images.concat()),
+                    };
+                    upload.image_3d(target, [width, height, container.layers], &data);
+                }
+                TextureType::Texture3D => {
+                    upload.image_3d(target, [width, height, depth], images[0])
+                }
+            }
+        }
+        if generate {
+            self.finish(target);
+        }
+        Ok((texture, internal_format))
+    }
+}
+
+/// What's common to uploading each image of a mip level.
+struct Upload {
+    level: GLint,
+    internal_format: InternalFormat,
+    pixels: Option<(PixelFormat, PixelType)>,
+}
+impl Upload {
+    fn image_2d(&self, target: GLenum, [width, height]: [u32; 2], data: &[u8]) {
+        let [width, height] = [width, height].map(|n| GLsizei::try_from(n).unwrap());
+        unsafe {
+            match self.pixels {
+                Some((format, ty)) => gl::TexImage2D(
+                    target,
+                    self.level,
+                    self.internal_format as GLint,
+                    width,
+                    height,
+                    0,
+                    format as GLenum,
+                    ty as GLenum,
+                    data.as_ptr().cast(),
+                ),
+                None => gl::CompressedTexImage2D(
+                    target,
+                    self.level,
+                    self.internal_format as GLenum,
+                    width,
+                    height,
+                    0,
+                    GLsizei::try_from(data.len()).unwrap(),
+                    data.as_ptr().cast(),
+                ),
+            }
+        }
+    }
+
+    fn image_3d(&self, target: GLenum, size: [u32; 3], data: &[u8]) {
+        let [width, height, depth] = size.map(|n| GLsizei::try_from(n).unwrap());
+        unsafe {
+            match self.pixels {
+                Some((format, ty)) => gl::TexImage3D(
+                    target,
+                    self.level,
+                    self.internal_format as GLint,
+                    width,
+                    height,
+                    depth,
+                    0,
+                    format as GLenum,
+                    ty as GLenum,
+                    data.as_ptr().cast(),
+                ),
+                None => gl::CompressedTexImage3D(
+                    target,
+                    self.level,
+                    self.internal_format as GLenum,
+                    width,
+                    height,
+                    depth,
+                    0,
+                    GLsizei::try_from(data.len()).unwrap(),
+                    data.as_ptr().cast(),
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_picks_the_kind_from_the_magic() {
+        assert_eq!(
+            TextureContainer::parse(b"\x89PNG\r\n\x1a\n").unwrap_err(),
+            ContainerError::UnknownContainer
+        );
+        assert_eq!(
+            TextureContainer::parse(&[]).unwrap_err(),
+            ContainerError::UnknownContainer
+        );
+        assert_eq!(
+            TextureContainer::parse(&KTX_IDENTIFIER).unwrap_err(),
+            ContainerError::Truncated
+        );
+        assert_eq!(
+            TextureContainer::parse(&KTX2_IDENTIFIER).unwrap_err(),
+            ContainerError::Truncated
+        );
+        assert_eq!(
+            TextureContainer::parse(&DDS_MAGIC).unwrap_err(),
+            ContainerError::Truncated
+        );
+    }
+}
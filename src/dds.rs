@@ -0,0 +1,346 @@
+//! Reading DDS files.
+
+use crate::container::{check_levels, check_size, slice_at, u32_at};
+use crate::{
+    ContainerError, ContainerKind, InternalFormat, PixelFormat, PixelType, TextureContainer,
+};
+
+/// The first bytes of every DDS file.
+pub(crate) const DDS_MAGIC: [u8; 4] = *b"DDS ";
+
+/// The size of the magic number and `DDS_HEADER`, where the DX10 header or
+/// the data starts.
+const HEADER_END: usize = 128;
+
+// `DDS_HEADER` flags.
+const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+const DDSD_DEPTH: u32 = 0x80_0000;
+
+// `DDS_PIXELFORMAT` flags.
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x2_0000;
+
+// `dwCaps2` flags.
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+
+// DX10 header values.
+const DIMENSION_TEXTURE3D: u32 = 4;
+const MISC_TEXTURECUBE: u32 = 0x4;
+
+type Format = (InternalFormat, Option<(PixelFormat, PixelType)>);
+
+/// Reads a DDS file, which describes its format with a FourCC code, bit
+/// masks, or a `DXGI_FORMAT` in its DX10 header.
+pub(crate) fn parse_dds(bytes: &[u8]) -> Result<TextureContainer<'_>, ContainerError> {
+    let field = |offset: usize| u32_at(bytes, offset);
+    let flags = field(8)?;
+    let (height, width) = (field(12)?.max(1), field(16)?);
+    let mut depth = if flags & DDSD_DEPTH != 0 {
+        field(24)?.max(1)
+    } else {
+        1
+    };
+    let levels = if flags & DDSD_MIPMAPCOUNT != 0 {
+        field(28)?.max(1)
+    } else {
+        1
+    };
+    let caps2 = field(112)?;
+    let pixel_flags = field(80)?;
+    let four_cc: [u8; 4] = slice_at(bytes, 84, 4)?.try_into().unwrap();
+
+    let mut data_start = HEADER_END;
+    let mut layers = 1;
+    let mut is_array = false;
+    let mut faces = 1;
+    let format = if pixel_flags & DDPF_FOURCC != 0 && &four_cc == b"DX10" {
+        let dxgi_format = field(HEADER_END)?;
+        let dimension = field(HEADER_END + 4)?;
+        let misc = field(HEADER_END + 8)?;
+        layers = field(HEADER_END + 12)?.max(1);
+        is_array = layers > 1;
+        if misc & MISC_TEXTURECUBE != 0 {
+            faces = 6;
+        }
+        if dimension != DIMENSION_TEXTURE3D {
+            depth = 1;
+        }
+        data_start += 20;
+        dxgi_format_info(dxgi_format)
+            .ok_or_else(|| ContainerError::UnknownFormat(format!("DXGI format {dxgi_format}")))?
+    } else {
+        if caps2 & DDSCAPS2_CUBEMAP != 0 {
+            if caps2 & DDSCAPS2_CUBEMAP_ALLFACES != DDSCAPS2_CUBEMAP_ALLFACES {
+                return Err(ContainerError::Unsupported(
+                    "cube maps without all six faces",
+                ));
+            }
+            faces = 6;
+        }
+        if caps2 & DDSCAPS2_VOLUME == 0 {
+            depth = 1;
+        }
+        legacy_format_info(bytes, pixel_flags, four_cc)?
+    };
+    check_size(width, height, depth)?;
+    check_levels(levels)?;
+
+    let (internal_format, pixels) = format;
+    let mut container = TextureContainer {
+        kind: ContainerKind::Dds,
+        internal_format,
+        pixels,
+        width,
+        height,
+        depth,
+        layers,
+        faces,
+        is_array,
+        row_alignment: 1,
+        levels: vec![Vec::new(); levels as usize],
+    };
+    // Unlike KTX, DDS files give each image's whole mip chain before moving
+    // on to the next image.
+    let mut offset = data_start;
+    for _ in 0..layers as usize * faces as usize {
+        for level in 0..levels as usize {
+            let len = container.image_len(level);
+            let image = slice_at(bytes, offset, len)?;
+            container.levels[level].push(image);
+            offset += len;
+        }
+    }
+    Ok(container)
+}
+
+/// The format described by a `DDS_PIXELFORMAT` without a DX10 header.
+fn legacy_format_info(
+    bytes: &[u8],
+    pixel_flags: u32,
+    four_cc: [u8; 4],
+) -> Result<Format, ContainerError> {
+    use InternalFormat as I;
+    use PixelFormat as F;
+    use PixelType as T;
+    let uncompressed = |internal, format, ty| Ok((internal, Some((format, ty))));
+    if pixel_flags & DDPF_FOURCC != 0 {
+        return match &four_cc {
+            b"DXT1" => Ok((I::Bc1Rgba, None)),
+            b"DXT2" | b"DXT3" => Ok((I::Bc2, None)),
+            b"DXT4" | b"DXT5" => Ok((I::Bc3, None)),
+            b"ATI1" | b"BC4U" => Ok((I::Bc4, None)),
+            b"BC4S" => Ok((I::Bc4Signed, None)),
+            b"ATI2" | b"BC5U" => Ok((I::Bc5, None)),
+            b"BC5S" => Ok((I::Bc5Signed, None)),
+            // Some tools write a `D3DFORMAT` number instead of a FourCC.
+            _ => match u32::from_le_bytes(four_cc) {
+                36 => uncompressed(I::Rgba16, F::Rgba, T::UnsignedShort),
+                111 => uncompressed(I::R16F, F::Red, T::HalfFloat),
+                112 => uncompressed(I::Rg16F, F::Rg, T::HalfFloat),
+                113 => uncompressed(I::Rgba16F, F::Rgba, T::HalfFloat),
+                114 => uncompressed(I::R32F, F::Red, T::Float),
+                115 => uncompressed(I::Rg32F, F::Rg, T::Float),
+                116 => uncompressed(I::Rgba32F, F::Rgba, T::Float),
+                _ => Err(ContainerError::UnknownFormat(format!(
+                    "FourCC {:?}",
+                    String::from_utf8_lossy(&four_cc)
+                ))),
+            },
+        };
+    }
+    let bit_count = u32_at(bytes, 88)?;
+    let masks = [
+        u32_at(bytes, 92)?,
+        u32_at(bytes, 96)?,
+        u32_at(bytes, 100)?,
+        u32_at(bytes, 104)?,
+    ];
+    let alpha = pixel_flags & DDPF_ALPHAPIXELS != 0;
+    let rgba = [0xFF, 0xFF00, 0xFF_0000];
+    let bgra = [0xFF_0000, 0xFF00, 0xFF];
+    let rgb_internal = if alpha { I::Rgba8 } else { I::Rgb8 };
+    match (pixel_flags & (DDPF_RGB | DDPF_LUMINANCE), bit_count) {
+        (DDPF_RGB, 32) if masks[..3] == rgba => {
+            uncompressed(rgb_internal, F::Rgba, T::UnsignedByte)
+        }
+        (DDPF_RGB, 32) if masks[..3] == bgra => {
+            uncompressed(rgb_internal, F::Bgra, T::UnsignedByte)
+        }
+        (DDPF_RGB, 24) if masks[..3] == rgba => uncompressed(I::Rgb8, F::Rgb, T::UnsignedByte),
+        (DDPF_RGB, 24) if masks[..3] == bgra => uncompressed(I::Rgb8, F::Bgr, T::UnsignedByte),
+        (DDPF_LUMINANCE, 8) => uncompressed(I::R8, F::Red, T::UnsignedByte),
+        (DDPF_LUMINANCE, 16) if alpha => uncompressed(I::Rg8, F::Rg, T::UnsignedByte),
+        (DDPF_LUMINANCE, 16) => uncompressed(I::R16, F::Red, T::UnsignedShort),
+        _ => Err(ContainerError::UnknownFormat(format!(
+            "{bit_count}-bit pixels with flags {pixel_flags:#X} and masks {masks:X?}"
+        ))),
+    }
+}
+
+/// The format described by a `DXGI_FORMAT`, for the formats that have a GL
+/// equivalent here.
+fn dxgi_format_info(dxgi_format: u32) -> Option<Format> {
+    use InternalFormat as I;
+    use PixelFormat as F;
+    use PixelType as T;
+    let uncompressed = |internal, format, ty| Some((internal, Some((format, ty))));
+    match dxgi_format {
+        2 => uncompressed(I::Rgba32F, F::Rgba, T::Float),
+        6 => uncompressed(I::Rgb32F, F::Rgb, T::Float),
+        10 => uncompressed(I::Rgba16F, F::Rgba, T::HalfFloat),
+        11 => uncompressed(I::Rgba16, F::Rgba, T::UnsignedShort),
+        16 => uncompressed(I::Rg32F, F::Rg, T::Float),
+        28 => uncompressed(I::Rgba8, F::Rgba, T::UnsignedByte),
+        29 => uncompressed(I::Srgb8Alpha8, F::Rgba, T::UnsignedByte),
+        34 => uncompressed(I::Rg16F, F::Rg, T::HalfFloat),
+        35 => uncompressed(I::Rg16, F::Rg, T::UnsignedShort),
+        41 => uncompressed(I::R32F, F::Red, T::Float),
+        49 => uncompressed(I::Rg8, F::Rg, T::UnsignedByte),
+        54 => uncompressed(I::R16F, F::Red, T::HalfFloat),
+        56 => uncompressed(I::R16, F::Red, T::UnsignedShort),
+        61 => uncompressed(I::R8, F::Red, T::UnsignedByte),
+        71 => Some((I::Bc1Rgba, None)),
+        72 => Some((I::Bc1SrgbAlpha, None)),
+        74 => Some((I::Bc2, None)),
+        75 => Some((I::Bc2Srgb, None)),
+        77 => Some((I::Bc3, None)),
+        78 => Some((I::Bc3Srgb, None)),
+        80 => Some((I::Bc4, None)),
+        81 => Some((I::Bc4Signed, None)),
+        83 => Some((I::Bc5, None)),
+        84 => Some((I::Bc5Signed, None)),
+        87 => uncompressed(I::Rgba8, F::Bgra, T::UnsignedByte),
+        91 => uncompressed(I::Srgb8Alpha8, F::Bgra, T::UnsignedByte),
+        95 => Some((I::Bc6HUnsigned, None)),
+        96 => Some((I::Bc6HSigned, None)),
+        98 => Some((I::Bc7, None)),
+        99 => Some((I::Bc7Srgb, None)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a DDS file with a DX10 header, from its `DDS_HEADER` flags, size
+    /// and mip count, and its `DXGI_FORMAT`, dimension, misc flags and array
+    /// size.
+    fn dx10(header: [u32; 4], dx10: [u32; 4], data: &[u8]) -> Vec<u8> {
+        let [flags, width, height, levels] = header;
+        let mut bytes = vec![0; HEADER_END];
+        bytes[..4].copy_from_slice(&DDS_MAGIC);
+        let mut put = |offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+        put(4, 124);
+        put(8, flags);
+        put(12, height);
+        put(16, width);
+        put(28, levels);
+        put(76, 32);
+        put(80, DDPF_FOURCC);
+        put(84, u32::from_le_bytes(*b"DX10"));
+        for value in dx10 {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    const BC1: u32 = 71;
+    const RGBA8: u32 = 28;
+    const TEXTURE2D: u32 = 3;
+
+    #[test]
+    fn dx10_array_with_mipmaps() {
+        // Each layer's whole mip chain comes before the next layer's. An 8x8
+        // BC1 image is four 8-byte blocks, and its 4x4 mip level is one.
+        let mut data = Vec::new();
+        for layer in 0..2 {
+            data.extend([layer * 2; 32]);
+            data.extend([layer * 2 + 1; 8]);
+        }
+        let bytes = dx10([DDSD_MIPMAPCOUNT, 8, 8, 2], [BC1, TEXTURE2D, 0, 2], &data);
+        let container = parse_dds(&bytes).unwrap();
+        assert_eq!(container.kind(), ContainerKind::Dds);
+        assert_eq!(container.internal_format(), InternalFormat::Bc1Rgba);
+        assert_eq!(container.pixel_format(), None);
+        assert!(container.is_array());
+        assert_eq!(container.layers(), 2);
+        assert_eq!(container.level_count(), 2);
+        assert_eq!(container.image(0, 0, 0), Some(&[0; 32][..]));
+        assert_eq!(container.image(1, 0, 0), Some(&[1; 8][..]));
+        assert_eq!(container.image(0, 1, 0), Some(&[2; 32][..]));
+        assert_eq!(container.image(1, 1, 0), Some(&[3; 8][..]));
+    }
+
+    #[test]
+    fn dx10_cube() {
+        let data: Vec<u8> = (0..6).flat_map(|face| [face; 4]).collect();
+        let bytes = dx10([0, 1, 1, 0], [RGBA8, TEXTURE2D, MISC_TEXTURECUBE, 1], &data);
+        let container = parse_dds(&bytes).unwrap();
+        assert_eq!(container.faces(), 6);
+        assert!(!container.is_array());
+        assert_eq!(container.level_count(), 1);
+        for face in 0..6 {
+            assert_eq!(container.image(0, 0, face), Some(&[face as u8; 4][..]));
+        }
+    }
+
+    #[test]
+    fn dds_rejects_bad_headers() {
+        let too_many_levels = dx10(
+            [DDSD_MIPMAPCOUNT, 1, 1, 1000],
+            [RGBA8, TEXTURE2D, 0, 1],
+            &[],
+        );
+        assert_eq!(
+            parse_dds(&too_many_levels).unwrap_err(),
+            ContainerError::Invalid("there are too many mip levels")
+        );
+        let no_width = dx10([0, 0, 1, 0], [RGBA8, TEXTURE2D, 0, 1], &[0; 4]);
+        assert!(matches!(
+            parse_dds(&no_width),
+            Err(ContainerError::Invalid(_))
+        ));
+        let unknown = dx10([0, 1, 1, 0], [1234, TEXTURE2D, 0, 1], &[0; 4]);
+        assert!(matches!(
+            parse_dds(&unknown),
+            Err(ContainerError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn dds_rejects_cubes_without_every_face() {
+        let mut bytes = dx10([0, 1, 1, 0], [RGBA8, TEXTURE2D, 0, 1], &[0; 24]);
+        // Without a DX10 header, the faces are given by `dwCaps2`.
+        bytes[84..88].copy_from_slice(b"DXT1");
+        bytes[112..116].copy_from_slice(&(DDSCAPS2_CUBEMAP | 0x400).to_le_bytes());
+        assert_eq!(
+            parse_dds(&bytes).unwrap_err(),
+            ContainerError::Unsupported("cube maps without all six faces")
+        );
+    }
+
+    #[test]
+    fn dds_rejects_truncated_files() {
+        let bytes = dx10(
+            [DDSD_MIPMAPCOUNT, 8, 8, 2],
+            [BC1, TEXTURE2D, 0, 1],
+            &[0; 40],
+        );
+        for len in [4, 100, HEADER_END, HEADER_END + 19, bytes.len() - 1] {
+            assert_eq!(
+                parse_dds(&bytes[..len]).unwrap_err(),
+                ContainerError::Truncated,
+                "cut to {len} bytes"
+            );
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Parsing of shader compiler info logs into line/column diagnostics.
+
+use core::fmt;
+
+/// How serious a compiler message is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The shader didn't compile because of this.
+    Error,
+    /// The shader compiled, but the driver thinks something is off.
+    Warning,
+    /// Anything else the driver decided to tell us.
+    Info,
+}
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        })
+    }
+}
+
+/// A single message out of a shader info log.
+///
+/// Drivers don't agree on a log format, so this understands the common ones
+/// (Mesa's `0:12(5): error: ...`, NVIDIA's `0(12) : error C0000: ...`, and the
+/// `ERROR: 0:12: ...` style used by AMD, Intel and Apple). Lines that don't
+/// match any of those are kept as a message without a location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the message is.
+    pub severity: Severity,
+    /// The 1-based line within the shader source, if the driver gave one.
+    pub line: Option<u32>,
+    /// The 1-based column within the line, if the driver gave one.
+    pub column: Option<u32>,
+    /// The message text, without the location prefix.
+    pub message: String,
+    /// The text of the source line the message points at.
+    pub source_line: Option<String>,
+}
+impl Diagnostic {
+    /// Splits an info log into diagnostics, looking up each line number in
+    /// `source` so the offending GLSL can be shown next to the message.
+    pub fn parse_log(log: &str, source: &str) -> Vec<Self> {
+        log.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                let mut d = Self::parse_line(l);
+                d.source_line = d
+                    .line
+                    .and_then(|n| source.lines().nth(n.checked_sub(1)? as usize))
+                    .map(|s| s.trim_end().to_string());
+                d
+            })
+            .collect()
+    }
+
+    fn parse_line(l: &str) -> Self {
+        parse_mesa(l)
+            .or_else(|| parse_nvidia(l))
+            .or_else(|| parse_prefixed(l))
+            .unwrap_or_else(|| Self {
+                severity: if l.to_ascii_lowercase().contains("error") {
+                    Severity::Error
+                } else {
+                    Severity::Info
+                },
+                line: None,
+                column: None,
+                message: l.to_string(),
+                source_line: None,
+            })
+    }
+}
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(col)) => write!(f, "line {line}, column {col}: ")?,
+            (Some(line), None) => write!(f, "line {line}: ")?,
+            _ => (),
+        }
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let (Some(line), Some(text)) = (self.line, &self.source_line) {
+            write!(f, "\n{line:>6} | {text}")?;
+            if let Some(col) = self.column.filter(|&c| c > 0) {
+                write!(f, "\n{:>6} | {:>width$}", "", "^", width = col as usize)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn severity_of(word: &str) -> Option<Severity> {
+    match word.trim().to_ascii_lowercase().as_str() {
+        "error" | "fatal error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        "info" | "note" => Some(Severity::Info),
+        _ => None,
+    }
+}
+
+/// Splits `"error C1008: message"` or `"error: message"` into the severity and
+/// the message.
+fn split_severity(rest: &str) -> Option<(Severity, String)> {
+    let (head, message) = rest.split_once(':')?;
+    let word = head.split_whitespace().next()?;
+    Some((severity_of(word)?, message.trim().to_string()))
+}
+
+/// `0:12(5): error: message`
+fn parse_mesa(l: &str) -> Option<Diagnostic> {
+    let (_string, rest) = l.split_once(':')?;
+    let (loc, rest) = rest.split_once("):")?;
+    let (line, col) = loc.split_once('(')?;
+    let (severity, message) = split_severity(rest)?;
+    Some(Diagnostic {
+        severity,
+        line: Some(line.trim().parse().ok()?),
+        column: Some(col.trim().parse().ok()?),
+        message,
+        source_line: None,
+    })
+}
+
+/// `0(12) : error C1008: message`
+fn parse_nvidia(l: &str) -> Option<Diagnostic> {
+    let (_string, rest) = l.split_once('(')?;
+    let (line, rest) = rest.split_once(')')?;
+    let rest = rest.trim_start().strip_prefix(':')?;
+    let (severity, message) = split_severity(rest)?;
+    Some(Diagnostic {
+        severity,
+        line: Some(line.trim().parse().ok()?),
+        column: None,
+        message,
+        source_line: None,
+    })
+}
+
+/// `ERROR: 0:12: message`
+fn parse_prefixed(l: &str) -> Option<Diagnostic> {
+    let (word, rest) = l.split_once(':')?;
+    let severity = severity_of(word)?;
+    let (_string, rest) = rest.split_once(':')?;
+    let (line, message) = rest.split_once(':')?;
+    Some(Diagnostic {
+        severity,
+        line: Some(line.trim().parse().ok()?),
+        column: None,
+        message: message.trim().to_string(),
+        source_line: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "#version 330 core\nvoid main() {\n  gl_Position = vec4(x);\n}\n";
+
+    #[test]
+    fn mesa_log() {
+        let log = "0:3(22): error: `x' undeclared\n0:2(1): warning: unused\n";
+        let diagnostics = Diagnostic::parse_log(log, SOURCE);
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    severity: Severity::Error,
+                    line: Some(3),
+                    column: Some(22),
+                    message: "`x' undeclared".to_string(),
+                    source_line: Some("  gl_Position = vec4(x);".to_string()),
+                },
+                Diagnostic {
+                    severity: Severity::Warning,
+                    line: Some(2),
+                    column: Some(1),
+                    message: "unused".to_string(),
+                    source_line: Some("void main() {".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn nvidia_log() {
+        let log = "0(3) : error C1008: undefined variable \"x\"";
+        let diagnostics = Diagnostic::parse_log(log, SOURCE);
+        assert_eq!(
+            diagnostics,
+            [Diagnostic {
+                severity: Severity::Error,
+                line: Some(3),
+                column: None,
+                message: "undefined variable \"x\"".to_string(),
+                source_line: Some("  gl_Position = vec4(x);".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn prefixed_log() {
+        let log = "ERROR: 0:1: '' : version '330' is not supported\n\
+                   WARNING: 0:2: something odd\n\
+                   ERROR: 1 compilation errors.  No code generated.";
+        let diagnostics = Diagnostic::parse_log(log, SOURCE);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].line, Some(1));
+        assert_eq!(diagnostics[0].column, None);
+        assert_eq!(
+            diagnostics[0].message,
+            "'' : version '330' is not supported"
+        );
+        assert_eq!(
+            diagnostics[0].source_line.as_deref(),
+            Some("#version 330 core")
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].line, Some(2));
+        // The summary line has no location, but is still an error.
+        assert_eq!(diagnostics[2].severity, Severity::Error);
+        assert_eq!(diagnostics[2].line, None);
+        assert_eq!(
+            diagnostics[2].message,
+            "ERROR: 1 compilation errors.  No code generated."
+        );
+    }
+
+    #[test]
+    fn lines_outside_the_source_have_no_source_line() {
+        let log = "0:99(1): error: past the end\n0:0(1): error: before the start";
+        let diagnostics = Diagnostic::parse_log(log, SOURCE);
+        assert_eq!(diagnostics[0].line, Some(99));
+        assert_eq!(diagnostics[0].source_line, None);
+        assert_eq!(diagnostics[1].line, Some(0));
+        assert_eq!(diagnostics[1].source_line, None);
+    }
+
+    #[test]
+    fn unknown_lines_are_kept_without_a_location() {
+        let log = "\n  Vertex info  \n-----------\n";
+        let diagnostics = Diagnostic::parse_log(log, SOURCE);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Severity::Info);
+        assert_eq!(diagnostics[0].line, None);
+        assert_eq!(diagnostics[0].message, "Vertex info");
+    }
+
+    #[test]
+    fn display_points_at_the_column() {
+        let diagnostics = Diagnostic::parse_log("0:3(22): error: `x' undeclared", SOURCE);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "line 3, column 22: error: `x' undeclared\n\
+             \x20    3 |   gl_Position = vec4(x);\n\
+             \x20      |                      ^"
+        );
+    }
+}
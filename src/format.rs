@@ -0,0 +1,570 @@
+//! The formats that texels are stored in, and that pixel data is given in.
+
+use crate::{gl_version, has_extension};
+use gl::types::{GLenum, GLint};
+use image::DynamicImage;
+
+// The S3TC (BC1 to BC3) formats only come from extensions, so the `gl`
+// bindings don't have them.
+const COMPRESSED_RGB_S3TC_DXT1: GLenum = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1: GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1: GLenum = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: GLenum = 0x8C4F;
+
+/// How a texture stores its texels on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InternalFormat {
+    /// One 8-bit normalized channel.
+    R8 = gl::R8 as isize,
+    /// Two 8-bit normalized channels.
+    Rg8 = gl::RG8 as isize,
+    /// Three 8-bit normalized channels.
+    Rgb8 = gl::RGB8 as isize,
+    /// Four 8-bit normalized channels.
+    Rgba8 = gl::RGBA8 as isize,
+    /// Three 8-bit sRGB encoded channels.
+    Srgb8 = gl::SRGB8 as isize,
+    /// Three 8-bit sRGB encoded channels, and an 8-bit linear alpha channel.
+    Srgb8Alpha8 = gl::SRGB8_ALPHA8 as isize,
+    /// One 16-bit normalized channel.
+    R16 = gl::R16 as isize,
+    /// Two 16-bit normalized channels.
+    Rg16 = gl::RG16 as isize,
+    /// Three 16-bit normalized channels.
+    Rgb16 = gl::RGB16 as isize,
+    /// Four 16-bit normalized channels.
+    Rgba16 = gl::RGBA16 as isize,
+    /// One 16-bit float channel.
+    R16F = gl::R16F as isize,
+    /// Two 16-bit float channels.
+    Rg16F = gl::RG16F as isize,
+    /// Three 16-bit float channels.
+    Rgb16F = gl::RGB16F as isize,
+    /// Four 16-bit float channels.
+    Rgba16F = gl::RGBA16F as isize,
+    /// One 32-bit float channel.
+    R32F = gl::R32F as isize,
+    /// Two 32-bit float channels.
+    Rg32F = gl::RG32F as isize,
+    /// Three 32-bit float channels.
+    Rgb32F = gl::RGB32F as isize,
+    /// Four 32-bit float channels.
+    Rgba32F = gl::RGBA32F as isize,
+    /// One 8-bit unsigned integer channel.
+    R8UI = gl::R8UI as isize,
+    /// One 32-bit unsigned integer channel.
+    R32UI = gl::R32UI as isize,
+    /// One 32-bit signed integer channel.
+    R32I = gl::R32I as isize,
+    /// A 16-bit depth value.
+    DepthComponent16 = gl::DEPTH_COMPONENT16 as isize,
+    /// A 24-bit depth value.
+    DepthComponent24 = gl::DEPTH_COMPONENT24 as isize,
+    /// A 32-bit float depth value.
+    DepthComponent32F = gl::DEPTH_COMPONENT32F as isize,
+    /// A 24-bit depth value and an 8-bit stencil value.
+    Depth24Stencil8 = gl::DEPTH24_STENCIL8 as isize,
+    /// BC1 (DXT1) compressed RGB.
+    Bc1Rgb = COMPRESSED_RGB_S3TC_DXT1 as isize,
+    /// BC1 (DXT1) compressed RGB, with 1-bit alpha.
+    Bc1Rgba = COMPRESSED_RGBA_S3TC_DXT1 as isize,
+    /// BC1 (DXT1) compressed sRGB.
+    Bc1Srgb = COMPRESSED_SRGB_S3TC_DXT1 as isize,
+    /// BC1 (DXT1) compressed sRGB, with 1-bit alpha.
+    Bc1SrgbAlpha = COMPRESSED_SRGB_ALPHA_S3TC_DXT1 as isize,
+    /// BC2 (DXT3) compressed RGBA.
+    Bc2 = COMPRESSED_RGBA_S3TC_DXT3 as isize,
+    /// BC2 (DXT3) compressed sRGB and alpha.
+    Bc2Srgb = COMPRESSED_SRGB_ALPHA_S3TC_DXT3 as isize,
+    /// BC3 (DXT5) compressed RGBA.
+    Bc3 = COMPRESSED_RGBA_S3TC_DXT5 as isize,
+    /// BC3 (DXT5) compressed sRGB and alpha.
+    Bc3Srgb = COMPRESSED_SRGB_ALPHA_S3TC_DXT5 as isize,
+    /// BC4 (RGTC1) compressed red.
+    Bc4 = gl::COMPRESSED_RED_RGTC1 as isize,
+    /// BC4 (RGTC1) compressed signed red.
+    Bc4Signed = gl::COMPRESSED_SIGNED_RED_RGTC1 as isize,
+    /// BC5 (RGTC2) compressed red and green.
+    Bc5 = gl::COMPRESSED_RG_RGTC2 as isize,
+    /// BC5 (RGTC2) compressed signed red and green.
+    Bc5Signed = gl::COMPRESSED_SIGNED_RG_RGTC2 as isize,
+    /// BC6H compressed unsigned float RGB.
+    Bc6HUnsigned = gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT as isize,
+    /// BC6H compressed signed float RGB.
+    Bc6HSigned = gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT as isize,
+    /// BC7 compressed RGBA.
+    Bc7 = gl::COMPRESSED_RGBA_BPTC_UNORM as isize,
+    /// BC7 compressed sRGB and alpha.
+    Bc7Srgb = gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM as isize,
+    /// ETC2 compressed RGB.
+    Etc2Rgb8 = gl::COMPRESSED_RGB8_ETC2 as isize,
+    /// ETC2 compressed sRGB.
+    Etc2Srgb8 = gl::COMPRESSED_SRGB8_ETC2 as isize,
+    /// ETC2 compressed RGB, with 1-bit alpha.
+    Etc2Rgb8A1 = gl::COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2 as isize,
+    /// ETC2 compressed sRGB, with 1-bit alpha.
+    Etc2Srgb8A1 = gl::COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2 as isize,
+    /// ETC2 compressed RGB, with EAC compressed alpha.
+    Etc2Rgba8 = gl::COMPRESSED_RGBA8_ETC2_EAC as isize,
+    /// ETC2 compressed sRGB, with EAC compressed alpha.
+    Etc2Srgb8Alpha8 = gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC as isize,
+    /// EAC compressed red.
+    EacR11 = gl::COMPRESSED_R11_EAC as isize,
+    /// EAC compressed signed red.
+    EacR11Signed = gl::COMPRESSED_SIGNED_R11_EAC as isize,
+    /// EAC compressed red and green.
+    EacRg11 = gl::COMPRESSED_RG11_EAC as isize,
+    /// EAC compressed signed red and green.
+    EacRg11Signed = gl::COMPRESSED_SIGNED_RG11_EAC as isize,
+}
+
+impl InternalFormat {
+    /// Every format, for looking one up by its GL enum.
+    const ALL: [InternalFormat; 51] = {
+        use InternalFormat::*;
+        [
+            R8,
+            Rg8,
+            Rgb8,
+            Rgba8,
+            Srgb8,
+            Srgb8Alpha8,
+            R16,
+            Rg16,
+            Rgb16,
+            Rgba16,
+            R16F,
+            Rg16F,
+            Rgb16F,
+            Rgba16F,
+            R32F,
+            Rg32F,
+            Rgb32F,
+            Rgba32F,
+            R8UI,
+            R32UI,
+            R32I,
+            DepthComponent16,
+            DepthComponent24,
+            DepthComponent32F,
+            Depth24Stencil8,
+            Bc1Rgb,
+            Bc1Rgba,
+            Bc1Srgb,
+            Bc1SrgbAlpha,
+            Bc2,
+            Bc2Srgb,
+            Bc3,
+            Bc3Srgb,
+            Bc4,
+            Bc4Signed,
+            Bc5,
+            Bc5Signed,
+            Bc6HUnsigned,
+            Bc6HSigned,
+            Bc7,
+            Bc7Srgb,
+            Etc2Rgb8,
+            Etc2Srgb8,
+            Etc2Rgb8A1,
+            Etc2Srgb8A1,
+            Etc2Rgba8,
+            Etc2Srgb8Alpha8,
+            EacR11,
+            EacR11Signed,
+            EacRg11,
+            EacRg11Signed,
+        ]
+    };
+
+    /// The format with the given GL enum, if it's one of ours.
+    pub(crate) fn from_raw(raw: GLenum) -> Option<Self> {
+        Self::ALL.into_iter().find(|&f| f as GLenum == raw)
+    }
+
+    /// The sRGB encoded version of this format, if there is one.
+    ///
+    /// Only `Rgb8`, `Rgba8` and the compressed color formats have one; other
+    /// formats are given back as-is.
+    pub fn to_srgb(self) -> Self {
+        use InternalFormat as I;
+        match self {
+            I::Rgb8 => I::Srgb8,
+            I::Rgba8 => I::Srgb8Alpha8,
+            I::Bc1Rgb => I::Bc1Srgb,
+            I::Bc1Rgba => I::Bc1SrgbAlpha,
+            I::Bc2 => I::Bc2Srgb,
+            I::Bc3 => I::Bc3Srgb,
+            I::Bc7 => I::Bc7Srgb,
+            I::Etc2Rgb8 => I::Etc2Srgb8,
+            I::Etc2Rgb8A1 => I::Etc2Srgb8A1,
+            I::Etc2Rgba8 => I::Etc2Srgb8Alpha8,
+            other => other,
+        }
+    }
+
+    /// If the color channels are sRGB encoded, so that GL converts them to
+    /// linear values when sampling.
+    pub fn is_srgb(self) -> bool {
+        use InternalFormat as I;
+        matches!(
+            self,
+            I::Srgb8
+                | I::Srgb8Alpha8
+                | I::Bc1Srgb
+                | I::Bc1SrgbAlpha
+                | I::Bc2Srgb
+                | I::Bc3Srgb
+                | I::Bc7Srgb
+                | I::Etc2Srgb8
+                | I::Etc2Srgb8A1
+                | I::Etc2Srgb8Alpha8
+        )
+    }
+
+    /// The size in bytes of each 4 by 4 block of texels, for block compressed
+    /// formats, or `None` for formats that aren't compressed.
+    pub fn block_size(self) -> Option<usize> {
+        use InternalFormat as I;
+        match self {
+            I::Bc1Rgb
+            | I::Bc1Rgba
+            | I::Bc1Srgb
+            | I::Bc1SrgbAlpha
+            | I::Bc4
+            | I::Bc4Signed
+            | I::Etc2Rgb8
+            | I::Etc2Srgb8
+            | I::Etc2Rgb8A1
+            | I::Etc2Srgb8A1
+            | I::EacR11
+            | I::EacR11Signed => Some(8),
+            I::Bc2
+            | I::Bc2Srgb
+            | I::Bc3
+            | I::Bc3Srgb
+            | I::Bc5
+            | I::Bc5Signed
+            | I::Bc6HUnsigned
+            | I::Bc6HSigned
+            | I::Bc7
+            | I::Bc7Srgb
+            | I::Etc2Rgba8
+            | I::Etc2Srgb8Alpha8
+            | I::EacRg11
+            | I::EacRg11Signed => Some(16),
+            _ => None,
+        }
+    }
+
+    /// If this is a block compressed format, which can only be given
+    /// compressed data.
+    pub fn is_compressed(self) -> bool {
+        self.block_size().is_some()
+    }
+
+    /// If the current context can make textures with this format.
+    ///
+    /// Every uncompressed format, and BC4 and BC5, are core in GL 3.3. BC1 to
+    /// BC3 need the S3TC extension (and an sRGB extension for their sRGB
+    /// versions), BC6H and BC7 need GL 4.2 or the BPTC extension, and ETC2
+    /// and EAC need GL 4.3 or the ES 3 compatibility extension.
+    pub fn is_supported(self) -> bool {
+        use InternalFormat as I;
+        let s3tc = || has_extension("GL_EXT_texture_compression_s3tc");
+        match self {
+            I::Bc1Rgb | I::Bc1Rgba | I::Bc2 | I::Bc3 => s3tc(),
+            I::Bc1Srgb | I::Bc1SrgbAlpha | I::Bc2Srgb | I::Bc3Srgb => {
+                (s3tc() && has_extension("GL_EXT_texture_sRGB"))
+                    || has_extension("GL_EXT_texture_compression_s3tc_srgb")
+            }
+            I::Bc6HUnsigned | I::Bc6HSigned | I::Bc7 | I::Bc7Srgb => {
+                gl_version().at_least(4, 2) || has_extension("GL_ARB_texture_compression_bptc")
+            }
+            I::Etc2Rgb8
+            | I::Etc2Srgb8
+            | I::Etc2Rgb8A1
+            | I::Etc2Srgb8A1
+            | I::Etc2Rgba8
+            | I::Etc2Srgb8Alpha8
+            | I::EacR11
+            | I::EacR11Signed
+            | I::EacRg11
+            | I::EacRg11Signed => {
+                gl_version().at_least(4, 3) || has_extension("GL_ARB_ES3_compatibility")
+            }
+            _ => true,
+        }
+    }
+
+    /// A pixel format and type that can be used with this format when there's
+    /// no pixel data to give, such as when just making room for an image.
+    ///
+    /// Compressed formats give the channels that they decompress to.
+    pub fn compatible_pixels(self) -> (PixelFormat, PixelType) {
+        use InternalFormat as I;
+        match self {
+            I::R8 | I::R16 | I::R16F | I::R32F => (PixelFormat::Red, PixelType::Float),
+            I::Rg8 | I::Rg16 | I::Rg16F | I::Rg32F => (PixelFormat::Rg, PixelType::Float),
+            I::Rgb8 | I::Srgb8 | I::Rgb16 | I::Rgb16F | I::Rgb32F => {
+                (PixelFormat::Rgb, PixelType::Float)
+            }
+            I::Rgba8 | I::Srgb8Alpha8 | I::Rgba16 | I::Rgba16F | I::Rgba32F => {
+                (PixelFormat::Rgba, PixelType::Float)
+            }
+            I::R8UI | I::R32UI => (PixelFormat::RedInteger, PixelType::UnsignedInt),
+            I::R32I => (PixelFormat::RedInteger, PixelType::Int),
+            I::DepthComponent16 | I::DepthComponent24 | I::DepthComponent32F => {
+                (PixelFormat::DepthComponent, PixelType::Float)
+            }
+            I::Depth24Stencil8 => (PixelFormat::DepthStencil, PixelType::UnsignedInt24_8),
+            I::Bc4 | I::Bc4Signed | I::EacR11 | I::EacR11Signed => {
+                (PixelFormat::Red, PixelType::Float)
+            }
+            I::Bc5 | I::Bc5Signed | I::EacRg11 | I::EacRg11Signed => {
+                (PixelFormat::Rg, PixelType::Float)
+            }
+            I::Bc1Rgb
+            | I::Bc1Srgb
+            | I::Bc6HUnsigned
+            | I::Bc6HSigned
+            | I::Etc2Rgb8
+            | I::Etc2Srgb8 => (PixelFormat::Rgb, PixelType::Float),
+            I::Bc1Rgba
+            | I::Bc1SrgbAlpha
+            | I::Bc2
+            | I::Bc2Srgb
+            | I::Bc3
+            | I::Bc3Srgb
+            | I::Bc7
+            | I::Bc7Srgb
+            | I::Etc2Rgb8A1
+            | I::Etc2Srgb8A1
+            | I::Etc2Rgba8
+            | I::Etc2Srgb8Alpha8 => (PixelFormat::Rgba, PixelType::Float),
+        }
+    }
+}
+
+/// The channels that pixel data given to (or read from) GL has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    /// Just red.
+    Red = gl::RED as isize,
+    /// Red and green.
+    Rg = gl::RG as isize,
+    /// Red, green and blue.
+    Rgb = gl::RGB as isize,
+    /// Red, green, blue and alpha.
+    Rgba = gl::RGBA as isize,
+    /// Blue, green and red.
+    Bgr = gl::BGR as isize,
+    /// Blue, green, red and alpha.
+    Bgra = gl::BGRA as isize,
+    /// Just red, as an integer rather than a normalized value.
+    RedInteger = gl::RED_INTEGER as isize,
+    /// A depth value.
+    DepthComponent = gl::DEPTH_COMPONENT as isize,
+    /// A depth value and a stencil value.
+    DepthStencil = gl::DEPTH_STENCIL as isize,
+}
+impl PixelFormat {
+    /// The format with the given GL enum, if it's one of ours.
+    pub(crate) fn from_raw(raw: GLenum) -> Option<Self> {
+        use PixelFormat::*;
+        [
+            Red,
+            Rg,
+            Rgb,
+            Rgba,
+            Bgr,
+            Bgra,
+            RedInteger,
+            DepthComponent,
+            DepthStencil,
+        ]
+        .into_iter()
+        .find(|&f| f as GLenum == raw)
+    }
+
+    /// The number of channels.
+    pub fn channels(self) -> usize {
+        match self {
+            PixelFormat::Red | PixelFormat::RedInteger | PixelFormat::DepthComponent => 1,
+            PixelFormat::Rg | PixelFormat::DepthStencil => 2,
+            PixelFormat::Rgb | PixelFormat::Bgr => 3,
+            PixelFormat::Rgba | PixelFormat::Bgra => 4,
+        }
+    }
+
+    /// The internal format that usually goes with pixel data of this format and
+    /// type, such as `Rgba8` for `Rgba` bytes.
+    pub fn default_internal_format(self, ty: PixelType) -> InternalFormat {
+        use InternalFormat as I;
+        let float = matches!(ty, PixelType::Float);
+        let half = matches!(ty, PixelType::HalfFloat);
+        let wide = matches!(ty, PixelType::UnsignedShort | PixelType::Short);
+        match self {
+            PixelFormat::Red if float => I::R32F,
+            PixelFormat::Red if half => I::R16F,
+            PixelFormat::Red if wide => I::R16,
+            PixelFormat::Red => I::R8,
+            PixelFormat::Rg if float => I::Rg32F,
+            PixelFormat::Rg if half => I::Rg16F,
+            PixelFormat::Rg if wide => I::Rg16,
+            PixelFormat::Rg => I::Rg8,
+            PixelFormat::Rgb | PixelFormat::Bgr if float => I::Rgb32F,
+            PixelFormat::Rgb | PixelFormat::Bgr if half => I::Rgb16F,
+            PixelFormat::Rgb | PixelFormat::Bgr if wide => I::Rgb16,
+            PixelFormat::Rgb | PixelFormat::Bgr => I::Rgb8,
+            PixelFormat::Rgba | PixelFormat::Bgra if float => I::Rgba32F,
+            PixelFormat::Rgba | PixelFormat::Bgra if half => I::Rgba16F,
+            PixelFormat::Rgba | PixelFormat::Bgra if wide => I::Rgba16,
+            PixelFormat::Rgba | PixelFormat::Bgra => I::Rgba8,
+            PixelFormat::RedInteger if matches!(ty, PixelType::Int) => I::R32I,
+            PixelFormat::RedInteger if matches!(ty, PixelType::UnsignedInt) => I::R32UI,
+            PixelFormat::RedInteger => I::R8UI,
+            PixelFormat::DepthComponent if float => I::DepthComponent32F,
+            PixelFormat::DepthComponent => I::DepthComponent24,
+            PixelFormat::DepthStencil => I::Depth24Stencil8,
+        }
+    }
+}
+
+/// The type of each channel in pixel data given to (or read from) GL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelType {
+    /// `u8`
+    UnsignedByte = gl::UNSIGNED_BYTE as isize,
+    /// `i8`
+    Byte = gl::BYTE as isize,
+    /// `u16`
+    UnsignedShort = gl::UNSIGNED_SHORT as isize,
+    /// `i16`
+    Short = gl::SHORT as isize,
+    /// `u32`
+    UnsignedInt = gl::UNSIGNED_INT as isize,
+    /// `i32`
+    Int = gl::INT as isize,
+    /// A 16-bit float.
+    HalfFloat = gl::HALF_FLOAT as isize,
+    /// `f32`
+    Float = gl::FLOAT as isize,
+    /// A 24-bit depth value and an 8-bit stencil value packed into a `u32`.
+    UnsignedInt24_8 = gl::UNSIGNED_INT_24_8 as isize,
+}
+impl PixelType {
+    /// The type with the given GL enum, if it's one of ours.
+    pub(crate) fn from_raw(raw: GLenum) -> Option<Self> {
+        use PixelType::*;
+        [
+            UnsignedByte,
+            Byte,
+            UnsignedShort,
+            Short,
+            UnsignedInt,
+            Int,
+            HalfFloat,
+            Float,
+            UnsignedInt24_8,
+        ]
+        .into_iter()
+        .find(|&t| t as GLenum == raw)
+    }
+
+    /// The size of one channel in bytes.
+    ///
+    /// Packed types give the size of the whole pixel.
+    pub fn size(self) -> usize {
+        match self {
+            PixelType::UnsignedByte | PixelType::Byte => 1,
+            PixelType::UnsignedShort | PixelType::Short | PixelType::HalfFloat => 2,
+            PixelType::UnsignedInt
+            | PixelType::Int
+            | PixelType::Float
+            | PixelType::UnsignedInt24_8 => 4,
+        }
+    }
+
+    fn is_packed(self) -> bool {
+        self == PixelType::UnsignedInt24_8
+    }
+}
+
+/// The size in bytes of one pixel with the given format and type.
+pub fn pixel_size(format: PixelFormat, ty: PixelType) -> usize {
+    if ty.is_packed() {
+        ty.size()
+    } else {
+        format.channels() * ty.size()
+    }
+}
+
+/// A slice of pixel data, along with what's in it.
+#[derive(Debug, Clone, Copy)]
+pub struct PixelData<'a> {
+    /// The channels of each pixel.
+    pub format: PixelFormat,
+    /// The type of each channel.
+    pub ty: PixelType,
+    /// The bytes, row by row from the bottom row up, with no padding between
+    /// rows.
+    pub bytes: &'a [u8],
+}
+impl<'a> PixelData<'a> {
+    /// Describes pixel data.
+    pub fn new(format: PixelFormat, ty: PixelType, bytes: &'a [u8]) -> Self {
+        Self { format, ty, bytes }
+    }
+
+    /// Describes the pixels of an image, keeping each channel's full depth.
+    ///
+    /// Grayscale images become `Red` (or `Rg` with alpha) data, which needs a
+    /// [swizzle](crate::TextureBuilder::swizzle) to look gray. Gives `None` for
+    /// image types that GL has no matching format for.
+    pub fn from_image(image: &'a DynamicImage) -> Option<Self> {
+        let (format, ty) = match image {
+            DynamicImage::ImageLuma8(_) => (PixelFormat::Red, PixelType::UnsignedByte),
+            DynamicImage::ImageLumaA8(_) => (PixelFormat::Rg, PixelType::UnsignedByte),
+            DynamicImage::ImageRgb8(_) => (PixelFormat::Rgb, PixelType::UnsignedByte),
+            DynamicImage::ImageRgba8(_) => (PixelFormat::Rgba, PixelType::UnsignedByte),
+            DynamicImage::ImageLuma16(_) => (PixelFormat::Red, PixelType::UnsignedShort),
+            DynamicImage::ImageLumaA16(_) => (PixelFormat::Rg, PixelType::UnsignedShort),
+            DynamicImage::ImageRgb16(_) => (PixelFormat::Rgb, PixelType::UnsignedShort),
+            DynamicImage::ImageRgba16(_) => (PixelFormat::Rgba, PixelType::UnsignedShort),
+            DynamicImage::ImageRgb32F(_) => (PixelFormat::Rgb, PixelType::Float),
+            DynamicImage::ImageRgba32F(_) => (PixelFormat::Rgba, PixelType::Float),
+            _ => return None,
+        };
+        Some(Self::new(format, ty, image.as_bytes()))
+    }
+
+    /// The number of bytes in one row of an image of the given width.
+    pub fn row_len(&self, width: u32) -> usize {
+        pixel_size(self.format, self.ty) * width as usize
+    }
+
+    /// The number of bytes needed for an image of the given size.
+    pub fn required_len(&self, width: u32, height: u32, depth: u32) -> usize {
+        self.row_len(width) * height as usize * depth as usize
+    }
+
+    /// Sets `GL_UNPACK_ALIGNMENT` so that GL reads rows of the given width
+    /// without any padding.
+    ///
+    /// GL assumes 4-byte aligned rows by default, which skews images with
+    /// rows such as 3 bytes per pixel times an odd width. This uses the
+    /// largest alignment that the rows happen to have.
+    pub fn set_unpack_alignment(&self, width: u32) {
+        let row = self.row_len(width);
+        let alignment: GLint = [8, 4, 2]
+            .into_iter()
+            .find(|&a| row.is_multiple_of(a as usize))
+            .unwrap_or(1);
+        unsafe { gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment) };
+    }
+}
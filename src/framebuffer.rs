@@ -0,0 +1,117 @@
+//! Framebuffer and renderbuffer objects.
+
+use gl::types::{GLenum, GLuint};
+use std::mem::ManuallyDrop;
+
+/// The targets that a framebuffer can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramebufferType {
+    /// Binds for both drawing and reading.
+    Framebuffer = gl::FRAMEBUFFER as isize,
+    /// Binds only as the destination of drawing and clearing.
+    Draw = gl::DRAW_FRAMEBUFFER as isize,
+    /// Binds only as the source of reads and blits.
+    Read = gl::READ_FRAMEBUFFER as isize,
+}
+
+/// Basic wrapper for a [Framebuffer
+/// Object](https://www.khronos.org/opengl/wiki/Framebuffer_Object).
+///
+/// The framebuffer is deleted when this value is dropped.
+pub struct Framebuffer(GLuint);
+impl Framebuffer {
+    /// Makes a new framebuffer.
+    pub fn new() -> Option<Self> {
+        let mut fbo = 0;
+        unsafe { gl::GenFramebuffers(1, &mut fbo) };
+        if fbo != 0 {
+            Some(Self(fbo))
+        } else {
+            None
+        }
+    }
+
+    /// Bind this framebuffer to the given target.
+    pub fn bind(&self, ty: FramebufferType) {
+        unsafe { gl::BindFramebuffer(ty as GLenum, self.0) }
+    }
+
+    /// Binds the default framebuffer (the window) to the given target.
+    pub fn clear_binding(ty: FramebufferType) {
+        unsafe { gl::BindFramebuffer(ty as GLenum, 0) }
+    }
+
+    /// The raw name of this framebuffer.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the framebuffer without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a framebuffer name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a framebuffer that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+}
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteFramebuffers(1, &self.0) }
+    }
+}
+
+/// Basic wrapper for a [Renderbuffer
+/// Object](https://www.khronos.org/opengl/wiki/Renderbuffer_Object).
+///
+/// The renderbuffer is deleted when this value is dropped.
+pub struct Renderbuffer(GLuint);
+impl Renderbuffer {
+    /// Makes a new renderbuffer.
+    pub fn new() -> Option<Self> {
+        let mut rbo = 0;
+        unsafe { gl::GenRenderbuffers(1, &mut rbo) };
+        if rbo != 0 {
+            Some(Self(rbo))
+        } else {
+            None
+        }
+    }
+
+    /// Bind this as the current renderbuffer.
+    pub fn bind(&self) {
+        unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, self.0) }
+    }
+
+    /// Clear the current renderbuffer binding.
+    pub fn clear_binding() {
+        unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, 0) }
+    }
+
+    /// The raw name of this renderbuffer.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the renderbuffer without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a renderbuffer name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a renderbuffer that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+}
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteRenderbuffers(1, &self.0) }
+    }
+}
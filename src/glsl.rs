@@ -0,0 +1,186 @@
+//! The data types that GL reports for shader inputs and uniforms.
+
+use core::fmt;
+use gl::types::GLenum;
+
+/// The scalar that a GLSL type is built out of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScalarType {
+    /// `float`, `vec*`, `mat*`
+    Float,
+    /// `double`, `dvec*`, `dmat*`
+    Double,
+    /// `int`, `ivec*`
+    Int,
+    /// `uint`, `uvec*`
+    UInt,
+    /// `bool`, `bvec*`
+    Bool,
+}
+
+/// A GLSL data type, as reported by `glGetActiveUniform`,
+/// `glGetActiveAttrib` and friends.
+///
+/// This is just the raw `GLenum` with some helpers attached, so any type that
+/// doesn't have a named constant here still round-trips fine.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlslType(pub GLenum);
+
+macro_rules! glsl_types {
+    ($($konst:ident = $gl:ident, $name:literal;)*) => {
+        impl GlslType {
+            $(
+                #[doc = concat!("`", $name, "`")]
+                pub const $konst: Self = Self(gl::$gl);
+            )*
+
+            /// The name of the type as it's written in GLSL.
+            ///
+            /// Types that aren't known give `"unknown"`.
+            pub fn name(self) -> &'static str {
+                match self.0 {
+                    $(gl::$gl => $name,)*
+                    _ => "unknown",
+                }
+            }
+        }
+    };
+}
+
+glsl_types! {
+    FLOAT = FLOAT, "float";
+    VEC2 = FLOAT_VEC2, "vec2";
+    VEC3 = FLOAT_VEC3, "vec3";
+    VEC4 = FLOAT_VEC4, "vec4";
+    DOUBLE = DOUBLE, "double";
+    DVEC2 = DOUBLE_VEC2, "dvec2";
+    DVEC3 = DOUBLE_VEC3, "dvec3";
+    DVEC4 = DOUBLE_VEC4, "dvec4";
+    INT = INT, "int";
+    IVEC2 = INT_VEC2, "ivec2";
+    IVEC3 = INT_VEC3, "ivec3";
+    IVEC4 = INT_VEC4, "ivec4";
+    UINT = UNSIGNED_INT, "uint";
+    UVEC2 = UNSIGNED_INT_VEC2, "uvec2";
+    UVEC3 = UNSIGNED_INT_VEC3, "uvec3";
+    UVEC4 = UNSIGNED_INT_VEC4, "uvec4";
+    BOOL = BOOL, "bool";
+    BVEC2 = BOOL_VEC2, "bvec2";
+    BVEC3 = BOOL_VEC3, "bvec3";
+    BVEC4 = BOOL_VEC4, "bvec4";
+    MAT2 = FLOAT_MAT2, "mat2";
+    MAT3 = FLOAT_MAT3, "mat3";
+    MAT4 = FLOAT_MAT4, "mat4";
+    MAT2X3 = FLOAT_MAT2x3, "mat2x3";
+    MAT2X4 = FLOAT_MAT2x4, "mat2x4";
+    MAT3X2 = FLOAT_MAT3x2, "mat3x2";
+    MAT3X4 = FLOAT_MAT3x4, "mat3x4";
+    MAT4X2 = FLOAT_MAT4x2, "mat4x2";
+    MAT4X3 = FLOAT_MAT4x3, "mat4x3";
+    SAMPLER_1D = SAMPLER_1D, "sampler1D";
+    SAMPLER_2D = SAMPLER_2D, "sampler2D";
+    SAMPLER_3D = SAMPLER_3D, "sampler3D";
+    SAMPLER_CUBE = SAMPLER_CUBE, "samplerCube";
+    SAMPLER_1D_SHADOW = SAMPLER_1D_SHADOW, "sampler1DShadow";
+    SAMPLER_2D_SHADOW = SAMPLER_2D_SHADOW, "sampler2DShadow";
+    SAMPLER_CUBE_SHADOW = SAMPLER_CUBE_SHADOW, "samplerCubeShadow";
+    SAMPLER_1D_ARRAY = SAMPLER_1D_ARRAY, "sampler1DArray";
+    SAMPLER_2D_ARRAY = SAMPLER_2D_ARRAY, "sampler2DArray";
+    SAMPLER_1D_ARRAY_SHADOW = SAMPLER_1D_ARRAY_SHADOW, "sampler1DArrayShadow";
+    SAMPLER_2D_ARRAY_SHADOW = SAMPLER_2D_ARRAY_SHADOW, "sampler2DArrayShadow";
+    SAMPLER_2D_MULTISAMPLE = SAMPLER_2D_MULTISAMPLE, "sampler2DMS";
+    SAMPLER_2D_MULTISAMPLE_ARRAY = SAMPLER_2D_MULTISAMPLE_ARRAY, "sampler2DMSArray";
+    SAMPLER_BUFFER = SAMPLER_BUFFER, "samplerBuffer";
+    SAMPLER_2D_RECT = SAMPLER_2D_RECT, "sampler2DRect";
+    SAMPLER_2D_RECT_SHADOW = SAMPLER_2D_RECT_SHADOW, "sampler2DRectShadow";
+    INT_SAMPLER_1D = INT_SAMPLER_1D, "isampler1D";
+    INT_SAMPLER_2D = INT_SAMPLER_2D, "isampler2D";
+    INT_SAMPLER_3D = INT_SAMPLER_3D, "isampler3D";
+    INT_SAMPLER_CUBE = INT_SAMPLER_CUBE, "isamplerCube";
+    INT_SAMPLER_1D_ARRAY = INT_SAMPLER_1D_ARRAY, "isampler1DArray";
+    INT_SAMPLER_2D_ARRAY = INT_SAMPLER_2D_ARRAY, "isampler2DArray";
+    INT_SAMPLER_2D_MULTISAMPLE = INT_SAMPLER_2D_MULTISAMPLE, "isampler2DMS";
+    INT_SAMPLER_2D_MULTISAMPLE_ARRAY = INT_SAMPLER_2D_MULTISAMPLE_ARRAY, "isampler2DMSArray";
+    INT_SAMPLER_BUFFER = INT_SAMPLER_BUFFER, "isamplerBuffer";
+    INT_SAMPLER_2D_RECT = INT_SAMPLER_2D_RECT, "isampler2DRect";
+    UINT_SAMPLER_1D = UNSIGNED_INT_SAMPLER_1D, "usampler1D";
+    UINT_SAMPLER_2D = UNSIGNED_INT_SAMPLER_2D, "usampler2D";
+    UINT_SAMPLER_3D = UNSIGNED_INT_SAMPLER_3D, "usampler3D";
+    UINT_SAMPLER_CUBE = UNSIGNED_INT_SAMPLER_CUBE, "usamplerCube";
+    UINT_SAMPLER_1D_ARRAY = UNSIGNED_INT_SAMPLER_1D_ARRAY, "usampler1DArray";
+    UINT_SAMPLER_2D_ARRAY = UNSIGNED_INT_SAMPLER_2D_ARRAY, "usampler2DArray";
+    UINT_SAMPLER_2D_MULTISAMPLE = UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE, "usampler2DMS";
+    UINT_SAMPLER_2D_MULTISAMPLE_ARRAY = UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY, "usampler2DMSArray";
+    UINT_SAMPLER_BUFFER = UNSIGNED_INT_SAMPLER_BUFFER, "usamplerBuffer";
+    UINT_SAMPLER_2D_RECT = UNSIGNED_INT_SAMPLER_2D_RECT, "usampler2DRect";
+}
+
+impl GlslType {
+    /// If this is one of the sampler types, which are set with a texture unit.
+    pub fn is_sampler(self) -> bool {
+        let name = self.name();
+        name.starts_with("sampler") || name.starts_with("isampler") || name.starts_with("usampler")
+    }
+
+    /// The scalar the type is made of, or `None` for samplers and unknown types.
+    pub fn scalar(self) -> Option<ScalarType> {
+        let name = self.name();
+        if self.is_sampler() || name == "unknown" {
+            None
+        } else if name.starts_with("float") || name.starts_with("vec") || name.starts_with("mat") {
+            Some(ScalarType::Float)
+        } else if name.starts_with('d') {
+            Some(ScalarType::Double)
+        } else if name.starts_with('i') {
+            Some(ScalarType::Int)
+        } else if name.starts_with('u') {
+            Some(ScalarType::UInt)
+        } else {
+            Some(ScalarType::Bool)
+        }
+    }
+
+    /// The number of columns and rows, `(1, n)` for scalars and vectors.
+    ///
+    /// Samplers and unknown types are `(1, 1)`.
+    pub fn dimensions(self) -> (u32, u32) {
+        let name = self.name();
+        let digit = |c: Option<char>| c.and_then(|c| c.to_digit(10));
+        if let Some(shape) = name
+            .strip_prefix("mat")
+            .or_else(|| name.strip_prefix("dmat"))
+        {
+            let mut chars = shape.chars();
+            let cols = digit(chars.next()).unwrap_or(1);
+            let rows = digit(chars.nth(1)).unwrap_or(cols);
+            (cols, rows)
+        } else if name.contains("vec") {
+            (1, digit(name.chars().last()).unwrap_or(1))
+        } else {
+            (1, 1)
+        }
+    }
+
+    /// The total number of scalar components, such as 3 for `vec3` or 16 for
+    /// `mat4`.
+    pub fn components(self) -> u32 {
+        let (cols, rows) = self.dimensions();
+        cols * rows
+    }
+}
+impl fmt::Debug for GlslType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            "unknown" => write!(f, "GlslType({:#X})", self.0),
+            name => write!(f, "GlslType({name})"),
+        }
+    }
+}
+impl fmt::Display for GlslType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            "unknown" => write!(f, "unknown type {:#X}", self.0),
+            name => f.write_str(name),
+        }
+    }
+}
@@ -0,0 +1,431 @@
+//! Making a GL context with no window, for rendering offscreen.
+//!
+//! This needs the `headless` feature, and EGL (such as Mesa's `libEGL`, which
+//! can draw with the `llvmpipe` software rasterizer on machines with no GPU).
+
+use crate::{gl_version, has_extension, print_debug_message, Framebuffer, FramebufferType};
+use crate::{InternalFormat, Renderbuffer};
+use gl::types::{GLenum, GLsizei};
+use image::RgbaImage;
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::ptr::{null, null_mut};
+
+/// The few parts of EGL that are needed to make a context.
+#[allow(non_snake_case, non_camel_case_types)]
+mod egl {
+    use std::ffi::{c_char, c_void};
+
+    pub type EGLBoolean = u32;
+    pub type EGLenum = u32;
+    pub type EGLint = i32;
+    pub type EGLDisplay = *mut c_void;
+    pub type EGLConfig = *mut c_void;
+    pub type EGLContext = *mut c_void;
+    pub type EGLSurface = *mut c_void;
+
+    pub const FALSE: EGLBoolean = 0;
+    pub const NONE: EGLint = 0x3038;
+    pub const EXTENSIONS: EGLint = 0x3055;
+    pub const ALPHA_SIZE: EGLint = 0x3021;
+    pub const BLUE_SIZE: EGLint = 0x3022;
+    pub const GREEN_SIZE: EGLint = 0x3023;
+    pub const RED_SIZE: EGLint = 0x3024;
+    pub const SURFACE_TYPE: EGLint = 0x3033;
+    pub const RENDERABLE_TYPE: EGLint = 0x3040;
+    pub const HEIGHT: EGLint = 0x3056;
+    pub const WIDTH: EGLint = 0x3057;
+    pub const PBUFFER_BIT: EGLint = 0x0001;
+    pub const OPENGL_BIT: EGLint = 0x0008;
+    pub const OPENGL_API: EGLenum = 0x30A2;
+    pub const CONTEXT_MAJOR_VERSION: EGLint = 0x3098;
+    pub const CONTEXT_MINOR_VERSION: EGLint = 0x30FB;
+    pub const CONTEXT_OPENGL_PROFILE_MASK: EGLint = 0x30FD;
+    pub const CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 0x0001;
+    pub const CONTEXT_OPENGL_DEBUG: EGLint = 0x31B0;
+    pub const CONTEXT_FLAGS_KHR: EGLint = 0x30FC;
+    pub const CONTEXT_OPENGL_DEBUG_BIT_KHR: EGLint = 0x0001;
+    pub const PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+
+    pub type GetPlatformDisplayEXT =
+        unsafe extern "C" fn(EGLenum, *mut c_void, *const EGLint) -> EGLDisplay;
+
+    #[link(name = "EGL")]
+    extern "C" {
+        pub fn eglGetError() -> EGLint;
+        pub fn eglGetProcAddress(name: *const c_char) -> *const c_void;
+        pub fn eglQueryString(display: EGLDisplay, name: EGLint) -> *const c_char;
+        pub fn eglGetDisplay(native: *mut c_void) -> EGLDisplay;
+        pub fn eglInitialize(
+            display: EGLDisplay,
+            major: *mut EGLint,
+            minor: *mut EGLint,
+        ) -> EGLBoolean;
+        pub fn eglTerminate(display: EGLDisplay) -> EGLBoolean;
+        pub fn eglBindAPI(api: EGLenum) -> EGLBoolean;
+        pub fn eglChooseConfig(
+            display: EGLDisplay,
+            attribs: *const EGLint,
+            configs: *mut EGLConfig,
+            config_size: EGLint,
+            num_config: *mut EGLint,
+        ) -> EGLBoolean;
+        pub fn eglCreateContext(
+            display: EGLDisplay,
+            config: EGLConfig,
+            share: EGLContext,
+            attribs: *const EGLint,
+        ) -> EGLContext;
+        pub fn eglDestroyContext(display: EGLDisplay, context: EGLContext) -> EGLBoolean;
+        pub fn eglCreatePbufferSurface(
+            display: EGLDisplay,
+            config: EGLConfig,
+            attribs: *const EGLint,
+        ) -> EGLSurface;
+        pub fn eglDestroySurface(display: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+        pub fn eglMakeCurrent(
+            display: EGLDisplay,
+            draw: EGLSurface,
+            read: EGLSurface,
+            context: EGLContext,
+        ) -> EGLBoolean;
+    }
+}
+
+/// Describes the last EGL error, for what was being done.
+fn egl_error(doing: &str) -> String {
+    let code = unsafe { egl::eglGetError() };
+    format!("Couldn't {doing} (EGL error 0x{code:04X})")
+}
+
+/// If a space-separated EGL extension string has an extension in it.
+fn has_egl_extension(display: egl::EGLDisplay, name: &str) -> bool {
+    let extensions = unsafe { egl::eglQueryString(display, egl::EXTENSIONS) };
+    if extensions.is_null() {
+        return false;
+    }
+    let extensions = unsafe { CStr::from_ptr(extensions) };
+    extensions
+        .to_string_lossy()
+        .split_ascii_whitespace()
+        .any(|e| e == name)
+}
+
+/// Builds a [`HeadlessContext`] with the chosen options.
+///
+/// The defaults are an 800 by 600 framebuffer with a GL 3.3 core context.
+#[derive(Debug, Clone)]
+pub struct HeadlessContextBuilder {
+    width: u32,
+    height: u32,
+    version: (u8, u8),
+    debug: bool,
+}
+impl Default for HeadlessContextBuilder {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            version: (3, 3),
+            debug: false,
+        }
+    }
+}
+impl HeadlessContextBuilder {
+    /// Sets the size of the framebuffer, in pixels.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the GL version to ask for. The context is always a core profile.
+    pub fn gl_version(mut self, major: u8, minor: u8) -> Self {
+        self.version = (major, minor);
+        self
+    }
+
+    /// Asks for a debug context, and prints the driver's debug messages to
+    /// standard error.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Starts EGL, makes the context and its framebuffer, and loads the GL
+    /// functions.
+    ///
+    /// Mesa's surfaceless platform is used if it's there, so that no display
+    /// server is needed at all. Otherwise, the default display is used with a
+    /// small pbuffer surface to make the context current with.
+    pub fn build(self) -> Result<HeadlessContext, String> {
+        unsafe {
+            let display = surfaceless_display().unwrap_or_else(|| egl::eglGetDisplay(null_mut()));
+            if display.is_null() {
+                return Err(egl_error("get an EGL display"));
+            }
+            let mut egl_version = (0, 0);
+            if egl::eglInitialize(display, &mut egl_version.0, &mut egl_version.1) == egl::FALSE {
+                return Err(egl_error("initialize EGL"));
+            }
+            // From here on, the context cleans up whatever has been made when
+            // it's dropped, including if this fails partway.
+            let mut context = HeadlessContext {
+                display,
+                context: null_mut(),
+                surface: null_mut(),
+                targets: None,
+                width: self.width,
+                height: self.height,
+            };
+
+            if egl::eglBindAPI(egl::OPENGL_API) == egl::FALSE {
+                return Err(egl_error("use desktop OpenGL through EGL"));
+            }
+
+            let surfaceless = has_egl_extension(display, "EGL_KHR_surfaceless_context");
+            let surface_type = if surfaceless { 0 } else { egl::PBUFFER_BIT };
+            let config_attribs = [
+                egl::SURFACE_TYPE,
+                surface_type,
+                egl::RENDERABLE_TYPE,
+                egl::OPENGL_BIT,
+                egl::RED_SIZE,
+                8,
+                egl::GREEN_SIZE,
+                8,
+                egl::BLUE_SIZE,
+                8,
+                egl::ALPHA_SIZE,
+                8,
+                egl::NONE,
+            ];
+            let mut config = null_mut();
+            let mut num_configs = 0;
+            if egl::eglChooseConfig(
+                display,
+                config_attribs.as_ptr(),
+                &mut config,
+                1,
+                &mut num_configs,
+            ) == egl::FALSE
+                || num_configs == 0
+            {
+                return Err(egl_error("find an EGL config for desktop OpenGL"));
+            }
+
+            let mut context_attribs = vec![
+                egl::CONTEXT_MAJOR_VERSION,
+                self.version.0.into(),
+                egl::CONTEXT_MINOR_VERSION,
+                self.version.1.into(),
+                egl::CONTEXT_OPENGL_PROFILE_MASK,
+                egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+            ];
+            if self.debug {
+                // The debug attribute is new in EGL 1.5, and 1.4 rejects it,
+                // so it needs the flag from `EGL_KHR_create_context` instead.
+                if egl_version >= (1, 5) {
+                    context_attribs.extend([egl::CONTEXT_OPENGL_DEBUG, 1]);
+                } else {
+                    context_attribs
+                        .extend([egl::CONTEXT_FLAGS_KHR, egl::CONTEXT_OPENGL_DEBUG_BIT_KHR]);
+                }
+            }
+            context_attribs.push(egl::NONE);
+            context.context =
+                egl::eglCreateContext(display, config, null_mut(), context_attribs.as_ptr());
+            if context.context.is_null() {
+                let (major, minor) = self.version;
+                return Err(egl_error(&format!(
+                    "make a GL {major}.{minor} core context"
+                )));
+            }
+
+            if !surfaceless {
+                let surface_attribs = [egl::WIDTH, 1, egl::HEIGHT, 1, egl::NONE];
+                context.surface =
+                    egl::eglCreatePbufferSurface(display, config, surface_attribs.as_ptr());
+                if context.surface.is_null() {
+                    return Err(egl_error("make a pbuffer surface"));
+                }
+            }
+            if egl::eglMakeCurrent(display, context.surface, context.surface, context.context)
+                == egl::FALSE
+            {
+                return Err(egl_error("make the context current"));
+            }
+
+            gl::load_with(|s| match CString::new(s) {
+                Ok(name) => egl::eglGetProcAddress(name.as_ptr()),
+                Err(_) => null(),
+            });
+
+            if self.debug && (gl_version().at_least(4, 3) || has_extension("GL_KHR_debug")) {
+                gl::Enable(gl::DEBUG_OUTPUT);
+                gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+                gl::DebugMessageCallback(Some(print_debug_message), null());
+            }
+
+            context.targets = Some(RenderTargets::new(self.width, self.height)?);
+            context.bind_framebuffer();
+            Ok(context)
+        }
+    }
+}
+
+/// Gets Mesa's surfaceless display, if EGL has it.
+unsafe fn surfaceless_display() -> Option<egl::EGLDisplay> {
+    // Client extensions are queried with no display.
+    if !has_egl_extension(null_mut(), "EGL_MESA_platform_surfaceless") {
+        return None;
+    }
+    let get_platform_display = egl::eglGetProcAddress(c"eglGetPlatformDisplayEXT".as_ptr());
+    if get_platform_display.is_null() {
+        return None;
+    }
+    let get_platform_display: egl::GetPlatformDisplayEXT =
+        std::mem::transmute(get_platform_display);
+    let attribs = [egl::NONE];
+    let display =
+        get_platform_display(egl::PLATFORM_SURFACELESS_MESA, null_mut(), attribs.as_ptr());
+    (!display.is_null()).then_some(display)
+}
+
+/// The framebuffer that a headless context draws to, in place of a window.
+struct RenderTargets {
+    framebuffer: Framebuffer,
+    _color: Renderbuffer,
+    _depth_stencil: Renderbuffer,
+}
+impl RenderTargets {
+    fn new(width: u32, height: u32) -> Result<Self, String> {
+        let framebuffer = Framebuffer::new().ok_or("Couldn't make a framebuffer")?;
+        framebuffer.bind(FramebufferType::Framebuffer);
+        let attach = |format: InternalFormat, attachment: GLenum| {
+            let renderbuffer = Renderbuffer::new().ok_or("Couldn't make a renderbuffer")?;
+            renderbuffer.bind();
+            unsafe {
+                gl::RenderbufferStorage(
+                    gl::RENDERBUFFER,
+                    format as GLenum,
+                    width as GLsizei,
+                    height as GLsizei,
+                );
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    attachment,
+                    gl::RENDERBUFFER,
+                    renderbuffer.id(),
+                );
+            }
+            Ok::<_, String>(renderbuffer)
+        };
+        let color = attach(InternalFormat::Rgba8, gl::COLOR_ATTACHMENT0)?;
+        let depth_stencil = attach(
+            InternalFormat::Depth24Stencil8,
+            gl::DEPTH_STENCIL_ATTACHMENT,
+        )?;
+        Renderbuffer::clear_binding();
+
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!(
+                "The headless framebuffer isn't complete (status 0x{status:04X})"
+            ));
+        }
+        Ok(Self {
+            framebuffer,
+            _color: color,
+            _depth_stencil: depth_stencil,
+        })
+    }
+}
+
+/// A GL context with no window, which draws to a framebuffer of its own.
+///
+/// This lets the same drawing code run without a display, such as in
+/// `cargo test` on a build machine with no GPU. The framebuffer has 8-bit
+/// RGBA color and a 24-bit depth buffer with an 8-bit stencil buffer, and
+/// it's bound (with the viewport set to cover it) when the context is made.
+///
+/// Only one context should be made at a time on a thread, and everything made
+/// with it must be dropped before it is.
+pub struct HeadlessContext {
+    display: egl::EGLDisplay,
+    context: egl::EGLContext,
+    surface: egl::EGLSurface,
+    targets: Option<RenderTargets>,
+    width: u32,
+    height: u32,
+}
+impl HeadlessContext {
+    /// Starts building a context.
+    pub fn builder() -> HeadlessContextBuilder {
+        HeadlessContextBuilder::default()
+    }
+
+    /// The size of the framebuffer, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The width of the framebuffer divided by its height, for making
+    /// projection matrices.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    /// The framebuffer that stands in for a window's.
+    pub fn framebuffer(&self) -> &Framebuffer {
+        // It's only missing while the context is being built.
+        &self.targets.as_ref().unwrap().framebuffer
+    }
+
+    /// Binds the context's framebuffer for drawing and reading, and sets the
+    /// viewport to cover it.
+    ///
+    /// There's no window to draw to, so after drawing to another framebuffer
+    /// this is what to go back to, rather than
+    /// [`Framebuffer::clear_binding`].
+    pub fn bind_framebuffer(&self) {
+        self.framebuffer().bind(FramebufferType::Framebuffer);
+        unsafe {
+            gl::Viewport(0, 0, self.width as GLsizei, self.height as GLsizei);
+        }
+    }
+
+    /// Reads what has been drawn to the whole framebuffer.
+    pub fn read_pixels(&self) -> RgbaImage {
+        self.framebuffer()
+            .read_pixels(0, 0, self.width, self.height)
+    }
+
+    /// Saves what has been drawn as an image, in the format that the path's
+    /// extension names (such as PNG).
+    pub fn save_screenshot(&self, path: impl AsRef<Path>) -> image::ImageResult<()> {
+        self.read_pixels().save(path)
+    }
+
+    /// Waits for all drawing to finish, which stands in for swapping buffers
+    /// when timing frames.
+    pub fn finish(&self) {
+        unsafe { gl::Finish() }
+    }
+}
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        // The framebuffer is deleted while the context is still current.
+        self.targets = None;
+        unsafe {
+            egl::eglMakeCurrent(self.display, null_mut(), null_mut(), null_mut());
+            if !self.surface.is_null() {
+                egl::eglDestroySurface(self.display, self.surface);
+            }
+            if !self.context.is_null() {
+                egl::eglDestroyContext(self.display, self.context);
+            }
+            egl::eglTerminate(self.display);
+        }
+    }
+}
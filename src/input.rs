@@ -0,0 +1,593 @@
+//! Keeping track of the keyboard, mouse and game controllers, and mapping
+//! them to actions.
+
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+use sdl2::{GameControllerSubsystem, Sdl};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Buttons that are held, the ones that were held at the start of this frame,
+/// and the ones that were pressed or released this frame.
+#[derive(Debug, Clone)]
+struct ButtonSet<T> {
+    held: HashSet<T>,
+    previous: HashSet<T>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+impl<T> Default for ButtonSet<T> {
+    fn default() -> Self {
+        Self {
+            held: HashSet::new(),
+            previous: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+}
+impl<T: Copy + Eq + Hash> ButtonSet<T> {
+    fn begin_frame(&mut self) {
+        self.previous.clone_from(&self.held);
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    fn press(&mut self, button: T) {
+        if self.held.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: T) {
+        if self.held.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    /// Releases everything, such as when the window loses focus and the
+    /// releases would go to another window.
+    fn release_all(&mut self) {
+        self.released.extend(self.held.drain());
+    }
+
+    fn state(&self, button: T) -> BindingState {
+        let value = |set: &HashSet<T>| f32::from(u8::from(set.contains(&button)));
+        BindingState {
+            now: value(&self.held),
+            before: value(&self.previous),
+            pressed: self.pressed.contains(&button),
+            released: self.released.contains(&button),
+        }
+    }
+}
+
+/// How far a binding, or the bindings of an action, are pushed this frame and
+/// at the start of it, and if a button was pressed or released in between.
+///
+/// The presses and releases are kept apart from the values so that a button
+/// tapped within one frame, which is neither held now nor before, still
+/// counts as both.
+#[derive(Debug, Clone, Copy, Default)]
+struct BindingState {
+    now: f32,
+    before: f32,
+    pressed: bool,
+    released: bool,
+}
+impl BindingState {
+    /// Combines two states, taking the furthest pushed of each.
+    fn max(self, other: Self) -> Self {
+        Self {
+            now: self.now.max(other.now),
+            before: self.before.max(other.before),
+            pressed: self.pressed || other.pressed,
+            released: self.released || other.released,
+        }
+    }
+}
+
+/// A game controller that's plugged in, and its state.
+struct Controller {
+    // Kept open so that its events keep coming.
+    _controller: GameController,
+    buttons: ButtonSet<Button>,
+    axes: HashMap<Axis, f32>,
+    previous_axes: HashMap<Axis, f32>,
+}
+
+/// The state of the keyboard, mouse and game controllers, built up from SDL
+/// events.
+///
+/// Each frame, call [`begin_frame`](Self::begin_frame) and then pass every
+/// event to [`handle_event`](Self::handle_event).
+/// [`Runner`](crate::Runner) does both, and passes the result to
+/// [`App::update`](crate::App::update).
+///
+/// Something is "pressed" or "released" only in the frame that it happened,
+/// and "held" for as long as it's down.
+#[derive(Default)]
+pub struct Input {
+    keys: ButtonSet<Scancode>,
+    mouse_buttons: ButtonSet<MouseButton>,
+    mouse_position: (i32, i32),
+    mouse_delta: (i32, i32),
+    wheel: (f32, f32),
+    text: String,
+    controller_subsystem: Option<GameControllerSubsystem>,
+    controllers: HashMap<u32, Controller>,
+}
+impl Input {
+    /// Makes an input tracker for the keyboard and mouse.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes an input tracker that also opens game controllers as they're
+    /// plugged in.
+    ///
+    /// Controllers that are already plugged in send an event for that when
+    /// the first events are read, so they're found the same way.
+    pub fn with_controllers(sdl: &Sdl) -> Result<Self, String> {
+        Ok(Self {
+            controller_subsystem: Some(sdl.game_controller()?),
+            ..Self::default()
+        })
+    }
+
+    /// Forgets what was pressed and released, and how far things moved, in
+    /// the last frame.
+    pub fn begin_frame(&mut self) {
+        self.keys.begin_frame();
+        self.mouse_buttons.begin_frame();
+        self.mouse_delta = (0, 0);
+        self.wheel = (0.0, 0.0);
+        self.text.clear();
+        for controller in self.controllers.values_mut() {
+            controller.buttons.begin_frame();
+            controller.previous_axes.clone_from(&controller.axes);
+        }
+    }
+
+    /// Updates the state from an event.
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown {
+                scancode: Some(scancode),
+                ..
+            } => self.keys.press(scancode),
+            Event::KeyUp {
+                scancode: Some(scancode),
+                ..
+            } => self.keys.release(scancode),
+            Event::MouseButtonDown { mouse_btn, .. } => self.mouse_buttons.press(mouse_btn),
+            Event::MouseButtonUp { mouse_btn, .. } => self.mouse_buttons.release(mouse_btn),
+            Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => {
+                self.mouse_position = (x, y);
+                self.mouse_delta.0 += xrel;
+                self.mouse_delta.1 += yrel;
+            }
+            Event::MouseWheel {
+                precise_x,
+                precise_y,
+                direction,
+                ..
+            } => {
+                let sign = match direction {
+                    MouseWheelDirection::Flipped => -1.0,
+                    _ => 1.0,
+                };
+                self.wheel.0 += precise_x * sign;
+                self.wheel.1 += precise_y * sign;
+            }
+            Event::TextInput { ref text, .. } => self.text.push_str(text),
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                self.keys.release_all();
+                self.mouse_buttons.release_all();
+            }
+            Event::ControllerDeviceAdded { which, .. } => {
+                let Some(subsystem) = &self.controller_subsystem else {
+                    return;
+                };
+                // A controller that can't be opened is left out, the same as
+                // one that isn't plugged in.
+                if let Ok(controller) = subsystem.open(which) {
+                    self.controllers.insert(
+                        controller.instance_id(),
+                        Controller {
+                            _controller: controller,
+                            buttons: ButtonSet::default(),
+                            axes: HashMap::new(),
+                            previous_axes: HashMap::new(),
+                        },
+                    );
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(&which);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                if let Some(controller) = self.controllers.get_mut(&which) {
+                    controller.buttons.press(button);
+                }
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                if let Some(controller) = self.controllers.get_mut(&which) {
+                    controller.buttons.release(button);
+                }
+            }
+            Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                if let Some(controller) = self.controllers.get_mut(&which) {
+                    let value = (f32::from(value) / f32::from(i16::MAX)).max(-1.0);
+                    controller.axes.insert(axis, value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// If a key is held down.
+    ///
+    /// Keys are named by where they are on the keyboard, so `Scancode::W` is
+    /// the key above `S` whatever the layout is.
+    pub fn key_held(&self, key: Scancode) -> bool {
+        self.keys.held.contains(&key)
+    }
+
+    /// If a key was pressed this frame, not counting key repeats.
+    pub fn key_pressed(&self, key: Scancode) -> bool {
+        self.keys.pressed.contains(&key)
+    }
+
+    /// If a key was released this frame.
+    pub fn key_released(&self, key: Scancode) -> bool {
+        self.keys.released.contains(&key)
+    }
+
+    /// If a mouse button is held down.
+    pub fn mouse_held(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.held.contains(&button)
+    }
+
+    /// If a mouse button was pressed this frame.
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.pressed.contains(&button)
+    }
+
+    /// If a mouse button was released this frame.
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.released.contains(&button)
+    }
+
+    /// Where the mouse is in the window, in screen coordinates from the top
+    /// left.
+    pub fn mouse_position(&self) -> (i32, i32) {
+        self.mouse_position
+    }
+
+    /// How far the mouse moved this frame, in screen coordinates.
+    ///
+    /// This keeps working in relative mouse mode (see
+    /// [`GlWindow::set_relative_mouse_mode`](crate::GlWindow::set_relative_mouse_mode)),
+    /// where the cursor is hidden and can't leave the window.
+    pub fn mouse_delta(&self) -> (i32, i32) {
+        self.mouse_delta
+    }
+
+    /// How far the mouse wheel scrolled this frame, in notches, with right
+    /// and up positive.
+    pub fn wheel(&self) -> (f32, f32) {
+        self.wheel
+    }
+
+    /// The text typed this frame.
+    ///
+    /// This is only sent while SDL's text input is started, with
+    /// `video.text_input().start()`.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The ids of the game controllers that are plugged in.
+    pub fn controllers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.controllers.keys().copied()
+    }
+
+    /// If a button is held on a game controller.
+    pub fn controller_held(&self, id: u32, button: Button) -> bool {
+        self.controllers
+            .get(&id)
+            .is_some_and(|c| c.buttons.held.contains(&button))
+    }
+
+    /// If a button was pressed on a game controller this frame.
+    pub fn controller_pressed(&self, id: u32, button: Button) -> bool {
+        self.controllers
+            .get(&id)
+            .is_some_and(|c| c.buttons.pressed.contains(&button))
+    }
+
+    /// If a button was released on a game controller this frame.
+    pub fn controller_released(&self, id: u32, button: Button) -> bool {
+        self.controllers
+            .get(&id)
+            .is_some_and(|c| c.buttons.released.contains(&button))
+    }
+
+    /// Where an axis of a game controller is, from `-1.0` to `1.0` for the
+    /// sticks and `0.0` to `1.0` for the triggers.
+    pub fn controller_axis(&self, id: u32, axis: Axis) -> f32 {
+        self.controllers
+            .get(&id)
+            .and_then(|c| c.axes.get(&axis).copied())
+            .unwrap_or(0.0)
+    }
+
+    /// How far a binding is pushed, and if it was pressed or released.
+    fn binding_state(&self, binding: Binding, deadzone: f32) -> BindingState {
+        match binding {
+            Binding::Key(key) => self.keys.state(key),
+            Binding::Mouse(button) => self.mouse_buttons.state(button),
+            Binding::ControllerButton(button) => self
+                .controllers
+                .values()
+                .map(|c| c.buttons.state(button))
+                .fold(BindingState::default(), BindingState::max),
+            Binding::ControllerAxis { axis, positive } => {
+                let push = |axes: &HashMap<Axis, f32>| {
+                    let value = axes.get(&axis).copied().unwrap_or(0.0);
+                    let value = if positive { value } else { -value };
+                    if value > deadzone {
+                        value
+                    } else {
+                        0.0
+                    }
+                };
+                self.controllers
+                    .values()
+                    .map(|c| BindingState {
+                        now: push(&c.axes),
+                        before: push(&c.previous_axes),
+                        ..BindingState::default()
+                    })
+                    .fold(BindingState::default(), BindingState::max)
+            }
+        }
+    }
+}
+
+/// Something that can trigger an action in an [`ActionMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// A key, by where it is on the keyboard.
+    Key(Scancode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// A button on any game controller.
+    ControllerButton(Button),
+    /// One direction of an axis on any game controller.
+    ControllerAxis {
+        /// The axis.
+        axis: Axis,
+        /// If pushing the axis positive triggers this, rather than negative.
+        positive: bool,
+    },
+}
+
+/// Names for what the player wants to do, each triggered by any of a list of
+/// [`Binding`]s that can be changed while the program runs.
+///
+/// The actions are usually a `Copy` enum of the program's own, like `Jump`
+/// or `MoveLeft`.
+#[derive(Debug, Clone)]
+pub struct ActionMap<A> {
+    bindings: HashMap<A, Vec<Binding>>,
+    deadzone: f32,
+    threshold: f32,
+}
+impl<A> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            deadzone: 0.2,
+            threshold: 0.5,
+        }
+    }
+}
+impl<A: Eq + Hash> ActionMap<A> {
+    /// Makes a map with no bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a binding for an action.
+    pub fn bind(mut self, action: A, binding: Binding) -> Self {
+        self.add_binding(action, binding);
+        self
+    }
+
+    /// Sets how far a controller axis has to be pushed before it counts at
+    /// all, from `0.0` to `1.0`. Defaults to `0.2`.
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = deadzone;
+        self
+    }
+
+    /// Sets how far a controller axis has to be pushed to count as held.
+    /// Defaults to `0.5`.
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Adds a binding for an action, if it's not already bound to it.
+    pub fn add_binding(&mut self, action: A, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Removes a binding from an action.
+    pub fn remove_binding(&mut self, action: &A, binding: Binding) {
+        if let Some(bindings) = self.bindings.get_mut(action) {
+            bindings.retain(|&b| b != binding);
+        }
+    }
+
+    /// Replaces all of an action's bindings.
+    pub fn rebind(&mut self, action: A, bindings: impl IntoIterator<Item = Binding>) {
+        self.bindings.insert(action, bindings.into_iter().collect());
+    }
+
+    /// The bindings for an action.
+    pub fn bindings(&self, action: &A) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The state of an action, as the furthest pushed of its bindings.
+    fn state(&self, input: &Input, action: &A) -> BindingState {
+        self.bindings(action)
+            .iter()
+            .map(|&binding| input.binding_state(binding, self.deadzone))
+            .fold(BindingState::default(), BindingState::max)
+    }
+
+    /// How far an action is pushed, from `0.0` to `1.0`.
+    ///
+    /// Keys and buttons are `0.0` or `1.0`, and controller axes are anywhere
+    /// between once they're past the deadzone.
+    pub fn value(&self, input: &Input, action: &A) -> f32 {
+        self.state(input, action).now
+    }
+
+    /// If an action is held, by any of its bindings.
+    pub fn held(&self, input: &Input, action: &A) -> bool {
+        self.value(input, action) >= self.threshold
+    }
+
+    /// If an action started being held this frame.
+    ///
+    /// A button that's pressed and released within one frame counts, so
+    /// quick taps aren't lost at low frame rates.
+    pub fn pressed(&self, input: &Input, action: &A) -> bool {
+        let state = self.state(input, action);
+        let held = state.now >= self.threshold;
+        let was_held = state.before >= self.threshold;
+        !was_held && (held || state.pressed)
+    }
+
+    /// If an action stopped being held this frame.
+    ///
+    /// Like with [`pressed`](Self::pressed), a button tapped within one frame
+    /// counts as released too.
+    pub fn released(&self, input: &Input, action: &A) -> bool {
+        let state = self.state(input, action);
+        let held = state.now >= self.threshold;
+        let was_held = state.before >= self.threshold;
+        !held && (was_held || state.released)
+    }
+
+    /// How far an action is pushed minus how far its opposite is, from
+    /// `-1.0` to `1.0`, such as for moving left and right.
+    pub fn axis(&self, input: &Input, negative: &A, positive: &A) -> f32 {
+        self.value(input, positive) - self.value(input, negative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::Mod;
+
+    fn key(down: bool, scancode: Scancode) -> Event {
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: None,
+                scancode: Some(scancode),
+                keymod: Mod::empty(),
+                repeat: false,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: None,
+                scancode: Some(scancode),
+                keymod: Mod::empty(),
+                repeat: false,
+            }
+        }
+    }
+
+    #[test]
+    fn held_key_is_pressed_once() {
+        let actions = ActionMap::new().bind("jump", Binding::Key(Scancode::Space));
+        let mut input = Input::new();
+
+        input.begin_frame();
+        input.handle_event(&key(true, Scancode::Space));
+        assert!(actions.held(&input, &"jump"));
+        assert!(actions.pressed(&input, &"jump"));
+        assert!(!actions.released(&input, &"jump"));
+
+        input.begin_frame();
+        assert!(actions.held(&input, &"jump"));
+        assert!(!actions.pressed(&input, &"jump"));
+        assert!(!actions.released(&input, &"jump"));
+
+        input.begin_frame();
+        input.handle_event(&key(false, Scancode::Space));
+        assert!(!actions.held(&input, &"jump"));
+        assert!(!actions.pressed(&input, &"jump"));
+        assert!(actions.released(&input, &"jump"));
+    }
+
+    #[test]
+    fn tap_within_one_frame_is_pressed_and_released() {
+        let actions = ActionMap::new().bind("jump", Binding::Key(Scancode::Space));
+        let mut input = Input::new();
+
+        input.begin_frame();
+        input.handle_event(&key(true, Scancode::Space));
+        input.handle_event(&key(false, Scancode::Space));
+        assert!(input.key_pressed(Scancode::Space));
+        assert!(!actions.held(&input, &"jump"));
+        assert!(actions.pressed(&input, &"jump"));
+        assert!(actions.released(&input, &"jump"));
+
+        input.begin_frame();
+        assert!(!actions.pressed(&input, &"jump"));
+        assert!(!actions.released(&input, &"jump"));
+    }
+
+    #[test]
+    fn second_binding_does_not_press_a_held_action() {
+        let actions = ActionMap::new()
+            .bind("left", Binding::Key(Scancode::A))
+            .bind("left", Binding::Key(Scancode::Left));
+        let mut input = Input::new();
+
+        input.begin_frame();
+        input.handle_event(&key(true, Scancode::A));
+        input.begin_frame();
+        input.handle_event(&key(true, Scancode::Left));
+        assert!(!actions.pressed(&input, &"left"));
+
+        input.begin_frame();
+        input.handle_event(&key(false, Scancode::A));
+        assert!(actions.held(&input, &"left"));
+        assert!(!actions.released(&input, &"left"));
+    }
+}
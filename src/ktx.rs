@@ -0,0 +1,419 @@
+//! Reading KTX and KTX2 files.
+
+use crate::container::{check_levels, check_size, slice_at, u32_at, u64_at};
+use crate::{
+    align_up, ContainerError, ContainerKind, InternalFormat, PixelFormat, PixelType,
+    TextureContainer,
+};
+
+/// The first bytes of every KTX file.
+pub(crate) const KTX_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'1', b'1', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+
+/// The first bytes of every KTX2 file.
+pub(crate) const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+
+/// Checks the face count shared by both versions.
+fn check_faces(faces: u32) -> Result<(), ContainerError> {
+    if faces == 1 || faces == 6 {
+        Ok(())
+    } else {
+        Err(ContainerError::Invalid("a texture must have 1 or 6 faces"))
+    }
+}
+
+/// Reads a KTX file, which describes its format with GL enums.
+pub(crate) fn parse_ktx(bytes: &[u8]) -> Result<TextureContainer<'_>, ContainerError> {
+    let field = |i: usize| u32_at(bytes, KTX_IDENTIFIER.len() + 4 * i);
+    if field(0)? != 0x0403_0201 {
+        return Err(ContainerError::Unsupported("big endian KTX files"));
+    }
+    let (gl_type, gl_format, gl_internal_format) = (field(1)?, field(3)?, field(4)?);
+    let width = field(6)?;
+    let (height, depth) = (field(7)?.max(1), field(8)?.max(1));
+    let (array_elements, faces, levels) = (field(9)?, field(10)?, field(11)?.max(1));
+    let key_value_len = field(12)? as usize;
+    check_size(width, height, depth)?;
+    check_faces(faces)?;
+    check_levels(levels)?;
+    let internal_format = InternalFormat::from_raw(gl_internal_format).ok_or_else(|| {
+        ContainerError::UnknownFormat(format!("glInternalFormat {gl_internal_format:#06X}"))
+    })?;
+    let pixels = if gl_type == 0 {
+        if !internal_format.is_compressed() {
+            return Err(ContainerError::Invalid("uncompressed data has no glType"));
+        }
+        None
+    } else {
+        let format = PixelFormat::from_raw(gl_format)
+            .ok_or_else(|| ContainerError::UnknownFormat(format!("glFormat {gl_format:#06X}")))?;
+        let ty = PixelType::from_raw(gl_type)
+            .ok_or_else(|| ContainerError::UnknownFormat(format!("glType {gl_type:#06X}")))?;
+        Some((format, ty))
+    };
+    let mut container = TextureContainer {
+        kind: ContainerKind::Ktx,
+        internal_format,
+        pixels,
+        width,
+        height,
+        depth,
+        layers: array_elements.max(1),
+        faces,
+        is_array: array_elements > 0,
+        row_alignment: 4,
+        levels: Vec::new(),
+    };
+    // Cube maps that aren't arrays give the size of one face rather than the
+    // whole level, and pad each face.
+    let face_by_face = faces == 6 && array_elements == 0;
+    let images = container.layers as usize * faces as usize;
+    let mut offset = 64 + key_value_len;
+    for level in 0..levels as usize {
+        let image_size = u32_at(bytes, offset)? as usize;
+        offset += 4;
+        let len = container.image_len(level);
+        let expected = if face_by_face {
+            Some(len)
+        } else {
+            len.checked_mul(images)
+        };
+        if expected != Some(image_size) {
+            return Err(ContainerError::Invalid(
+                "a mip level's size doesn't match its format",
+            ));
+        }
+        let mut level_images = Vec::new();
+        for _ in 0..images {
+            level_images.push(slice_at(bytes, offset, len)?);
+            offset += len;
+            if face_by_face {
+                offset = align_up(offset, 4);
+            }
+        }
+        offset = align_up(offset, 4);
+        container.levels.push(level_images);
+    }
+    Ok(container)
+}
+
+/// Reads a KTX2 file, which describes its format with a Vulkan enum.
+pub(crate) fn parse_ktx2(bytes: &[u8]) -> Result<TextureContainer<'_>, ContainerError> {
+    let field = |i: usize| u32_at(bytes, KTX2_IDENTIFIER.len() + 4 * i);
+    let vk_format = field(0)?;
+    let width = field(2)?;
+    let (height, depth) = (field(3)?.max(1), field(4)?.max(1));
+    let (layers, faces, levels) = (field(5)?, field(6)?, field(7)?.max(1));
+    if field(8)? != 0 {
+        return Err(ContainerError::Unsupported("supercompressed KTX2 files"));
+    }
+    check_size(width, height, depth)?;
+    check_faces(faces)?;
+    check_levels(levels)?;
+    let (internal_format, pixels) = vk_format_info(vk_format)
+        .ok_or_else(|| ContainerError::UnknownFormat(format!("vkFormat {vk_format}")))?;
+    let mut container = TextureContainer {
+        kind: ContainerKind::Ktx2,
+        internal_format,
+        pixels,
+        width,
+        height,
+        depth,
+        layers: layers.max(1),
+        faces,
+        is_array: layers > 0,
+        row_alignment: 1,
+        levels: Vec::new(),
+    };
+    let images = container.layers as usize * faces as usize;
+    // The level index comes after the header and the section index, with
+    // three `u64`s (offset, length and uncompressed length) per level.
+    const LEVEL_INDEX: usize = 80;
+    for level in 0..levels as usize {
+        let entry = LEVEL_INDEX + 24 * level;
+        let offset =
+            usize::try_from(u64_at(bytes, entry)?).map_err(|_| ContainerError::Truncated)?;
+        let length = u64_at(bytes, entry + 8)?;
+        let len = container.image_len(level);
+        if len.checked_mul(images).and_then(|n| u64::try_from(n).ok()) != Some(length) {
+            return Err(ContainerError::Invalid(
+                "a mip level's size doesn't match its format",
+            ));
+        }
+        let level_images = (0..images)
+            .map(|i| slice_at(bytes, offset.saturating_add(i * len), len))
+            .collect::<Result<_, _>>()?;
+        container.levels.push(level_images);
+    }
+    Ok(container)
+}
+
+/// The internal format and (for uncompressed formats) pixel format of a
+/// `VkFormat`, for the formats that have a GL equivalent here.
+fn vk_format_info(vk_format: u32) -> Option<(InternalFormat, Option<(PixelFormat, PixelType)>)> {
+    use InternalFormat as I;
+    use PixelFormat as F;
+    use PixelType as T;
+    let uncompressed = |internal, format, ty| Some((internal, Some((format, ty))));
+    match vk_format {
+        9 => uncompressed(I::R8, F::Red, T::UnsignedByte),
+        16 => uncompressed(I::Rg8, F::Rg, T::UnsignedByte),
+        23 => uncompressed(I::Rgb8, F::Rgb, T::UnsignedByte),
+        29 => uncompressed(I::Srgb8, F::Rgb, T::UnsignedByte),
+        37 => uncompressed(I::Rgba8, F::Rgba, T::UnsignedByte),
+        43 => uncompressed(I::Srgb8Alpha8, F::Rgba, T::UnsignedByte),
+        44 => uncompressed(I::Rgba8, F::Bgra, T::UnsignedByte),
+        50 => uncompressed(I::Srgb8Alpha8, F::Bgra, T::UnsignedByte),
+        70 => uncompressed(I::R16, F::Red, T::UnsignedShort),
+        76 => uncompressed(I::R16F, F::Red, T::HalfFloat),
+        77 => uncompressed(I::Rg16, F::Rg, T::UnsignedShort),
+        83 => uncompressed(I::Rg16F, F::Rg, T::HalfFloat),
+        84 => uncompressed(I::Rgb16, F::Rgb, T::UnsignedShort),
+        90 => uncompressed(I::Rgb16F, F::Rgb, T::HalfFloat),
+        91 => uncompressed(I::Rgba16, F::Rgba, T::UnsignedShort),
+        97 => uncompressed(I::Rgba16F, F::Rgba, T::HalfFloat),
+        100 => uncompressed(I::R32F, F::Red, T::Float),
+        103 => uncompressed(I::Rg32F, F::Rg, T::Float),
+        106 => uncompressed(I::Rgb32F, F::Rgb, T::Float),
+        109 => uncompressed(I::Rgba32F, F::Rgba, T::Float),
+        131 => Some((I::Bc1Rgb, None)),
+        132 => Some((I::Bc1Srgb, None)),
+        133 => Some((I::Bc1Rgba, None)),
+        134 => Some((I::Bc1SrgbAlpha, None)),
+        135 => Some((I::Bc2, None)),
+        136 => Some((I::Bc2Srgb, None)),
+        137 => Some((I::Bc3, None)),
+        138 => Some((I::Bc3Srgb, None)),
+        139 => Some((I::Bc4, None)),
+        140 => Some((I::Bc4Signed, None)),
+        141 => Some((I::Bc5, None)),
+        142 => Some((I::Bc5Signed, None)),
+        143 => Some((I::Bc6HUnsigned, None)),
+        144 => Some((I::Bc6HSigned, None)),
+        145 => Some((I::Bc7, None)),
+        146 => Some((I::Bc7Srgb, None)),
+        147 => Some((I::Etc2Rgb8, None)),
+        148 => Some((I::Etc2Srgb8, None)),
+        149 => Some((I::Etc2Rgb8A1, None)),
+        150 => Some((I::Etc2Srgb8A1, None)),
+        151 => Some((I::Etc2Rgba8, None)),
+        152 => Some((I::Etc2Srgb8Alpha8, None)),
+        153 => Some((I::EacR11, None)),
+        154 => Some((I::EacR11Signed, None)),
+        155 => Some((I::EacRg11, None)),
+        156 => Some((I::EacRg11Signed, None)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a little endian KTX file with no key/value data.
+    fn ktx(fields: [u32; 12], data: &[u8]) -> Vec<u8> {
+        let mut bytes = KTX_IDENTIFIER.to_vec();
+        bytes.extend(0x0403_0201_u32.to_le_bytes());
+        for field in fields.iter().skip(1) {
+            bytes.extend(field.to_le_bytes());
+        }
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    /// The header fields of a KTX file, after the endianness, with the
+    /// `glType`, `glFormat` and `glInternalFormat` of 8-bit RGBA.
+    fn rgba8(width: u32, height: u32, array: u32, faces: u32, levels: u32) -> [u32; 12] {
+        [
+            0,
+            gl::UNSIGNED_BYTE,
+            1,
+            gl::RGBA,
+            gl::RGBA8,
+            gl::RGBA,
+            width,
+            height,
+            0,
+            array,
+            faces,
+            levels,
+        ]
+    }
+
+    /// Appends a mip level to KTX data, giving its size first.
+    fn level(data: &mut Vec<u8>, image_size: u32, images: &[&[u8]]) {
+        data.extend(image_size.to_le_bytes());
+        for image in images {
+            data.extend(*image);
+        }
+    }
+
+    #[test]
+    fn ktx_2d_with_mipmaps() {
+        let mut data = Vec::new();
+        level(&mut data, 16, &[&[1; 16]]);
+        level(&mut data, 4, &[&[2; 4]]);
+        let bytes = ktx(rgba8(2, 2, 0, 1, 2), &data);
+        let container = parse_ktx(&bytes).unwrap();
+        assert_eq!(container.kind(), ContainerKind::Ktx);
+        assert_eq!(container.internal_format(), InternalFormat::Rgba8);
+        assert_eq!(
+            container.pixel_format(),
+            Some((PixelFormat::Rgba, PixelType::UnsignedByte))
+        );
+        assert_eq!((container.width(), container.height()), (2, 2));
+        assert!(!container.is_array());
+        assert_eq!(container.level_count(), 2);
+        assert_eq!(container.level_size(1), [1, 1, 1]);
+        assert_eq!(container.image(0, 0, 0), Some(&[1; 16][..]));
+        assert_eq!(container.image(1, 0, 0), Some(&[2; 4][..]));
+        assert_eq!(container.image(0, 0, 1), None);
+    }
+
+    #[test]
+    fn ktx_cube_gives_the_size_of_each_face() {
+        // RGB rows of two texels are padded from 6 to 8 bytes.
+        let mut fields = rgba8(2, 1, 0, 6, 1);
+        fields[3] = gl::RGB;
+        fields[4] = gl::RGB8;
+        let faces: Vec<[u8; 8]> = (0..6).map(|face| [face; 8]).collect();
+        let mut data = Vec::new();
+        let images: Vec<&[u8]> = faces.iter().map(|face| &face[..]).collect();
+        level(&mut data, 8, &images);
+        let bytes = ktx(fields, &data);
+        let container = parse_ktx(&bytes).unwrap();
+        assert_eq!(container.faces(), 6);
+        assert!(!container.is_array());
+        for face in 0..6 {
+            assert_eq!(container.image(0, 0, face), Some(&[face as u8; 8][..]));
+        }
+
+        // The size of the whole level is wrong for a cube that isn't an array.
+        let mut data = Vec::new();
+        level(&mut data, 48, &images);
+        assert_eq!(
+            parse_ktx(&ktx(fields, &data)).unwrap_err(),
+            ContainerError::Invalid("a mip level's size doesn't match its format")
+        );
+    }
+
+    #[test]
+    fn ktx_array_gives_the_size_of_the_whole_level() {
+        let mut data = Vec::new();
+        level(&mut data, 8, &[&[1; 4], &[2; 4]]);
+        let bytes = ktx(rgba8(1, 1, 2, 1, 1), &data);
+        let container = parse_ktx(&bytes).unwrap();
+        assert!(container.is_array());
+        assert_eq!(container.layers(), 2);
+        assert_eq!(container.image(0, 1, 0), Some(&[2; 4][..]));
+    }
+
+    #[test]
+    fn ktx_rejects_bad_headers() {
+        let mut data = Vec::new();
+        level(&mut data, 4, &[&[0; 4]]);
+        let bad_faces = ktx(rgba8(1, 1, 0, 3, 1), &data);
+        assert_eq!(
+            parse_ktx(&bad_faces).unwrap_err(),
+            ContainerError::Invalid("a texture must have 1 or 6 faces")
+        );
+        let no_faces = ktx(rgba8(1, 1, 0, 0, 1), &data);
+        assert!(matches!(
+            parse_ktx(&no_faces),
+            Err(ContainerError::Invalid(_))
+        ));
+        let too_many_levels = ktx(rgba8(1, 1, 0, 1, 1000), &data);
+        assert_eq!(
+            parse_ktx(&too_many_levels).unwrap_err(),
+            ContainerError::Invalid("there are too many mip levels")
+        );
+        let mut unknown = rgba8(1, 1, 0, 1, 1);
+        unknown[4] = 0x1234;
+        assert!(matches!(
+            parse_ktx(&ktx(unknown, &data)),
+            Err(ContainerError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn ktx_rejects_truncated_files() {
+        let mut data = Vec::new();
+        level(&mut data, 16, &[&[1; 16]]);
+        level(&mut data, 4, &[&[2; 4]]);
+        let bytes = ktx(rgba8(2, 2, 0, 1, 2), &data);
+        for len in [20, 63, 64, 70, bytes.len() - 1] {
+            assert_eq!(
+                parse_ktx(&bytes[..len]).unwrap_err(),
+                ContainerError::Truncated,
+                "cut to {len} bytes"
+            );
+        }
+    }
+
+    /// Makes a KTX2 file with no data format descriptor or other sections,
+    /// and a level index of `(offset, length)` pairs.
+    fn ktx2(fields: [u32; 8], index: &[(u64, u64)], data: &[u8]) -> Vec<u8> {
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        for field in fields {
+            bytes.extend(field.to_le_bytes());
+        }
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend([0; 32]);
+        for &(offset, length) in index {
+            bytes.extend(offset.to_le_bytes());
+            bytes.extend(length.to_le_bytes());
+            bytes.extend(length.to_le_bytes());
+        }
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn ktx2_2d_with_mipmaps() {
+        // Levels are stored smallest first, after a level index of two.
+        let start = 80 + 2 * 24;
+        let index = [(start + 4, 8), (start, 4)];
+        let mut data = vec![2; 4];
+        data.extend([1; 8]);
+        let bytes = ktx2([37, 1, 2, 1, 0, 0, 1, 2], &index, &data);
+        let container = parse_ktx2(&bytes).unwrap();
+        assert_eq!(container.kind(), ContainerKind::Ktx2);
+        assert_eq!(container.internal_format(), InternalFormat::Rgba8);
+        assert_eq!(container.level_count(), 2);
+        assert_eq!(container.image(0, 0, 0), Some(&[1; 8][..]));
+        assert_eq!(container.image(1, 0, 0), Some(&[2; 4][..]));
+
+        let cut = &bytes[..bytes.len() - 1];
+        assert_eq!(parse_ktx2(cut).unwrap_err(), ContainerError::Truncated);
+    }
+
+    #[test]
+    fn ktx2_rejects_bad_headers() {
+        let start = 80 + 24;
+        let data = [0; 4];
+        let bad_faces = ktx2([37, 1, 1, 1, 0, 0, 2, 1], &[(start, 4)], &data);
+        assert_eq!(
+            parse_ktx2(&bad_faces).unwrap_err(),
+            ContainerError::Invalid("a texture must have 1 or 6 faces")
+        );
+        let too_many_levels = ktx2([37, 1, 1, 1, 0, 0, 1, 5000], &[(start, 4)], &data);
+        assert_eq!(
+            parse_ktx2(&too_many_levels).unwrap_err(),
+            ContainerError::Invalid("there are too many mip levels")
+        );
+        let wrong_length = ktx2([37, 1, 1, 1, 0, 0, 1, 1], &[(start, 3)], &data);
+        assert!(matches!(
+            parse_ktx2(&wrong_length),
+            Err(ContainerError::Invalid(_))
+        ));
+        let past_the_end = ktx2([37, 1, 1, 1, 0, 0, 1, 1], &[(u64::MAX, 4)], &data);
+        assert_eq!(
+            parse_ktx2(&past_the_end).unwrap_err(),
+            ContainerError::Truncated
+        );
+    }
+}
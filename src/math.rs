@@ -0,0 +1,996 @@
+//! Vectors, matrices and quaternions, for transforms and projections.
+//!
+//! Matrices are column-major and multiply column vectors on their right, the
+//! same as GLSL, so `projection * view * model` reads right to left. The
+//! projections follow GL's conventions: a right-handed view space looking down
+//! `-Z`, and clip space depth from `-1` to `1`.
+//!
+//! Angles are in radians.
+
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A 2D vector, the same as a GLSL `vec2`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[repr(C)]
+pub struct Vec2 {
+    #[allow(missing_docs)]
+    pub x: f32,
+    #[allow(missing_docs)]
+    pub y: f32,
+}
+
+/// A 3D vector, the same as a GLSL `vec3`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[repr(C)]
+pub struct Vec3 {
+    #[allow(missing_docs)]
+    pub x: f32,
+    #[allow(missing_docs)]
+    pub y: f32,
+    #[allow(missing_docs)]
+    pub z: f32,
+}
+
+/// A 4D vector, the same as a GLSL `vec4`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[repr(C)]
+pub struct Vec4 {
+    #[allow(missing_docs)]
+    pub x: f32,
+    #[allow(missing_docs)]
+    pub y: f32,
+    #[allow(missing_docs)]
+    pub z: f32,
+    #[allow(missing_docs)]
+    pub w: f32,
+}
+
+macro_rules! vector {
+    ($($name:ident, $n:literal, [$($field:ident),+];)*) => {
+        $(
+            impl $name {
+                /// All zeroes.
+                pub const ZERO: Self = Self::splat(0.0);
+                /// All ones.
+                pub const ONE: Self = Self::splat(1.0);
+
+                /// Makes a vector from its components.
+                pub const fn new($($field: f32),+) -> Self {
+                    Self { $($field),+ }
+                }
+
+                /// Makes a vector with every component set to `value`.
+                pub const fn splat(value: f32) -> Self {
+                    Self { $($field: value),+ }
+                }
+
+                /// The components, in order.
+                pub const fn to_array(self) -> [f32; $n] {
+                    [$(self.$field),+]
+                }
+
+                /// The dot product.
+                pub fn dot(self, other: Self) -> f32 {
+                    0.0 $(+ self.$field * other.$field)+
+                }
+
+                /// The length, squared, which is cheaper to get than the
+                /// length.
+                pub fn length_squared(self) -> f32 {
+                    self.dot(self)
+                }
+
+                /// The length.
+                pub fn length(self) -> f32 {
+                    self.length_squared().sqrt()
+                }
+
+                /// The distance to another point.
+                pub fn distance(self, other: Self) -> f32 {
+                    (other - self).length()
+                }
+
+                /// This vector scaled to a length of `1`.
+                ///
+                /// A zero vector gives `NaN`s; see
+                /// [`normalize_or_zero`](Self::normalize_or_zero).
+                pub fn normalize(self) -> Self {
+                    self / self.length()
+                }
+
+                /// This vector scaled to a length of `1`, or zero if it has
+                /// no length.
+                pub fn normalize_or_zero(self) -> Self {
+                    let length = self.length();
+                    if length > 0.0 {
+                        self / length
+                    } else {
+                        Self::ZERO
+                    }
+                }
+
+                /// Goes `t` of the way from this vector to `other`.
+                pub fn lerp(self, other: Self, t: f32) -> Self {
+                    self + (other - self) * t
+                }
+
+                /// The smaller of each component.
+                pub fn min(self, other: Self) -> Self {
+                    Self { $($field: self.$field.min(other.$field)),+ }
+                }
+
+                /// The larger of each component.
+                pub fn max(self, other: Self) -> Self {
+                    Self { $($field: self.$field.max(other.$field)),+ }
+                }
+            }
+            impl From<[f32; $n]> for $name {
+                fn from([$($field),+]: [f32; $n]) -> Self {
+                    Self { $($field),+ }
+                }
+            }
+            impl From<$name> for [f32; $n] {
+                fn from(v: $name) -> Self {
+                    v.to_array()
+                }
+            }
+            impl Add for $name {
+                type Output = Self;
+                fn add(self, rhs: Self) -> Self {
+                    Self { $($field: self.$field + rhs.$field),+ }
+                }
+            }
+            impl Sub for $name {
+                type Output = Self;
+                fn sub(self, rhs: Self) -> Self {
+                    Self { $($field: self.$field - rhs.$field),+ }
+                }
+            }
+            impl Mul for $name {
+                type Output = Self;
+                fn mul(self, rhs: Self) -> Self {
+                    Self { $($field: self.$field * rhs.$field),+ }
+                }
+            }
+            impl Mul<f32> for $name {
+                type Output = Self;
+                fn mul(self, rhs: f32) -> Self {
+                    Self { $($field: self.$field * rhs),+ }
+                }
+            }
+            impl Mul<$name> for f32 {
+                type Output = $name;
+                fn mul(self, rhs: $name) -> $name {
+                    rhs * self
+                }
+            }
+            impl Div for $name {
+                type Output = Self;
+                fn div(self, rhs: Self) -> Self {
+                    Self { $($field: self.$field / rhs.$field),+ }
+                }
+            }
+            impl Div<f32> for $name {
+                type Output = Self;
+                fn div(self, rhs: f32) -> Self {
+                    Self { $($field: self.$field / rhs),+ }
+                }
+            }
+            impl Neg for $name {
+                type Output = Self;
+                fn neg(self) -> Self {
+                    Self { $($field: -self.$field),+ }
+                }
+            }
+            impl AddAssign for $name {
+                fn add_assign(&mut self, rhs: Self) {
+                    *self = *self + rhs;
+                }
+            }
+            impl SubAssign for $name {
+                fn sub_assign(&mut self, rhs: Self) {
+                    *self = *self - rhs;
+                }
+            }
+            impl MulAssign<f32> for $name {
+                fn mul_assign(&mut self, rhs: f32) {
+                    *self = *self * rhs;
+                }
+            }
+            impl DivAssign<f32> for $name {
+                fn div_assign(&mut self, rhs: f32) {
+                    *self = *self / rhs;
+                }
+            }
+        )*
+    };
+}
+vector! {
+    Vec2, 2, [x, y];
+    Vec3, 3, [x, y, z];
+    Vec4, 4, [x, y, z, w];
+}
+
+/// Makes a [`Vec2`].
+pub const fn vec2(x: f32, y: f32) -> Vec2 {
+    Vec2::new(x, y)
+}
+
+/// Makes a [`Vec3`].
+pub const fn vec3(x: f32, y: f32, z: f32) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+/// Makes a [`Vec4`].
+pub const fn vec4(x: f32, y: f32, z: f32, w: f32) -> Vec4 {
+    Vec4::new(x, y, z, w)
+}
+
+impl Vec2 {
+    /// The unit vector along `+X`.
+    pub const X: Self = Self::new(1.0, 0.0);
+    /// The unit vector along `+Y`.
+    pub const Y: Self = Self::new(0.0, 1.0);
+
+    /// Adds a `z` component.
+    pub const fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl Vec3 {
+    /// The unit vector along `+X`.
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    /// The unit vector along `+Y`.
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    /// The unit vector along `+Z`.
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    /// The cross product, which is at right angles to both vectors.
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Adds a `w` component.
+    pub const fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    /// Drops the `z` component.
+    pub const fn truncate(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl Vec4 {
+    /// The unit vector along `+X`.
+    pub const X: Self = Self::new(1.0, 0.0, 0.0, 0.0);
+    /// The unit vector along `+Y`.
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0, 0.0);
+    /// The unit vector along `+Z`.
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0, 0.0);
+    /// The unit vector along `+W`.
+    pub const W: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    /// Drops the `w` component.
+    pub const fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+/// A rotation, as a unit quaternion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Quat {
+    #[allow(missing_docs)]
+    pub x: f32,
+    #[allow(missing_docs)]
+    pub y: f32,
+    #[allow(missing_docs)]
+    pub z: f32,
+    #[allow(missing_docs)]
+    pub w: f32,
+}
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+impl Quat {
+    /// No rotation.
+    pub const IDENTITY: Self = Self::from_xyzw(0.0, 0.0, 0.0, 1.0);
+
+    /// Makes a quaternion from its components, which should have a length of
+    /// `1`.
+    pub const fn from_xyzw(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// A rotation by `angle` around `axis`, which should have a length of
+    /// `1`.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        let v = axis * sin;
+        Self::from_xyzw(v.x, v.y, v.z, cos)
+    }
+
+    /// A rotation by `angle` around `+X`.
+    pub fn from_rotation_x(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::X, angle)
+    }
+
+    /// A rotation by `angle` around `+Y`.
+    pub fn from_rotation_y(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::Y, angle)
+    }
+
+    /// A rotation by `angle` around `+Z`.
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::Z, angle)
+    }
+
+    /// A rotation by `roll` around `Z`, then `pitch` around `X`, then `yaw`
+    /// around `Y`, the usual order for something that looks down `-Z`.
+    pub fn from_yaw_pitch_roll(yaw: f32, pitch: f32, roll: f32) -> Self {
+        Self::from_rotation_y(yaw) * Self::from_rotation_x(pitch) * Self::from_rotation_z(roll)
+    }
+
+    /// The dot product of the components.
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// The length of the components, which is `1` for a rotation.
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// This quaternion scaled back to a length of `1`, such as after many
+    /// rotations have been combined.
+    pub fn normalize(self) -> Self {
+        let length = self.length();
+        Self::from_xyzw(
+            self.x / length,
+            self.y / length,
+            self.z / length,
+            self.w / length,
+        )
+    }
+
+    /// The opposite rotation.
+    pub fn inverse(self) -> Self {
+        Self::from_xyzw(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// Goes `t` of the way from this rotation to `other`, at a steady
+    /// angular speed, the short way around.
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = other;
+        if cos < 0.0 {
+            cos = -cos;
+            other = Self::from_xyzw(-other.x, -other.y, -other.z, -other.w);
+        }
+        let (a, b) = if cos > 0.9995 {
+            // Nearly the same rotation, where the angle is too small to
+            // divide by.
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+        Self::from_xyzw(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        )
+        .normalize()
+    }
+
+    fn xyz(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+impl Mul for Quat {
+    type Output = Self;
+    /// Combines two rotations, so that `rhs` happens first.
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self.xyz(), rhs.xyz());
+        let v = b * self.w + a * rhs.w + a.cross(b);
+        Self::from_xyzw(v.x, v.y, v.z, self.w * rhs.w - a.dot(b))
+    }
+}
+impl MulAssign for Quat {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+    /// Rotates a vector.
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        let q = self.xyz();
+        let t = q.cross(rhs) * 2.0;
+        rhs + t * self.w + q.cross(t)
+    }
+}
+
+/// A 3x3 matrix, the same as a GLSL `mat3`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Mat3 {
+    /// The columns.
+    pub cols: [Vec3; 3],
+}
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+impl Mat3 {
+    /// All zeroes.
+    pub const ZERO: Self = Self::from_cols(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO);
+    /// The identity matrix, which changes nothing.
+    pub const IDENTITY: Self = Self::from_cols(Vec3::X, Vec3::Y, Vec3::Z);
+
+    /// Makes a matrix from its columns.
+    pub const fn from_cols(x: Vec3, y: Vec3, z: Vec3) -> Self {
+        Self { cols: [x, y, z] }
+    }
+
+    /// The columns as arrays.
+    pub const fn to_cols_array_2d(self) -> [[f32; 3]; 3] {
+        let [x, y, z] = self.cols;
+        [x.to_array(), y.to_array(), z.to_array()]
+    }
+
+    /// The upper left 3x3 of a 4x4 matrix, which is its rotation and scale.
+    pub const fn from_mat4(m: Mat4) -> Self {
+        let [x, y, z, _] = m.cols;
+        Self::from_cols(x.truncate(), y.truncate(), z.truncate())
+    }
+
+    /// A rotation.
+    pub fn from_quat(rotation: Quat) -> Self {
+        Self::from_mat4(Mat4::from_quat(rotation))
+    }
+
+    /// Scales each axis.
+    pub const fn from_scale(scale: Vec3) -> Self {
+        Self::from_cols(
+            Vec3::new(scale.x, 0.0, 0.0),
+            Vec3::new(0.0, scale.y, 0.0),
+            Vec3::new(0.0, 0.0, scale.z),
+        )
+    }
+
+    /// The matrix for transforming normals by a model matrix, which is the
+    /// inverse transpose of its upper left 3x3.
+    ///
+    /// Unlike the model matrix itself, this keeps normals at right angles to
+    /// surfaces that have been scaled unevenly. A model matrix that can't be
+    /// inverted gives the zero matrix.
+    pub fn normal_matrix(model: Mat4) -> Self {
+        Self::from_mat4(model)
+            .inverse()
+            .map_or(Self::ZERO, Self::transpose)
+    }
+
+    /// Swaps the rows and columns.
+    pub fn transpose(self) -> Self {
+        let [x, y, z] = self.cols;
+        Self::from_cols(
+            Vec3::new(x.x, y.x, z.x),
+            Vec3::new(x.y, y.y, z.y),
+            Vec3::new(x.z, y.z, z.z),
+        )
+    }
+
+    /// The determinant.
+    pub fn determinant(self) -> f32 {
+        let [x, y, z] = self.cols;
+        x.dot(y.cross(z))
+    }
+
+    /// The inverse, or `None` if the matrix can't be inverted.
+    pub fn inverse(self) -> Option<Self> {
+        let [x, y, z] = self.cols;
+        let (yz, zx, xy) = (y.cross(z), z.cross(x), x.cross(y));
+        let det = x.dot(yz);
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Self::from_cols(yz, zx, xy).transpose() * (1.0 / det))
+    }
+}
+impl From<[[f32; 3]; 3]> for Mat3 {
+    fn from([x, y, z]: [[f32; 3]; 3]) -> Self {
+        Self::from_cols(x.into(), y.into(), z.into())
+    }
+}
+impl From<Mat3> for [[f32; 3]; 3] {
+    fn from(m: Mat3) -> Self {
+        m.to_cols_array_2d()
+    }
+}
+impl Mul for Mat3 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let [x, y, z] = rhs.cols;
+        Self::from_cols(self * x, self * y, self * z)
+    }
+}
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        let [x, y, z] = self.cols;
+        x * rhs.x + y * rhs.y + z * rhs.z
+    }
+}
+impl Mul<f32> for Mat3 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        let [x, y, z] = self.cols;
+        Self::from_cols(x * rhs, y * rhs, z * rhs)
+    }
+}
+impl MulAssign for Mat3 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+/// A 4x4 matrix, the same as a GLSL `mat4`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Mat4 {
+    /// The columns.
+    pub cols: [Vec4; 4],
+}
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+impl Mat4 {
+    /// All zeroes.
+    pub const ZERO: Self = Self::from_cols(Vec4::ZERO, Vec4::ZERO, Vec4::ZERO, Vec4::ZERO);
+    /// The identity matrix, which changes nothing.
+    pub const IDENTITY: Self = Self::from_cols(Vec4::X, Vec4::Y, Vec4::Z, Vec4::W);
+
+    /// Makes a matrix from its columns.
+    pub const fn from_cols(x: Vec4, y: Vec4, z: Vec4, w: Vec4) -> Self {
+        Self { cols: [x, y, z, w] }
+    }
+
+    /// The columns as arrays.
+    pub const fn to_cols_array_2d(self) -> [[f32; 4]; 4] {
+        let [x, y, z, w] = self.cols;
+        [x.to_array(), y.to_array(), z.to_array(), w.to_array()]
+    }
+
+    /// A 3x3 matrix in the upper left, with no translation.
+    pub const fn from_mat3(m: Mat3) -> Self {
+        let [x, y, z] = m.cols;
+        Self::from_cols(x.extend(0.0), y.extend(0.0), z.extend(0.0), Vec4::W)
+    }
+
+    /// Moves by `translation`.
+    pub const fn from_translation(translation: Vec3) -> Self {
+        Self::from_cols(Vec4::X, Vec4::Y, Vec4::Z, translation.extend(1.0))
+    }
+
+    /// Scales each axis.
+    pub const fn from_scale(scale: Vec3) -> Self {
+        Self::from_mat3(Mat3::from_scale(scale))
+    }
+
+    /// A rotation.
+    pub fn from_quat(rotation: Quat) -> Self {
+        let Quat { x, y, z, w } = rotation;
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+        let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        Self::from_cols(
+            Vec4::new(1.0 - (yy + zz), xy + wz, xz - wy, 0.0),
+            Vec4::new(xy - wz, 1.0 - (xx + zz), yz + wx, 0.0),
+            Vec4::new(xz + wy, yz - wx, 1.0 - (xx + yy), 0.0),
+            Vec4::W,
+        )
+    }
+
+    /// A rotation by `angle` around `axis`, which should have a length of
+    /// `1`.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        Self::from_quat(Quat::from_axis_angle(axis, angle))
+    }
+
+    /// A rotation by `angle` around `+X`.
+    pub fn from_rotation_x(angle: f32) -> Self {
+        Self::from_quat(Quat::from_rotation_x(angle))
+    }
+
+    /// A rotation by `angle` around `+Y`.
+    pub fn from_rotation_y(angle: f32) -> Self {
+        Self::from_quat(Quat::from_rotation_y(angle))
+    }
+
+    /// A rotation by `angle` around `+Z`.
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Self::from_quat(Quat::from_rotation_z(angle))
+    }
+
+    /// Scales, then rotates, then moves, which is the usual model matrix.
+    pub fn from_scale_rotation_translation(scale: Vec3, rotation: Quat, translation: Vec3) -> Self {
+        let [x, y, z, _] = Self::from_quat(rotation).cols;
+        Self::from_cols(
+            x * scale.x,
+            y * scale.y,
+            z * scale.z,
+            translation.extend(1.0),
+        )
+    }
+
+    /// A view matrix for a camera at `eye` looking at `target`, with `up`
+    /// pointing roughly up.
+    ///
+    /// The camera looks down `-Z` in view space, with `+Y` up and `+X` to the
+    /// right.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self::look_to(eye, target - eye, up)
+    }
+
+    /// A view matrix for a camera at `eye` looking along `direction`, with
+    /// `up` pointing roughly up.
+    pub fn look_to(eye: Vec3, direction: Vec3, up: Vec3) -> Self {
+        let f = direction.normalize();
+        let s = f.cross(up).normalize();
+        let u = s.cross(f);
+        Self::from_cols(
+            Vec4::new(s.x, u.x, -f.x, 0.0),
+            Vec4::new(s.y, u.y, -f.y, 0.0),
+            Vec4::new(s.z, u.z, -f.z, 0.0),
+            Vec4::new(-s.dot(eye), -u.dot(eye), f.dot(eye), 1.0),
+        )
+    }
+
+    /// A perspective projection, like `gluPerspective`.
+    ///
+    /// `fov_y` is the vertical field of view and `aspect_ratio` is the width
+    /// divided by the height (see
+    /// [`GlWindow::aspect_ratio`](crate::GlWindow::aspect_ratio)). Depths
+    /// from `near` to `far` in front of the camera map to `-1` to `1`.
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y * 0.5).tan();
+        let range = near - far;
+        Self::from_cols(
+            Vec4::new(f / aspect_ratio, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, f, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, (far + near) / range, -1.0),
+            Vec4::new(0.0, 0.0, 2.0 * far * near / range, 0.0),
+        )
+    }
+
+    /// A perspective projection with no far plane.
+    pub fn perspective_infinite(fov_y: f32, aspect_ratio: f32, near: f32) -> Self {
+        let f = 1.0 / (fov_y * 0.5).tan();
+        Self::from_cols(
+            Vec4::new(f / aspect_ratio, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, f, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, -1.0, -1.0),
+            Vec4::new(0.0, 0.0, -2.0 * near, 0.0),
+        )
+    }
+
+    /// An orthographic projection, like `glOrtho`.
+    ///
+    /// The box from `left` to `right`, `bottom` to `top`, and `near` to
+    /// `far` in front of the camera maps to the `-1` to `1` cube.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (width, height, depth) = (right - left, top - bottom, far - near);
+        Self::from_cols(
+            Vec4::new(2.0 / width, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 / height, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, -2.0 / depth, 0.0),
+            Vec4::new(
+                -(right + left) / width,
+                -(top + bottom) / height,
+                -(far + near) / depth,
+                1.0,
+            ),
+        )
+    }
+
+    /// A row, as a vector.
+    pub fn row(self, index: usize) -> Vec4 {
+        let [x, y, z, w] = self.to_cols_array_2d();
+        Vec4::new(x[index], y[index], z[index], w[index])
+    }
+
+    /// Swaps the rows and columns.
+    pub fn transpose(self) -> Self {
+        Self::from_cols(self.row(0), self.row(1), self.row(2), self.row(3))
+    }
+
+    /// The determinant.
+    pub fn determinant(self) -> f32 {
+        self.cofactors().0
+    }
+
+    /// The inverse, or `None` if the matrix can't be inverted.
+    pub fn inverse(self) -> Option<Self> {
+        let (det, adjugate) = self.cofactors();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(adjugate * (1.0 / det))
+    }
+
+    /// The determinant and the adjugate (the transposed cofactors), from the
+    /// 2x2 determinants of the first two columns and of the last two.
+    fn cofactors(self) -> (f32, Self) {
+        // `aCR` is column `C`, row `R`.
+        let [[a00, a01, a02, a03], [a10, a11, a12, a13], [a20, a21, a22, a23], [a30, a31, a32, a33]] =
+            self.to_cols_array_2d();
+        let b00 = a00 * a11 - a01 * a10;
+        let b01 = a00 * a12 - a02 * a10;
+        let b02 = a00 * a13 - a03 * a10;
+        let b03 = a01 * a12 - a02 * a11;
+        let b04 = a01 * a13 - a03 * a11;
+        let b05 = a02 * a13 - a03 * a12;
+        let b06 = a20 * a31 - a21 * a30;
+        let b07 = a20 * a32 - a22 * a30;
+        let b08 = a20 * a33 - a23 * a30;
+        let b09 = a21 * a32 - a22 * a31;
+        let b10 = a21 * a33 - a23 * a31;
+        let b11 = a22 * a33 - a23 * a32;
+        let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+        let adjugate = Self::from_cols(
+            Vec4::new(
+                a11 * b11 - a12 * b10 + a13 * b09,
+                a02 * b10 - a01 * b11 - a03 * b09,
+                a31 * b05 - a32 * b04 + a33 * b03,
+                a22 * b04 - a21 * b05 - a23 * b03,
+            ),
+            Vec4::new(
+                a12 * b08 - a10 * b11 - a13 * b07,
+                a00 * b11 - a02 * b08 + a03 * b07,
+                a32 * b02 - a30 * b05 - a33 * b01,
+                a20 * b05 - a22 * b02 + a23 * b01,
+            ),
+            Vec4::new(
+                a10 * b10 - a11 * b08 + a13 * b06,
+                a01 * b08 - a00 * b10 - a03 * b06,
+                a30 * b04 - a31 * b02 + a33 * b00,
+                a21 * b02 - a20 * b04 - a23 * b00,
+            ),
+            Vec4::new(
+                a11 * b07 - a10 * b09 - a12 * b06,
+                a00 * b09 - a01 * b07 + a02 * b06,
+                a31 * b01 - a30 * b03 - a32 * b00,
+                a20 * b03 - a21 * b01 + a22 * b00,
+            ),
+        );
+        (det, adjugate)
+    }
+
+    /// Transforms a point, including the translation.
+    pub fn transform_point3(self, point: Vec3) -> Vec3 {
+        (self * point.extend(1.0)).truncate()
+    }
+
+    /// Transforms a point and divides by `w`, for projection matrices.
+    pub fn project_point3(self, point: Vec3) -> Vec3 {
+        let v = self * point.extend(1.0);
+        v.truncate() / v.w
+    }
+
+    /// Transforms a direction, leaving out the translation.
+    pub fn transform_vector3(self, vector: Vec3) -> Vec3 {
+        (self * vector.extend(0.0)).truncate()
+    }
+}
+impl From<[[f32; 4]; 4]> for Mat4 {
+    fn from([x, y, z, w]: [[f32; 4]; 4]) -> Self {
+        Self::from_cols(x.into(), y.into(), z.into(), w.into())
+    }
+}
+impl From<Mat4> for [[f32; 4]; 4] {
+    fn from(m: Mat4) -> Self {
+        m.to_cols_array_2d()
+    }
+}
+impl Mul for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let [x, y, z, w] = rhs.cols;
+        Self::from_cols(self * x, self * y, self * z, self * w)
+    }
+}
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+    fn mul(self, rhs: Vec4) -> Vec4 {
+        let [x, y, z, w] = self.cols;
+        x * rhs.x + y * rhs.y + z * rhs.z + w * rhs.w
+    }
+}
+impl Mul<f32> for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        let [x, y, z, w] = self.cols;
+        Self::from_cols(x * rhs, y * rhs, z * rhs, w * rhs)
+    }
+}
+impl MulAssign for Mat4 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::FRAC_PI_2;
+
+    fn assert_near(a: &[f32], b: &[f32]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4),
+            "{a:?} is not near {b:?}"
+        );
+    }
+
+    fn assert_vec3_near(a: Vec3, b: Vec3) {
+        assert_near(&a.to_array(), &b.to_array());
+    }
+
+    fn assert_mat4_near(a: Mat4, b: Mat4) {
+        assert_near(
+            a.to_cols_array_2d().as_flattened(),
+            b.to_cols_array_2d().as_flattened(),
+        );
+    }
+
+    /// A matrix with no zeroes or symmetry, so every cofactor term matters.
+    fn uneven() -> Mat4 {
+        Mat4::from([
+            [2.0, 1.0, -1.0, 0.5],
+            [0.0, 3.0, 2.0, 1.0],
+            [1.0, -2.0, 4.0, 0.0],
+            [3.0, 0.5, 1.0, 2.0],
+        ])
+    }
+
+    #[test]
+    fn mat4_inverse() {
+        let transform = Mat4::from_scale_rotation_translation(
+            vec3(2.0, 0.5, 3.0),
+            Quat::from_axis_angle(vec3(1.0, 2.0, 3.0).normalize(), 0.7),
+            vec3(4.0, -5.0, 6.0),
+        );
+        let projection = Mat4::perspective(1.0, 1.5, 0.1, 100.0);
+        for m in [uneven(), transform, projection, projection * transform] {
+            let inverse = m.inverse().unwrap();
+            assert_mat4_near(m * inverse, Mat4::IDENTITY);
+            assert_mat4_near(inverse * m, Mat4::IDENTITY);
+        }
+        assert!(Mat4::from_scale(vec3(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn mat4_determinant() {
+        assert_eq!(Mat4::IDENTITY.determinant(), 1.0);
+        assert_eq!(Mat4::from_scale(vec3(2.0, 3.0, 4.0)).determinant(), 24.0);
+        // Worked out separately by cofactor expansion along the first row.
+        assert!((uneven().determinant() - 47.5).abs() < 1e-4);
+        assert!((uneven().transpose().determinant() - 47.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn mat3_inverse() {
+        let m = Mat3::from([[2.0, 1.0, -1.0], [0.0, 3.0, 2.0], [1.0, -2.0, 4.0]]);
+        let product = (m * m.inverse().unwrap()).to_cols_array_2d();
+        assert_near(
+            product.as_flattened(),
+            Mat3::IDENTITY.to_cols_array_2d().as_flattened(),
+        );
+        assert!(Mat3::from_scale(vec3(1.0, 1.0, 0.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn normal_matrix_undoes_uneven_scale() {
+        let normal = Mat3::normal_matrix(Mat4::from_scale(vec3(2.0, 1.0, 1.0)));
+        assert_vec3_near(normal * Vec3::X, vec3(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn rotation_directions() {
+        // Rotations are counterclockwise looking down the axis.
+        assert_vec3_near(Quat::from_rotation_z(FRAC_PI_2) * Vec3::X, Vec3::Y);
+        assert_vec3_near(Quat::from_rotation_x(FRAC_PI_2) * Vec3::Y, Vec3::Z);
+        assert_vec3_near(Quat::from_rotation_y(FRAC_PI_2) * Vec3::Z, Vec3::X);
+        assert_vec3_near(
+            Mat4::from_rotation_z(FRAC_PI_2).transform_vector3(Vec3::X),
+            Vec3::Y,
+        );
+    }
+
+    #[test]
+    fn quat_products_match_matrix_products() {
+        let a = Quat::from_axis_angle(vec3(1.0, 2.0, 3.0).normalize(), 0.7);
+        let b = Quat::from_yaw_pitch_roll(0.3, -1.1, 2.0);
+        assert_mat4_near(
+            Mat4::from_quat(a * b),
+            Mat4::from_quat(a) * Mat4::from_quat(b),
+        );
+        let v = vec3(0.5, -2.0, 1.0);
+        assert_vec3_near((a * b) * v, a * (b * v));
+        assert_vec3_near(Mat4::from_quat(a).transform_vector3(v), a * v);
+        assert_vec3_near(a.inverse() * (a * v), v);
+    }
+
+    #[test]
+    fn slerp_ends() {
+        let a = Quat::from_rotation_z(0.2);
+        let b = Quat::from_rotation_z(1.4);
+        let mid = a.slerp(b, 0.5) * Vec3::X;
+        assert_vec3_near(mid, Quat::from_rotation_z(0.8) * Vec3::X);
+        assert_vec3_near(a.slerp(b, 1.0) * Vec3::X, b * Vec3::X);
+    }
+
+    #[test]
+    fn look_at_puts_the_target_down_negative_z() {
+        let eye = vec3(3.0, 2.0, 5.0);
+        let target = vec3(-1.0, 0.0, 2.0);
+        let view = Mat4::look_at(eye, target, Vec3::Y);
+        assert_vec3_near(view.transform_point3(eye), Vec3::ZERO);
+        let distance = eye.distance(target);
+        assert_vec3_near(view.transform_point3(target), vec3(0.0, 0.0, -distance));
+        // Up stays up, so a point above the target is above it in view space.
+        assert!(view.transform_point3(target + Vec3::Y).y > 0.0);
+    }
+
+    #[test]
+    fn perspective_depth_range() {
+        let projection = Mat4::perspective(1.0, 2.0, 0.5, 50.0);
+        assert_near(
+            &[projection.project_point3(vec3(0.0, 0.0, -0.5)).z],
+            &[-1.0],
+        );
+        assert_near(
+            &[projection.project_point3(vec3(0.0, 0.0, -50.0)).z],
+            &[1.0],
+        );
+        // The top edge of the field of view is the top of clip space.
+        let top = vec3(0.0, (0.5f32).tan() * 10.0, -10.0);
+        assert_near(&[projection.project_point3(top).y], &[1.0]);
+
+        let infinite = Mat4::perspective_infinite(1.0, 2.0, 0.5);
+        assert_near(&[infinite.project_point3(vec3(0.0, 0.0, -0.5)).z], &[-1.0]);
+        let far = infinite.project_point3(vec3(0.0, 0.0, -1e6)).z;
+        assert!(far < 1.0 && far > 0.999, "{far}");
+    }
+
+    #[test]
+    fn orthographic_box_maps_to_the_unit_cube() {
+        let projection = Mat4::orthographic(-4.0, 2.0, -1.0, 3.0, 1.0, 11.0);
+        assert_vec3_near(
+            projection.transform_point3(vec3(-4.0, -1.0, -1.0)),
+            vec3(-1.0, -1.0, -1.0),
+        );
+        assert_vec3_near(
+            projection.transform_point3(vec3(2.0, 3.0, -11.0)),
+            vec3(1.0, 1.0, 1.0),
+        );
+        assert_vec3_near(
+            projection.transform_point3(vec3(-1.0, 1.0, -6.0)),
+            Vec3::ZERO,
+        );
+    }
+}
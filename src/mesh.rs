@@ -0,0 +1,221 @@
+//! Vertex data bundled up with the vertex array and buffers that draw it.
+
+use crate::{buffer_data, Buffer, BufferType, BufferUsage, Vertex, VertexArray};
+use core::ops::Range;
+use gl::types::{GLenum, GLint, GLsizei};
+
+/// The kinds of primitive that vertices can be assembled into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Each vertex is a point.
+    Points = gl::POINTS as isize,
+    /// Each pair of vertices is a line.
+    Lines = gl::LINES as isize,
+    /// Each vertex continues a line from the vertex before it.
+    LineStrip = gl::LINE_STRIP as isize,
+    /// A line strip that also joins the last vertex back to the first.
+    LineLoop = gl::LINE_LOOP as isize,
+    /// Each group of three vertices is a triangle.
+    Triangles = gl::TRIANGLES as isize,
+    /// Each vertex makes a triangle with the two vertices before it.
+    TriangleStrip = gl::TRIANGLE_STRIP as isize,
+    /// Each vertex makes a triangle with the vertex before it and the first
+    /// vertex.
+    TriangleFan = gl::TRIANGLE_FAN as isize,
+}
+
+/// The types that index data can be stored as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    /// `u8`
+    U8 = gl::UNSIGNED_BYTE as isize,
+    /// `u16`
+    U16 = gl::UNSIGNED_SHORT as isize,
+    /// `u32`
+    U32 = gl::UNSIGNED_INT as isize,
+}
+impl IndexType {
+    /// The size of one index, in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            IndexType::U8 => 1,
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        }
+    }
+}
+
+/// Data that can go in an index buffer.
+///
+/// This is the unsigned integer types, and arrays of them so that each
+/// triangle (or line) can be written as one item.
+///
+/// ## Safety
+/// The type must be exactly `COUNT` indices of type `TYPE` with no padding.
+pub unsafe trait Index: Copy {
+    /// The type of each index.
+    const TYPE: IndexType;
+    /// How many indices are in one item.
+    const COUNT: usize;
+}
+unsafe impl Index for u8 {
+    const TYPE: IndexType = IndexType::U8;
+    const COUNT: usize = 1;
+}
+unsafe impl Index for u16 {
+    const TYPE: IndexType = IndexType::U16;
+    const COUNT: usize = 1;
+}
+unsafe impl Index for u32 {
+    const TYPE: IndexType = IndexType::U32;
+    const COUNT: usize = 1;
+}
+unsafe impl<T: Index, const N: usize> Index for [T; N] {
+    const TYPE: IndexType = T::TYPE;
+    const COUNT: usize = T::COUNT * N;
+}
+
+/// A vertex array with its vertex buffer and (optionally) index buffer.
+///
+/// The mesh remembers how many vertices or indices it holds, so drawing always
+/// uses the right count. Everything is deleted when this value is dropped.
+pub struct Mesh {
+    vao: VertexArray,
+    vbo: Buffer,
+    ebo: Option<(Buffer, IndexType)>,
+    count: usize,
+    topology: Topology,
+}
+impl Mesh {
+    /// Makes a mesh that draws `vertices` in order.
+    pub fn new<V: Vertex>(vertices: &[V], topology: Topology) -> Option<Self> {
+        let vao = VertexArray::new()?;
+        let vbo = Buffer::new()?;
+        vao.set_vertex_layout::<V>(&vbo);
+        buffer_data(
+            BufferType::Array,
+            V::slice_as_bytes(vertices),
+            BufferUsage::StaticDraw,
+        );
+        VertexArray::clear_binding();
+        Buffer::clear_binding(BufferType::Array);
+        Some(Self {
+            vao,
+            vbo,
+            ebo: None,
+            count: vertices.len(),
+            topology,
+        })
+    }
+
+    /// Makes a mesh that draws `vertices` in the order given by `indices`.
+    pub fn indexed<V: Vertex, I: Index>(
+        vertices: &[V],
+        indices: &[I],
+        topology: Topology,
+    ) -> Option<Self> {
+        let vao = VertexArray::new()?;
+        let vbo = Buffer::new()?;
+        let ebo = Buffer::new()?;
+        vao.set_vertex_layout::<V>(&vbo);
+        buffer_data(
+            BufferType::Array,
+            V::slice_as_bytes(vertices),
+            BufferUsage::StaticDraw,
+        );
+        ebo.bind(BufferType::ElementArray);
+        let index_bytes = unsafe {
+            core::slice::from_raw_parts(indices.as_ptr().cast(), core::mem::size_of_val(indices))
+        };
+        buffer_data(
+            BufferType::ElementArray,
+            index_bytes,
+            BufferUsage::StaticDraw,
+        );
+        // The element array binding is part of the vertex array's state, so
+        // the vertex array has to be unbound first.
+        VertexArray::clear_binding();
+        Buffer::clear_binding(BufferType::Array);
+        Some(Self {
+            vao,
+            vbo,
+            ebo: Some((ebo, I::TYPE)),
+            count: indices.len() * I::COUNT,
+            topology,
+        })
+    }
+
+    /// The vertex array.
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vao
+    }
+
+    /// The buffer holding the vertices.
+    pub fn vertex_buffer(&self) -> &Buffer {
+        &self.vbo
+    }
+
+    /// The buffer holding the indices, if the mesh is indexed.
+    pub fn index_buffer(&self) -> Option<&Buffer> {
+        self.ebo.as_ref().map(|(ebo, _)| ebo)
+    }
+
+    /// The type of the indices, if the mesh is indexed.
+    pub fn index_type(&self) -> Option<IndexType> {
+        self.ebo.as_ref().map(|&(_, ty)| ty)
+    }
+
+    /// The number of indices, or of vertices if the mesh isn't indexed.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// How the vertices are assembled into primitives.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Changes how the vertices are assembled into primitives.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// Draws the whole mesh with the program currently in use.
+    ///
+    /// This leaves the mesh's vertex array bound.
+    pub fn draw(&self) {
+        self.draw_range(0..self.count);
+    }
+
+    /// Draws part of the mesh with the program currently in use.
+    ///
+    /// The range is of indices, or of vertices if the mesh isn't indexed. This
+    /// leaves the mesh's vertex array bound.
+    ///
+    /// ## Panics
+    /// If the range goes past the end of the mesh.
+    pub fn draw_range(&self, range: Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.count,
+            "draw range {range:?} is outside of a mesh with {} elements",
+            self.count
+        );
+        let count = GLsizei::try_from(range.len()).unwrap();
+        self.vao.bind();
+        unsafe {
+            match self.ebo {
+                Some((_, ty)) => gl::DrawElements(
+                    self.topology as GLenum,
+                    count,
+                    ty as GLenum,
+                    (range.start * ty.size()) as *const _,
+                ),
+                None => gl::DrawArrays(
+                    self.topology as GLenum,
+                    GLint::try_from(range.start).unwrap(),
+                    count,
+                ),
+            }
+        }
+    }
+}
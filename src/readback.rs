@@ -0,0 +1,215 @@
+//! Reading pixels back out of framebuffers, either right away or without
+//! stalling the GPU.
+
+use crate::{get_integer, orphan_buffer, Buffer, BufferType, BufferUsage};
+use crate::{Fence, Framebuffer, MapAccess};
+use gl::types::{GLint, GLsizei, GLuint};
+use image::RgbaImage;
+use std::ptr::null_mut;
+
+/// The pixel-pack state that changes how pixels are written out, which is
+/// set to tightly packed rows (and no pack buffer) while this is alive and
+/// put back when it's dropped.
+struct PackState {
+    alignment: GLint,
+    row_length: GLint,
+    skip_rows: GLint,
+    skip_pixels: GLint,
+    buffer: GLuint,
+}
+impl PackState {
+    fn tight() -> Self {
+        let state = Self {
+            alignment: get_integer(gl::PACK_ALIGNMENT),
+            row_length: get_integer(gl::PACK_ROW_LENGTH),
+            skip_rows: get_integer(gl::PACK_SKIP_ROWS),
+            skip_pixels: get_integer(gl::PACK_SKIP_PIXELS),
+            buffer: get_integer(gl::PIXEL_PACK_BUFFER_BINDING) as GLuint,
+        };
+        state.set(1, 0, 0, 0, 0);
+        state
+    }
+
+    fn set(&self, alignment: GLint, row_length: GLint, rows: GLint, pixels: GLint, buffer: GLuint) {
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, alignment);
+            gl::PixelStorei(gl::PACK_ROW_LENGTH, row_length);
+            gl::PixelStorei(gl::PACK_SKIP_ROWS, rows);
+            gl::PixelStorei(gl::PACK_SKIP_PIXELS, pixels);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, buffer);
+        }
+    }
+}
+impl Drop for PackState {
+    fn drop(&mut self) {
+        self.set(
+            self.alignment,
+            self.row_length,
+            self.skip_rows,
+            self.skip_pixels,
+            self.buffer,
+        );
+    }
+}
+
+/// Binds a framebuffer for reading until this is dropped, then binds back
+/// whatever was bound before.
+struct ReadBinding(GLuint);
+impl ReadBinding {
+    fn bind(framebuffer: GLuint) -> Self {
+        let previous = get_integer(gl::READ_FRAMEBUFFER_BINDING) as GLuint;
+        unsafe { gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer) };
+        Self(previous)
+    }
+}
+impl Drop for ReadBinding {
+    fn drop(&mut self) {
+        unsafe { gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.0) };
+    }
+}
+
+/// The number of bytes in a rectangle of RGBA pixels.
+fn rgba_len(width: u32, height: u32) -> usize {
+    width as usize * height as usize * 4
+}
+
+/// Makes an image from tightly packed RGBA rows that go from the bottom up,
+/// as GL gives them, by putting the top row first.
+fn flipped_image(width: u32, height: u32, pixels: &[u8]) -> RgbaImage {
+    let row_len = width as usize * 4;
+    let mut flipped = Vec::with_capacity(pixels.len());
+    if row_len > 0 {
+        for row in pixels.chunks_exact(row_len).rev() {
+            flipped.extend_from_slice(row);
+        }
+    }
+    RgbaImage::from_raw(width, height, flipped).unwrap()
+}
+
+/// Reads a rectangle of pixels from the framebuffer bound for reading.
+///
+/// The position is from the bottom left, as GL has it, but the image is
+/// flipped so that its first row is the top, as images usually are. This
+/// waits for all drawing to the framebuffer to finish; see [`PixelReadback`]
+/// to read without waiting.
+pub fn read_pixels(x: i32, y: i32, width: u32, height: u32) -> RgbaImage {
+    let mut pixels = vec![0; rgba_len(width, height)];
+    let _pack = PackState::tight();
+    unsafe {
+        gl::ReadPixels(
+            x,
+            y,
+            width as GLsizei,
+            height as GLsizei,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr().cast(),
+        );
+    }
+    flipped_image(width, height, &pixels)
+}
+
+/// Reads a rectangle of pixels from the default framebuffer (the window).
+///
+/// With a double-buffered window, this reads the back buffer, so it has to
+/// be called after drawing and before swapping.
+pub fn read_default_pixels(x: i32, y: i32, width: u32, height: u32) -> RgbaImage {
+    let _binding = ReadBinding::bind(0);
+    read_pixels(x, y, width, height)
+}
+
+impl Framebuffer {
+    /// Reads a rectangle of pixels from this framebuffer's read buffer
+    /// (normally its first color attachment), like [`read_pixels`].
+    ///
+    /// The framebuffer that was bound for reading before is bound again
+    /// afterwards.
+    pub fn read_pixels(&self, x: i32, y: i32, width: u32, height: u32) -> RgbaImage {
+        let _binding = ReadBinding::bind(self.id());
+        read_pixels(x, y, width, height)
+    }
+
+    /// Starts reading a rectangle of pixels from this framebuffer without
+    /// waiting, like [`PixelReadback::start`].
+    pub fn start_readback(&self, x: i32, y: i32, width: u32, height: u32) -> Option<PixelReadback> {
+        let _binding = ReadBinding::bind(self.id());
+        PixelReadback::start(x, y, width, height)
+    }
+}
+
+/// Pixels being copied out of a framebuffer into a pixel-pack buffer, so that
+/// the copy happens on the GPU's schedule rather than stalling until drawing
+/// is done.
+///
+/// Start the readback after drawing, and [`finish`](Self::finish) it a frame
+/// or two later, once [`is_ready`](Self::is_ready). Finishing early still
+/// works, but waits for the GPU like [`read_pixels`] does.
+pub struct PixelReadback {
+    buffer: Buffer,
+    fence: Fence,
+    width: u32,
+    height: u32,
+}
+impl PixelReadback {
+    /// Starts reading a rectangle of pixels from the framebuffer bound for
+    /// reading, positioned from the bottom left.
+    ///
+    /// Gives `None` if the buffer or fence couldn't be made.
+    pub fn start(x: i32, y: i32, width: u32, height: u32) -> Option<Self> {
+        let buffer = Buffer::new()?;
+        let _pack = PackState::tight();
+        buffer.bind(BufferType::PixelPack);
+        orphan_buffer(
+            BufferType::PixelPack,
+            rgba_len(width, height),
+            BufferUsage::StreamRead,
+        );
+        unsafe {
+            // With a pack buffer bound, the pointer is an offset into it.
+            gl::ReadPixels(
+                x,
+                y,
+                width as GLsizei,
+                height as GLsizei,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                null_mut(),
+            );
+        }
+        let fence = Fence::new()?;
+        Some(Self {
+            buffer,
+            fence,
+            width,
+            height,
+        })
+    }
+
+    /// If the pixels have been copied, so that finishing won't wait.
+    pub fn is_ready(&self) -> bool {
+        self.fence.is_signaled()
+    }
+
+    /// The size of the rectangle being read.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Gets the pixels, waiting for the copy if it isn't done yet.
+    ///
+    /// The image is flipped to have the top row first, like with
+    /// [`read_pixels`]. Gives `None` if the buffer couldn't be mapped.
+    pub fn finish(self) -> Option<RgbaImage> {
+        let len = rgba_len(self.width, self.height);
+        if len == 0 {
+            return Some(RgbaImage::new(self.width, self.height));
+        }
+        let previous = get_integer(gl::PIXEL_PACK_BUFFER_BINDING) as GLuint;
+        let image = self
+            .buffer
+            .map_range(BufferType::PixelPack, 0, len, MapAccess::READ)
+            .map(|pixels| flipped_image(self.width, self.height, &pixels));
+        unsafe { gl::BindBuffer(gl::PIXEL_PACK_BUFFER, previous) };
+        image
+    }
+}
@@ -0,0 +1,203 @@
+//! Asking a linked program what inputs and uniforms it has.
+
+use crate::{GlslType, ShaderProgram};
+use gl::types::{GLchar, GLenum, GLint, GLsizei, GLuint};
+
+/// A vertex attribute that the program's vertex shader reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveAttribute {
+    /// The name in the GLSL source.
+    pub name: String,
+    /// The attribute location, or `None` for built-ins like `gl_VertexID`.
+    pub location: Option<u32>,
+    /// The declared type.
+    pub ty: GlslType,
+    /// The number of array elements, `1` when it's not an array.
+    pub array_size: usize,
+}
+
+/// A uniform that the program uses.
+///
+/// The layout fields only have values for uniforms inside of a uniform block,
+/// since those are what describe how the block's buffer must be filled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveUniform {
+    /// The name in the GLSL source.
+    ///
+    /// Arrays are named by their first element (`lights[0]`) and struct
+    /// members by their full path (`light.color`).
+    pub name: String,
+    /// The uniform location, or `None` for uniforms inside a uniform block.
+    pub location: Option<GLint>,
+    /// The declared type.
+    pub ty: GlslType,
+    /// The number of array elements, `1` when it's not an array.
+    pub array_size: usize,
+    /// The index of the uniform block this is in.
+    pub block_index: Option<u32>,
+    /// The byte offset from the start of the block.
+    pub offset: Option<usize>,
+    /// The bytes between array elements.
+    pub array_stride: Option<usize>,
+    /// The bytes between matrix columns (or rows, if row major).
+    pub matrix_stride: Option<usize>,
+    /// If a matrix is stored by rows instead of columns.
+    pub row_major: bool,
+}
+
+/// A uniform block that the program uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformBlock {
+    /// The block name in the GLSL source (not the instance name).
+    pub name: String,
+    /// The block index within the program.
+    pub index: u32,
+    /// The uniform buffer binding point the block currently reads from.
+    pub binding: u32,
+    /// The minimum buffer size that the block needs, in bytes.
+    pub data_size: usize,
+    /// The uniforms in the block, in offset order.
+    pub members: Vec<ActiveUniform>,
+}
+
+/// Reads a name with one of the `glGetActive*Name` style calls.
+fn read_name(max_len: GLint, f: impl FnOnce(GLsizei, *mut GLsizei, *mut GLchar)) -> String {
+    let mut buf = vec![0_u8; usize::try_from(max_len).unwrap_or(0).max(1)];
+    let mut len = 0;
+    f(
+        GLsizei::try_from(buf.len()).unwrap(),
+        &mut len,
+        buf.as_mut_ptr().cast(),
+    );
+    buf.truncate(usize::try_from(len).unwrap_or(0));
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+fn non_negative(value: GLint) -> Option<usize> {
+    usize::try_from(value).ok()
+}
+
+impl ShaderProgram {
+    fn program_iv(&self, pname: GLenum) -> GLint {
+        let mut value = 0;
+        unsafe { gl::GetProgramiv(self.id(), pname, &mut value) };
+        value
+    }
+
+    /// Lists the vertex attributes that the program actually reads.
+    ///
+    /// Attributes that the compiler found to be unused aren't listed.
+    pub fn active_attributes(&self) -> Vec<ActiveAttribute> {
+        let count = self.program_iv(gl::ACTIVE_ATTRIBUTES);
+        let max_len = self.program_iv(gl::ACTIVE_ATTRIBUTE_MAX_LENGTH);
+        (0..GLuint::try_from(count).unwrap_or(0))
+            .map(|index| {
+                let mut size = 0;
+                let mut ty = 0;
+                let name = read_name(max_len, |buf_size, len, buf| unsafe {
+                    gl::GetActiveAttrib(self.id(), index, buf_size, len, &mut size, &mut ty, buf)
+                });
+                let c_name = std::ffi::CString::new(name.as_str()).unwrap_or_default();
+                let location = unsafe { gl::GetAttribLocation(self.id(), c_name.as_ptr()) };
+                ActiveAttribute {
+                    name,
+                    location: u32::try_from(location).ok(),
+                    ty: GlslType(ty),
+                    array_size: non_negative(size).unwrap_or(1),
+                }
+            })
+            .collect()
+    }
+
+    /// Lists every uniform that the program actually uses, including the ones
+    /// inside of uniform blocks.
+    pub fn active_uniforms(&self) -> Vec<ActiveUniform> {
+        let count = self.program_iv(gl::ACTIVE_UNIFORMS);
+        let max_len = self.program_iv(gl::ACTIVE_UNIFORM_MAX_LENGTH);
+        let indices: Vec<GLuint> = (0..GLuint::try_from(count).unwrap_or(0)).collect();
+        let query = |pname: GLenum| {
+            let mut values = vec![0; indices.len()];
+            if !indices.is_empty() {
+                unsafe {
+                    gl::GetActiveUniformsiv(
+                        self.id(),
+                        GLsizei::try_from(indices.len()).unwrap(),
+                        indices.as_ptr(),
+                        pname,
+                        values.as_mut_ptr(),
+                    )
+                };
+            }
+            values
+        };
+        let types = query(gl::UNIFORM_TYPE);
+        let sizes = query(gl::UNIFORM_SIZE);
+        let blocks = query(gl::UNIFORM_BLOCK_INDEX);
+        let offsets = query(gl::UNIFORM_OFFSET);
+        let array_strides = query(gl::UNIFORM_ARRAY_STRIDE);
+        let matrix_strides = query(gl::UNIFORM_MATRIX_STRIDE);
+        let row_majors = query(gl::UNIFORM_IS_ROW_MAJOR);
+        indices
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| {
+                let name = read_name(max_len, |buf_size, len, buf| unsafe {
+                    gl::GetActiveUniformName(self.id(), index, buf_size, len, buf)
+                });
+                let block_index = u32::try_from(blocks[i]).ok();
+                let location = if block_index.is_some() {
+                    None
+                } else {
+                    let c_name = std::ffi::CString::new(name.as_str()).unwrap_or_default();
+                    let location = unsafe { gl::GetUniformLocation(self.id(), c_name.as_ptr()) };
+                    (location >= 0).then_some(location)
+                };
+                let in_block = |value: GLint| block_index.and(non_negative(value));
+                ActiveUniform {
+                    name,
+                    location,
+                    ty: GlslType(GLenum::try_from(types[i]).unwrap_or(0)),
+                    array_size: non_negative(sizes[i]).unwrap_or(1),
+                    block_index,
+                    offset: in_block(offsets[i]),
+                    array_stride: in_block(array_strides[i]).filter(|&s| s > 0),
+                    matrix_stride: in_block(matrix_strides[i]).filter(|&s| s > 0),
+                    row_major: row_majors[i] != 0,
+                }
+            })
+            .collect()
+    }
+
+    /// Lists the uniform blocks that the program uses, along with the layout of
+    /// the uniforms inside of each one.
+    pub fn uniform_blocks(&self) -> Vec<UniformBlock> {
+        let count = self.program_iv(gl::ACTIVE_UNIFORM_BLOCKS);
+        let max_len = self.program_iv(gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH);
+        let uniforms = self.active_uniforms();
+        (0..GLuint::try_from(count).unwrap_or(0))
+            .map(|index| {
+                let block_iv = |pname: GLenum| {
+                    let mut value = 0;
+                    unsafe { gl::GetActiveUniformBlockiv(self.id(), index, pname, &mut value) };
+                    value
+                };
+                let name = read_name(max_len, |buf_size, len, buf| unsafe {
+                    gl::GetActiveUniformBlockName(self.id(), index, buf_size, len, buf)
+                });
+                let mut members: Vec<ActiveUniform> = uniforms
+                    .iter()
+                    .filter(|u| u.block_index == Some(index))
+                    .cloned()
+                    .collect();
+                members.sort_by_key(|u| u.offset);
+                UniformBlock {
+                    name,
+                    index,
+                    binding: u32::try_from(block_iv(gl::UNIFORM_BLOCK_BINDING)).unwrap_or(0),
+                    data_size: non_negative(block_iv(gl::UNIFORM_BLOCK_DATA_SIZE)).unwrap_or(0),
+                    members,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,99 @@
+//! Sampler objects, which hold sampling parameters apart from any texture.
+
+use crate::texture::{clamp_anisotropy, TEXTURE_MAX_ANISOTROPY};
+use crate::{MagFilter, MinFilter, TextureWrap};
+use gl::types::{GLenum, GLint, GLuint};
+use std::mem::ManuallyDrop;
+
+/// Basic wrapper for a [Sampler
+/// Object](https://www.khronos.org/opengl/wiki/Sampler_Object).
+///
+/// While a sampler is bound to a texture unit, its parameters are used instead
+/// of the parameters of the texture bound to that unit. This lets one texture
+/// be sampled in different ways, and one set of parameters be shared by many
+/// textures.
+///
+/// The sampler is deleted when this value is dropped.
+pub struct Sampler(GLuint);
+impl Sampler {
+    /// Makes a new sampler, with GL's default parameters.
+    pub fn new() -> Option<Self> {
+        let mut sampler = 0;
+        unsafe { gl::GenSamplers(1, &mut sampler) };
+        if sampler != 0 {
+            Some(Self(sampler))
+        } else {
+            None
+        }
+    }
+
+    /// Bind this sampler to a texture unit.
+    pub fn bind(&self, unit: u32) {
+        unsafe { gl::BindSampler(unit, self.0) }
+    }
+
+    /// Clear the sampler binding of a texture unit, so that the texture's own
+    /// parameters are used again.
+    pub fn clear_binding(unit: u32) {
+        unsafe { gl::BindSampler(unit, 0) }
+    }
+
+    fn parameter(&self, pname: GLenum, value: GLint) {
+        unsafe { gl::SamplerParameteri(self.0, pname, value) }
+    }
+
+    /// Sets the wrap mode of every direction.
+    pub fn set_wrap(&self, wrap: TextureWrap) {
+        for pname in [gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R] {
+            self.parameter(pname, wrap as GLint);
+        }
+    }
+
+    /// Sets the filter for when textures are drawn smaller than their size.
+    pub fn set_min_filter(&self, filter: MinFilter) {
+        self.parameter(gl::TEXTURE_MIN_FILTER, filter as GLint);
+    }
+
+    /// Sets the filter for when textures are drawn bigger than their size.
+    pub fn set_mag_filter(&self, filter: MagFilter) {
+        self.parameter(gl::TEXTURE_MAG_FILTER, filter as GLint);
+    }
+
+    /// Sets the maximum anisotropy, for sharper textures at steep angles.
+    ///
+    /// This is clamped to what the driver allows, and skipped when anisotropic
+    /// filtering isn't supported at all.
+    pub fn set_anisotropy(&self, max: f32) {
+        if let Some(max) = clamp_anisotropy(max) {
+            unsafe { gl::SamplerParameterf(self.0, TEXTURE_MAX_ANISOTROPY, max) }
+        }
+    }
+
+    /// Sets the color used by [`TextureWrap::ClampToBorder`].
+    pub fn set_border_color(&self, color: [f32; 4]) {
+        unsafe { gl::SamplerParameterfv(self.0, gl::TEXTURE_BORDER_COLOR, color.as_ptr()) }
+    }
+
+    /// The raw name of this sampler.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the sampler without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a sampler name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a sampler that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+}
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSamplers(1, &self.0) }
+    }
+}
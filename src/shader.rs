@@ -0,0 +1,306 @@
+//! Shader and program objects.
+
+use crate::{uniform::UniformSlot, Diagnostic};
+use core::{cell::RefCell, fmt};
+use gl::types::{GLenum, GLint, GLuint};
+use std::{collections::HashMap, mem::ManuallyDrop};
+
+/// The types of shader object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderType {
+    /// Vertex shaders determine the position of geometry within the screen.
+    Vertex = gl::VERTEX_SHADER as isize,
+    /// Fragment shaders determine the color output of geometry.
+    ///
+    /// Also other values, but mostly color.
+    Fragment = gl::FRAGMENT_SHADER as isize,
+}
+impl fmt::Display for ShaderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ShaderType::Vertex => "vertex",
+            ShaderType::Fragment => "fragment",
+        })
+    }
+}
+
+/// The ways that building a shader or a program can fail.
+#[derive(Debug, Clone)]
+pub enum ShaderError {
+    /// GL couldn't allocate a new shader or program object.
+    Allocation,
+    /// A shader stage failed to compile.
+    Compile {
+        /// The stage that failed.
+        stage: ShaderType,
+        /// The info log exactly as the driver wrote it.
+        log: String,
+        /// The info log split into messages, with source lines attached.
+        diagnostics: Vec<Diagnostic>,
+    },
+    /// The compiled stages failed to link together.
+    Link {
+        /// The info log exactly as the driver wrote it.
+        log: String,
+    },
+}
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Allocation => f.write_str("couldn't allocate a shader object"),
+            ShaderError::Compile {
+                stage,
+                log,
+                diagnostics,
+            } => {
+                write!(f, "{stage} shader failed to compile")?;
+                if diagnostics.is_empty() {
+                    write!(f, ": {}", log.trim())
+                } else {
+                    diagnostics.iter().try_for_each(|d| write!(f, "\n{d}"))
+                }
+            }
+            ShaderError::Link { log } => write!(f, "program failed to link: {}", log.trim()),
+        }
+    }
+}
+impl std::error::Error for ShaderError {}
+
+/// A handle to a [Shader
+/// Object](https://www.khronos.org/opengl/wiki/GLSL_Object#Shader_objects)
+///
+/// The shader is marked for deletion when this value is dropped.
+pub struct Shader(GLuint);
+impl Shader {
+    /// Makes a new shader.
+    ///
+    /// Prefer the [`Shader::from_source`](Shader::from_source) method.
+    ///
+    /// Possibly skip the direct creation of the shader object and use
+    /// [`ShaderProgram::from_vert_frag`](ShaderProgram::from_vert_frag).
+    pub fn new(ty: ShaderType) -> Option<Self> {
+        let shader = unsafe { gl::CreateShader(ty as GLenum) };
+        if shader != 0 {
+            Some(Self(shader))
+        } else {
+            None
+        }
+    }
+
+    /// Assigns a source string to the shader.
+    ///
+    /// Replaces any previously assigned source.
+    pub fn set_source(&self, src: &str) {
+        unsafe {
+            gl::ShaderSource(
+                self.0,
+                1,
+                &(src.as_bytes().as_ptr().cast()),
+                &(GLint::try_from(src.len()).unwrap()),
+            );
+        }
+    }
+
+    /// Compiles the shader based on the current source.
+    pub fn compile(&self) {
+        unsafe { gl::CompileShader(self.0) };
+    }
+
+    /// Checks if the last compile was successful or not.
+    pub fn compile_success(&self) -> bool {
+        let mut compiled = 0;
+        unsafe { gl::GetShaderiv(self.0, gl::COMPILE_STATUS, &mut compiled) };
+        compiled == i32::from(gl::TRUE)
+    }
+
+    /// Gets the info log for the shader.
+    ///
+    /// Usually you use this to get the compilation log when a compile failed.
+    pub fn info_log(&self) -> String {
+        let mut needed_len = 0;
+        unsafe { gl::GetShaderiv(self.0, gl::INFO_LOG_LENGTH, &mut needed_len) };
+        let mut v: Vec<u8> = Vec::with_capacity(needed_len.try_into().unwrap());
+        let mut len_written = 0_i32;
+        unsafe {
+            gl::GetShaderInfoLog(
+                self.0,
+                v.capacity().try_into().unwrap(),
+                &mut len_written,
+                v.as_mut_ptr().cast(),
+            );
+            v.set_len(len_written.try_into().unwrap());
+        }
+        String::from_utf8_lossy(&v).into_owned()
+    }
+
+    /// The raw name of this shader.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the shader without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a shader name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a shader that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+
+    /// Takes a shader type and source string and produces either the compiled
+    /// shader or an error describing what went wrong.
+    ///
+    /// Prefer [`ShaderProgram::from_vert_frag`](ShaderProgram::from_vert_frag),
+    /// it makes a complete program from the vertex and fragment sources all at
+    /// once.
+    pub fn from_source(ty: ShaderType, source: &str) -> Result<Self, ShaderError> {
+        let id = Self::new(ty).ok_or(ShaderError::Allocation)?;
+        id.set_source(source);
+        id.compile();
+        if id.compile_success() {
+            Ok(id)
+        } else {
+            let log = id.info_log();
+            let diagnostics = Diagnostic::parse_log(&log, source);
+            Err(ShaderError::Compile {
+                stage: ty,
+                log,
+                diagnostics,
+            })
+        }
+    }
+}
+impl Drop for Shader {
+    /// Marks the shader for deletion.
+    ///
+    /// Note: This _does not_ immediately delete the shader. If the shader has
+    /// been previously attached to a program then the shader will stay allocated
+    /// until it's unattached from that program.
+    fn drop(&mut self) {
+        unsafe { gl::DeleteShader(self.0) };
+    }
+}
+
+/// A handle to a [Program
+/// Object](https://www.khronos.org/opengl/wiki/GLSL_Object#Program_objects)
+///
+/// The program is marked for deletion when this value is dropped.
+pub struct ShaderProgram {
+    id: GLuint,
+    /// Uniform locations by name, filled in on first use after each link.
+    pub(crate) uniforms: RefCell<Option<HashMap<String, UniformSlot>>>,
+}
+impl ShaderProgram {
+    /// Allocates a new program object.
+    ///
+    /// Prefer [`ShaderProgram::from_vert_frag`](ShaderProgram::from_vert_frag),
+    /// it makes a complete program from the vertex and fragment sources all at
+    /// once.
+    pub fn new() -> Option<Self> {
+        let prog = unsafe { gl::CreateProgram() };
+        if prog != 0 {
+            Some(unsafe { Self::from_raw(prog) })
+        } else {
+            None
+        }
+    }
+
+    /// Attaches a shader object to this program object.
+    pub fn attach_shader(&self, shader: &Shader) {
+        unsafe { gl::AttachShader(self.id, shader.id()) };
+    }
+
+    /// Links the various attached, compiled shader objects into a usable program.
+    pub fn link_program(&self) {
+        unsafe { gl::LinkProgram(self.id) };
+        self.uniforms.replace(None);
+    }
+
+    /// Checks if the last linking operation was successful.
+    pub fn link_success(&self) -> bool {
+        let mut success = 0;
+        unsafe { gl::GetProgramiv(self.id, gl::LINK_STATUS, &mut success) };
+        success == i32::from(gl::TRUE)
+    }
+
+    /// Gets the log data for this program.
+    ///
+    /// This is usually used to check the message when a program failed to link.
+    pub fn info_log(&self) -> String {
+        let mut needed_len = 0;
+        unsafe { gl::GetProgramiv(self.id, gl::INFO_LOG_LENGTH, &mut needed_len) };
+        let mut v: Vec<u8> = Vec::with_capacity(needed_len.try_into().unwrap());
+        let mut len_written = 0_i32;
+        unsafe {
+            gl::GetProgramInfoLog(
+                self.id,
+                v.capacity().try_into().unwrap(),
+                &mut len_written,
+                v.as_mut_ptr().cast(),
+            );
+            v.set_len(len_written.try_into().unwrap());
+        }
+        String::from_utf8_lossy(&v).into_owned()
+    }
+
+    /// Sets the program as the program to use when drawing.
+    pub fn use_program(&self) {
+        unsafe { gl::UseProgram(self.id) };
+    }
+
+    /// The raw name of this program.
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Gives up ownership of the program without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        let this = ManuallyDrop::new(self);
+        this.uniforms.take();
+        this.id
+    }
+
+    /// Takes ownership of a program name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a program that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self {
+            id,
+            uniforms: RefCell::new(None),
+        }
+    }
+
+    /// Takes a vertex shader source string and a fragment shader source string
+    /// and either gets you a working program object or gets you an error.
+    ///
+    /// This is the preferred way to create a simple shader program in the common
+    /// case. It's just less error prone than doing all the steps yourself.
+    pub fn from_vert_frag(vert: &str, frag: &str) -> Result<Self, ShaderError> {
+        let p = Self::new().ok_or(ShaderError::Allocation)?;
+        let v = Shader::from_source(ShaderType::Vertex, vert)?;
+        let f = Shader::from_source(ShaderType::Fragment, frag)?;
+        p.attach_shader(&v);
+        p.attach_shader(&f);
+        p.link_program();
+        if p.link_success() {
+            Ok(p)
+        } else {
+            Err(ShaderError::Link { log: p.info_log() })
+        }
+    }
+}
+impl Drop for ShaderProgram {
+    /// Marks the program for deletion.
+    ///
+    /// Note: This _does not_ immediately delete the program. If the program is
+    /// currently in use it won't be deleted until it's not the active program.
+    /// When a program is finally deleted and attached shaders are unattached.
+    fn drop(&mut self) {
+        unsafe { gl::DeleteProgram(self.id) };
+    }
+}
@@ -0,0 +1,469 @@
+//! Laying out data by the `std140` rules, for filling uniform buffers.
+
+use crate::{buffer_data, buffer_sub_data, Buffer};
+use crate::{BufferType, BufferUsage, GlslType, ShaderProgram};
+use crate::{Mat3, Mat4, Vec2, Vec3, Vec4};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Rounds `value` up to a multiple of `align`.
+pub const fn align_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+/// A type that can be written into a uniform block with `layout (std140)`.
+///
+/// This is implemented for the scalars (`f32`, `i32`, `u32`, `bool`), for
+/// vectors as arrays of 2 to 4 scalars (`[f32; 3]` is a `vec3`), and for square
+/// `f32` matrices as arrays of columns (`[[f32; 4]; 4]` is a `mat4`), and for
+/// the math types like [`Vec3`] and [`Mat4`]. Structs get it with the derive
+/// (with the `derive` feature).
+pub trait Std140 {
+    /// The alignment of the type within a block, in bytes.
+    const ALIGN: usize;
+    /// The size of the type within a block, in bytes.
+    const SIZE: usize;
+    /// The GLSL type, or `None` for structs.
+    const GLSL_TYPE: Option<GlslType>;
+    /// The alignment of an array of this type.
+    const ARRAY_ALIGN: usize = align_up(Self::ALIGN, 16);
+    /// The bytes between the elements of an array of this type.
+    const ARRAY_STRIDE: usize = align_up(Self::SIZE, 16);
+
+    /// Writes the value to the start of `out`, which is at least `SIZE` bytes.
+    ///
+    /// Padding bytes are left alone.
+    fn write_std140(&self, out: &mut [u8]);
+
+    /// Lists the members that GL reports for a value of this type named `name`
+    /// at `offset`, the same way that [`ShaderProgram::uniform_blocks`] names
+    /// them.
+    fn std140_members(name: &str, offset: usize, out: &mut Vec<Std140Member>);
+}
+
+/// One member of a `std140` layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Std140Member {
+    /// The name that GL gives the member, such as `lights[1].color`.
+    pub name: String,
+    /// The byte offset from the start of the block.
+    pub offset: usize,
+    /// The GLSL type.
+    pub ty: GlslType,
+    /// The number of array elements, `1` when it's not an array.
+    pub array_size: usize,
+}
+
+/// Lists the members for an array of `T` named `name` at `offset`.
+///
+/// This is for the derive, which handles array fields with it.
+pub fn std140_array_members<T: Std140>(
+    name: &str,
+    offset: usize,
+    len: usize,
+    out: &mut Vec<Std140Member>,
+) {
+    match T::GLSL_TYPE {
+        Some(ty) => out.push(Std140Member {
+            name: format!("{name}[0]"),
+            offset,
+            ty,
+            array_size: len,
+        }),
+        None => {
+            for i in 0..len {
+                T::std140_members(&format!("{name}[{i}]"), offset + i * T::ARRAY_STRIDE, out);
+            }
+        }
+    }
+}
+
+/// Writes an array of `T` to the start of `out`.
+///
+/// This is for the derive, which handles array fields with it.
+pub fn write_std140_array<T: Std140>(values: &[T], out: &mut [u8]) {
+    for (i, value) in values.iter().enumerate() {
+        value.write_std140(&mut out[i * T::ARRAY_STRIDE..]);
+    }
+}
+
+macro_rules! std140_scalar {
+    ($($t:ty => $glsl:ident, [$v2:ident, $v3:ident, $v4:ident];)*) => {
+        $(
+            impl Std140 for $t {
+                const ALIGN: usize = 4;
+                const SIZE: usize = 4;
+                const GLSL_TYPE: Option<GlslType> = Some(GlslType::$glsl);
+                fn write_std140(&self, out: &mut [u8]) {
+                    out[..4].copy_from_slice(&self.to_ne_bytes());
+                }
+                fn std140_members(name: &str, offset: usize, out: &mut Vec<Std140Member>) {
+                    scalar_member::<Self>(name, offset, out);
+                }
+            }
+            std140_vector!($t, 2, 8, $v2);
+            std140_vector!($t, 3, 16, $v3);
+            std140_vector!($t, 4, 16, $v4);
+        )*
+    };
+}
+macro_rules! std140_vector {
+    ($t:ty, $n:literal, $align:literal, $glsl:ident) => {
+        impl Std140 for [$t; $n] {
+            const ALIGN: usize = $align;
+            const SIZE: usize = 4 * $n;
+            const GLSL_TYPE: Option<GlslType> = Some(GlslType::$glsl);
+            fn write_std140(&self, out: &mut [u8]) {
+                for (i, c) in self.iter().enumerate() {
+                    c.write_std140(&mut out[4 * i..]);
+                }
+            }
+            fn std140_members(name: &str, offset: usize, out: &mut Vec<Std140Member>) {
+                scalar_member::<Self>(name, offset, out);
+            }
+        }
+    };
+}
+macro_rules! std140_matrix {
+    ($($n:literal => $glsl:ident),*) => {
+        $(
+            impl Std140 for [[f32; $n]; $n] {
+                const ALIGN: usize = 16;
+                const SIZE: usize = 16 * $n;
+                const GLSL_TYPE: Option<GlslType> = Some(GlslType::$glsl);
+                fn write_std140(&self, out: &mut [u8]) {
+                    // Each column is padded out to a vec4.
+                    for (i, column) in self.iter().enumerate() {
+                        column.write_std140(&mut out[16 * i..]);
+                    }
+                }
+                fn std140_members(name: &str, offset: usize, out: &mut Vec<Std140Member>) {
+                    scalar_member::<Self>(name, offset, out);
+                }
+            }
+        )*
+    };
+}
+
+fn scalar_member<T: Std140>(name: &str, offset: usize, out: &mut Vec<Std140Member>) {
+    out.push(Std140Member {
+        name: name.to_string(),
+        offset,
+        ty: T::GLSL_TYPE.unwrap(),
+        array_size: 1,
+    });
+}
+
+std140_scalar! {
+    f32 => FLOAT, [VEC2, VEC3, VEC4];
+    i32 => INT, [IVEC2, IVEC3, IVEC4];
+    u32 => UINT, [UVEC2, UVEC3, UVEC4];
+}
+std140_matrix!(2 => MAT2, 3 => MAT3, 4 => MAT4);
+
+macro_rules! std140_math {
+    ($($t:ty => $array:ty, $to_array:ident;)*) => {
+        $(
+            impl Std140 for $t {
+                const ALIGN: usize = <$array>::ALIGN;
+                const SIZE: usize = <$array>::SIZE;
+                const GLSL_TYPE: Option<GlslType> = <$array>::GLSL_TYPE;
+                fn write_std140(&self, out: &mut [u8]) {
+                    self.$to_array().write_std140(out);
+                }
+                fn std140_members(name: &str, offset: usize, out: &mut Vec<Std140Member>) {
+                    scalar_member::<Self>(name, offset, out);
+                }
+            }
+        )*
+    };
+}
+std140_math! {
+    Vec2 => [f32; 2], to_array;
+    Vec3 => [f32; 3], to_array;
+    Vec4 => [f32; 4], to_array;
+    Mat3 => [[f32; 3]; 3], to_cols_array_2d;
+    Mat4 => [[f32; 4]; 4], to_cols_array_2d;
+}
+
+impl Std140 for bool {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 4;
+    const GLSL_TYPE: Option<GlslType> = Some(GlslType::BOOL);
+    fn write_std140(&self, out: &mut [u8]) {
+        u32::from(*self).write_std140(out);
+    }
+    fn std140_members(name: &str, offset: usize, out: &mut Vec<Std140Member>) {
+        scalar_member::<Self>(name, offset, out);
+    }
+}
+
+/// Writes a value into a new buffer of its `std140` bytes.
+pub fn to_std140_bytes<T: Std140>(value: &T) -> Vec<u8> {
+    let mut bytes = vec![0; T::SIZE];
+    value.write_std140(&mut bytes);
+    bytes
+}
+
+/// A uniform buffer holding a single `T`, for filling a uniform block.
+///
+/// One buffer can be bound to a binding point that several programs read, so
+/// that shared data (such as the camera) is only uploaded once. The buffer is
+/// deleted when this value is dropped.
+pub struct UniformBuffer<T> {
+    buffer: Buffer,
+    bytes: Vec<u8>,
+    _marker: PhantomData<T>,
+}
+impl<T: Std140> UniformBuffer<T> {
+    /// Makes a new uniform buffer holding `value`.
+    ///
+    /// This leaves the buffer bound as the uniform buffer.
+    pub fn new(value: &T) -> Option<Self> {
+        let buffer = Buffer::new()?;
+        let bytes = to_std140_bytes(value);
+        buffer.bind(BufferType::Uniform);
+        buffer_data(BufferType::Uniform, &bytes, BufferUsage::DynamicDraw);
+        Some(Self {
+            buffer,
+            bytes,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Replaces the value in the buffer.
+    ///
+    /// This leaves the buffer bound as the uniform buffer.
+    pub fn set(&mut self, value: &T) {
+        value.write_std140(&mut self.bytes);
+        self.buffer.bind(BufferType::Uniform);
+        buffer_sub_data(BufferType::Uniform, 0, &self.bytes);
+    }
+
+    /// Binds the buffer to a uniform buffer binding point.
+    ///
+    /// Uniform blocks read from the binding point that they're assigned with
+    /// [`ShaderProgram::bind_uniform_block`] (or `layout (binding = N)`).
+    pub fn bind(&self, binding: u32) {
+        self.buffer.bind_base(BufferType::Uniform, binding);
+    }
+
+    /// The underlying buffer.
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+/// One way that a Rust type and a uniform block's layout disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockMismatch {
+    /// The block has a member that the type doesn't.
+    Missing {
+        /// The name of the member.
+        name: String,
+    },
+    /// The type has a member that the block doesn't.
+    Extra {
+        /// The name of the member.
+        name: String,
+    },
+    /// The member is a different type in the block.
+    Type {
+        /// The name of the member.
+        name: String,
+        /// The type in the block.
+        declared: GlslType,
+        /// The type in the Rust type.
+        given: GlslType,
+    },
+    /// The member is at a different offset in the block.
+    Offset {
+        /// The name of the member.
+        name: String,
+        /// The offset in the block.
+        declared: usize,
+        /// The offset in the Rust type.
+        given: usize,
+    },
+    /// The member is an array of a different length in the block.
+    ArraySize {
+        /// The name of the member.
+        name: String,
+        /// The length in the block.
+        declared: usize,
+        /// The length in the Rust type.
+        given: usize,
+    },
+    /// The block is bigger than the Rust type.
+    Size {
+        /// The size of the block.
+        declared: usize,
+        /// The size of the Rust type.
+        given: usize,
+    },
+}
+impl fmt::Display for BlockMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockMismatch::Missing { name } => write!(f, "`{name}` is only in the block"),
+            BlockMismatch::Extra { name } => write!(f, "`{name}` is only in the Rust type"),
+            BlockMismatch::Type {
+                name,
+                declared,
+                given,
+            } => write!(
+                f,
+                "`{name}` is a `{declared}` in the block, not a `{given}`"
+            ),
+            BlockMismatch::Offset {
+                name,
+                declared,
+                given,
+            } => write!(
+                f,
+                "`{name}` is at offset {declared} in the block, not {given}"
+            ),
+            BlockMismatch::ArraySize {
+                name,
+                declared,
+                given,
+            } => write!(
+                f,
+                "`{name}` has {declared} elements in the block, not {given}"
+            ),
+            BlockMismatch::Size { declared, given } => {
+                write!(
+                    f,
+                    "the block is {declared} bytes, but the Rust type is {given}"
+                )
+            }
+        }
+    }
+}
+
+/// The ways that using a uniform block can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UniformBlockError {
+    /// The program has no active uniform block with this name.
+    NotFound {
+        /// The name that was looked up.
+        block: String,
+    },
+    /// The block's layout doesn't match the Rust type.
+    Layout {
+        /// The name of the block.
+        block: String,
+        /// Every difference that was found.
+        mismatches: Vec<BlockMismatch>,
+    },
+}
+impl fmt::Display for UniformBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniformBlockError::NotFound { block } => {
+                write!(f, "no active uniform block named `{block}`")
+            }
+            UniformBlockError::Layout { block, mismatches } => {
+                write!(f, "uniform block `{block}` doesn't match the Rust type:")?;
+                for mismatch in mismatches {
+                    write!(f, "\n  {mismatch}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+impl std::error::Error for UniformBlockError {}
+
+impl ShaderProgram {
+    /// The index of the named uniform block, if the program uses it.
+    pub fn uniform_block_index(&self, block: &str) -> Option<u32> {
+        let name = std::ffi::CString::new(block).ok()?;
+        let index = unsafe { gl::GetUniformBlockIndex(self.id(), name.as_ptr()) };
+        (index != gl::INVALID_INDEX).then_some(index)
+    }
+
+    /// Makes the named uniform block read from a uniform buffer binding point.
+    pub fn bind_uniform_block(&self, block: &str, binding: u32) -> Result<(), UniformBlockError> {
+        let index = self
+            .uniform_block_index(block)
+            .ok_or_else(|| UniformBlockError::NotFound {
+                block: block.to_string(),
+            })?;
+        unsafe { gl::UniformBlockBinding(self.id(), index, binding) };
+        Ok(())
+    }
+
+    /// Checks that the named uniform block has the same layout as `T`.
+    ///
+    /// The block should be declared with `layout (std140)`, since the other
+    /// layouts are up to the driver.
+    pub fn check_uniform_block<T: Std140>(&self, block: &str) -> Result<(), UniformBlockError> {
+        let not_found = || UniformBlockError::NotFound {
+            block: block.to_string(),
+        };
+        let index = self.uniform_block_index(block).ok_or_else(not_found)?;
+        let reflected = self
+            .uniform_blocks()
+            .into_iter()
+            .find(|b| b.index == index)
+            .ok_or_else(not_found)?;
+
+        let mut expected = Vec::new();
+        T::std140_members("", 0, &mut expected);
+        // A block with an instance name has its members named `Block.member`.
+        let prefix = format!("{block}.");
+
+        let mut mismatches = Vec::new();
+        for member in &reflected.members {
+            let name = member.name.strip_prefix(&prefix).unwrap_or(&member.name);
+            let Some(i) = expected.iter().position(|e| e.name == name) else {
+                mismatches.push(BlockMismatch::Missing {
+                    name: name.to_string(),
+                });
+                continue;
+            };
+            let given = expected.remove(i);
+            if member.ty != given.ty {
+                mismatches.push(BlockMismatch::Type {
+                    name: given.name.clone(),
+                    declared: member.ty,
+                    given: given.ty,
+                });
+            }
+            if member.offset != Some(given.offset) {
+                mismatches.push(BlockMismatch::Offset {
+                    name: given.name.clone(),
+                    declared: member.offset.unwrap_or(0),
+                    given: given.offset,
+                });
+            }
+            if member.array_size != given.array_size {
+                mismatches.push(BlockMismatch::ArraySize {
+                    name: given.name,
+                    declared: member.array_size,
+                    given: given.array_size,
+                });
+            }
+        }
+        mismatches.extend(
+            expected
+                .into_iter()
+                .map(|e| BlockMismatch::Extra { name: e.name }),
+        );
+        if reflected.data_size > T::SIZE {
+            mismatches.push(BlockMismatch::Size {
+                declared: reflected.data_size,
+                given: T::SIZE,
+            });
+        }
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(UniformBlockError::Layout {
+                block: block.to_string(),
+                mismatches,
+            })
+        }
+    }
+}
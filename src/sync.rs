@@ -0,0 +1,75 @@
+//! Fences for finding out when the GPU has finished earlier commands.
+
+use core::time::Duration;
+use gl::types::{GLsync, GLuint64};
+use std::mem::ManuallyDrop;
+
+/// What waiting on a [`Fence`] ended with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenceStatus {
+    /// Every command before the fence has finished.
+    Signaled,
+    /// The timeout ran out first.
+    TimedOut,
+    /// GL reported an error, such as the context being lost.
+    Failed,
+}
+
+/// Basic wrapper for a [Sync
+/// Object](https://www.khronos.org/opengl/wiki/Sync_Object) fence.
+///
+/// The fence is signaled once the GPU has finished every command that was
+/// issued before the fence. It's deleted when this value is dropped.
+pub struct Fence(GLsync);
+impl Fence {
+    /// Puts a new fence into the command stream.
+    pub fn new() -> Option<Self> {
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        if !sync.is_null() {
+            Some(Self(sync))
+        } else {
+            None
+        }
+    }
+
+    /// Waits up to `timeout` for the fence to be signaled.
+    ///
+    /// Pending commands are flushed first, so this can't wait forever on
+    /// commands that were never sent to the GPU.
+    pub fn wait(&self, timeout: Duration) -> FenceStatus {
+        let nanos = GLuint64::try_from(timeout.as_nanos()).unwrap_or(GLuint64::MAX);
+        match unsafe { gl::ClientWaitSync(self.0, gl::SYNC_FLUSH_COMMANDS_BIT, nanos) } {
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => FenceStatus::Signaled,
+            gl::TIMEOUT_EXPIRED => FenceStatus::TimedOut,
+            _ => FenceStatus::Failed,
+        }
+    }
+
+    /// If the fence has been signaled, without waiting.
+    pub fn is_signaled(&self) -> bool {
+        self.wait(Duration::ZERO) == FenceStatus::Signaled
+    }
+
+    /// The raw sync object of this fence.
+    pub fn id(&self) -> GLsync {
+        self.0
+    }
+
+    /// Gives up ownership of the fence without deleting it.
+    pub fn into_raw(self) -> GLsync {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a sync object made elsewhere.
+    ///
+    /// ## Safety
+    /// The sync object must be one that nothing else will delete.
+    pub unsafe fn from_raw(sync: GLsync) -> Self {
+        Self(sync)
+    }
+}
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSync(self.0) }
+    }
+}
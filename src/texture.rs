@@ -0,0 +1,623 @@
+//! Texture objects.
+
+use crate::{
+    gl_version, has_extension, ContainerError, InternalFormat, PixelData, PixelFormat, PixelType,
+};
+use core::fmt;
+use core::marker::PhantomData;
+use gl::types::{GLenum, GLfloat, GLint, GLsizei, GLuint};
+use image::DynamicImage;
+use std::borrow::Cow;
+use std::mem::ManuallyDrop;
+
+/// `GL_TEXTURE_MAX_ANISOTROPY`, which is core in GL 4.6 and otherwise comes
+/// from the anisotropic filtering extensions. The `gl` bindings stop at 4.5.
+pub(crate) const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+/// `GL_MAX_TEXTURE_MAX_ANISOTROPY`, see above.
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// The targets that a texture can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureType {
+    /// A single two dimensional image.
+    Texture2D = gl::TEXTURE_2D as isize,
+    /// A stack of same-sized two dimensional images.
+    Texture2DArray = gl::TEXTURE_2D_ARRAY as isize,
+    /// A three dimensional block of texels.
+    Texture3D = gl::TEXTURE_3D as isize,
+    /// Six square images forming the faces of a cube.
+    CubeMap = gl::TEXTURE_CUBE_MAP as isize,
+}
+
+/// Basic wrapper for a [Texture
+/// Object](https://www.khronos.org/opengl/wiki/Texture).
+///
+/// The texture is deleted when this value is dropped.
+pub struct Texture(GLuint);
+impl Texture {
+    /// Makes a new texture.
+    pub fn new() -> Option<Self> {
+        let mut texture = 0;
+        unsafe { gl::GenTextures(1, &mut texture) };
+        if texture != 0 {
+            Some(Self(texture))
+        } else {
+            None
+        }
+    }
+
+    /// Bind this texture to the given target of the active texture unit.
+    pub fn bind(&self, ty: TextureType) {
+        unsafe { gl::BindTexture(ty as GLenum, self.0) }
+    }
+
+    /// Clear the texture binding for the given target of the active texture unit.
+    pub fn clear_binding(ty: TextureType) {
+        unsafe { gl::BindTexture(ty as GLenum, 0) }
+    }
+
+    /// The raw name of this texture.
+    pub fn id(&self) -> GLuint {
+        self.0
+    }
+
+    /// Gives up ownership of the texture without deleting it.
+    pub fn into_raw(self) -> GLuint {
+        ManuallyDrop::new(self).0
+    }
+
+    /// Takes ownership of a texture name made elsewhere.
+    ///
+    /// ## Safety
+    /// The name must be a texture that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Self {
+        Self(id)
+    }
+}
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.0) }
+    }
+}
+
+/// Sets which texture unit the texture binding calls affect.
+///
+/// Unit `0` is `GL_TEXTURE0`, unit `1` is `GL_TEXTURE1`, and so on.
+pub fn active_texture(unit: u32) {
+    unsafe { gl::ActiveTexture(gl::TEXTURE0 + unit) }
+}
+
+/// What texture coordinates outside of `0.0..=1.0` sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureWrap {
+    /// The texture repeats.
+    Repeat = gl::REPEAT as isize,
+    /// The texture repeats, mirrored every other time.
+    MirroredRepeat = gl::MIRRORED_REPEAT as isize,
+    /// The texels at the edge are stretched out.
+    ClampToEdge = gl::CLAMP_TO_EDGE as isize,
+    /// The border color is used.
+    ClampToBorder = gl::CLAMP_TO_BORDER as isize,
+}
+
+/// How a texture is sampled when it's drawn smaller than its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinFilter {
+    /// The nearest texel.
+    Nearest = gl::NEAREST as isize,
+    /// A blend of the four nearest texels.
+    Linear = gl::LINEAR as isize,
+    /// The nearest texel of the nearest mipmap.
+    NearestMipmapNearest = gl::NEAREST_MIPMAP_NEAREST as isize,
+    /// A blend of the four nearest texels of the nearest mipmap.
+    LinearMipmapNearest = gl::LINEAR_MIPMAP_NEAREST as isize,
+    /// A blend of the nearest texel of the two nearest mipmaps.
+    NearestMipmapLinear = gl::NEAREST_MIPMAP_LINEAR as isize,
+    /// A blend of the four nearest texels of the two nearest mipmaps
+    /// (trilinear filtering).
+    LinearMipmapLinear = gl::LINEAR_MIPMAP_LINEAR as isize,
+}
+impl MinFilter {
+    /// If this filter reads from mipmaps.
+    pub fn uses_mipmaps(self) -> bool {
+        !matches!(self, MinFilter::Nearest | MinFilter::Linear)
+    }
+}
+
+/// How a texture is sampled when it's drawn bigger than its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MagFilter {
+    /// The nearest texel, which gives a pixelated look.
+    Nearest = gl::NEAREST as isize,
+    /// A blend of the four nearest texels.
+    Linear = gl::LINEAR as isize,
+}
+
+/// Where a channel of a texture sample comes from, for swizzling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Swizzle {
+    /// The texel's red channel.
+    Red = gl::RED as isize,
+    /// The texel's green channel.
+    Green = gl::GREEN as isize,
+    /// The texel's blue channel.
+    Blue = gl::BLUE as isize,
+    /// The texel's alpha channel.
+    Alpha = gl::ALPHA as isize,
+    /// Always `0.0`.
+    Zero = gl::ZERO as isize,
+    /// Always `1.0`.
+    One = gl::ONE as isize,
+}
+
+/// The ways that making or updating a texture can fail.
+#[derive(Debug)]
+pub enum TextureError {
+    /// GL didn't give us a texture name.
+    Allocation,
+    /// Fewer bytes of pixel data were given than the image size needs.
+    DataTooShort {
+        /// The bytes needed.
+        needed: usize,
+        /// The bytes given.
+        given: usize,
+    },
+    /// A sub-image update would go past the edge of the texture.
+    OutOfBounds,
+    /// A texture made of several images (such as the faces of a cube map or
+    /// the layers of an array) was given no images.
+    NoImages,
+    /// The images for a texture made of several images don't all have the
+    /// same size and format.
+    SizeMismatch,
+    /// An image can't be split up into the layout that was asked for.
+    InvalidLayout {
+        /// The image's width.
+        width: u32,
+        /// The image's height.
+        height: u32,
+    },
+    /// The image file couldn't be opened or decoded.
+    Image(image::ImageError),
+    /// A texture container file couldn't be read.
+    Io(std::io::Error),
+    /// A texture container file couldn't be understood.
+    Container(ContainerError),
+    /// The current context can't make textures with this format.
+    UnsupportedFormat(InternalFormat),
+}
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Allocation => write!(f, "couldn't make a texture"),
+            TextureError::DataTooShort { needed, given } => {
+                write!(
+                    f,
+                    "the image needs {needed} bytes of pixel data, but {given} were given"
+                )
+            }
+            TextureError::OutOfBounds => write!(f, "the region is outside of the texture"),
+            TextureError::NoImages => write!(f, "no images were given"),
+            TextureError::SizeMismatch => {
+                write!(f, "the images don't all have the same size and format")
+            }
+            TextureError::InvalidLayout { width, height } => {
+                write!(f, "a {width}x{height} image can't be split up that way")
+            }
+            TextureError::Image(e) => write!(f, "couldn't load the image: {e}"),
+            TextureError::Io(e) => write!(f, "couldn't read the file: {e}"),
+            TextureError::Container(e) => write!(f, "couldn't read the texture container: {e}"),
+            TextureError::UnsupportedFormat(format) => {
+                write!(f, "this context doesn't support {format:?} textures")
+            }
+        }
+    }
+}
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureError::Image(e) => Some(e),
+            TextureError::Io(e) => Some(e),
+            TextureError::Container(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<image::ImageError> for TextureError {
+    fn from(e: image::ImageError) -> Self {
+        TextureError::Image(e)
+    }
+}
+impl From<std::io::Error> for TextureError {
+    fn from(e: std::io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+impl From<ContainerError> for TextureError {
+    fn from(e: ContainerError) -> Self {
+        TextureError::Container(e)
+    }
+}
+
+/// Builds a texture of type `T` with the chosen parameters.
+///
+/// Get one from the texture type (such as [`Texture2D::builder`]), and finish
+/// it with one of the methods that the type provides. The defaults are
+/// repeating, trilinear filtering, generated mipmaps, and an internal format
+/// picked from the pixel data.
+///
+/// [`Texture2D::builder`]: crate::Texture2D::builder
+#[derive(Debug, Clone)]
+pub struct TextureBuilder<T> {
+    pub(crate) wrap: [TextureWrap; 3],
+    pub(crate) min_filter: MinFilter,
+    pub(crate) mag_filter: MagFilter,
+    pub(crate) anisotropy: Option<f32>,
+    pub(crate) border_color: Option<[f32; 4]>,
+    pub(crate) internal_format: Option<InternalFormat>,
+    pub(crate) mipmaps: bool,
+    pub(crate) flip_vertically: bool,
+    pub(crate) srgb: bool,
+    pub(crate) swizzle: Option<[Swizzle; 4]>,
+    _marker: PhantomData<fn() -> T>,
+}
+impl<T> Default for TextureBuilder<T> {
+    fn default() -> Self {
+        Self {
+            wrap: [TextureWrap::Repeat; 3],
+            min_filter: MinFilter::LinearMipmapLinear,
+            mag_filter: MagFilter::Linear,
+            anisotropy: None,
+            border_color: None,
+            internal_format: None,
+            mipmaps: true,
+            flip_vertically: false,
+            srgb: false,
+            swizzle: None,
+            _marker: PhantomData,
+        }
+    }
+}
+impl<T> TextureBuilder<T> {
+    /// Sets the wrap mode of every direction.
+    pub fn wrap(mut self, wrap: TextureWrap) -> Self {
+        self.wrap = [wrap; 3];
+        self
+    }
+
+    /// Sets the wrap mode of the S (horizontal) direction.
+    pub fn wrap_s(mut self, wrap: TextureWrap) -> Self {
+        self.wrap[0] = wrap;
+        self
+    }
+
+    /// Sets the wrap mode of the T (vertical) direction.
+    pub fn wrap_t(mut self, wrap: TextureWrap) -> Self {
+        self.wrap[1] = wrap;
+        self
+    }
+
+    /// Sets the wrap mode of the R (depth) direction.
+    pub fn wrap_r(mut self, wrap: TextureWrap) -> Self {
+        self.wrap[2] = wrap;
+        self
+    }
+
+    /// Sets the filter for when the texture is drawn smaller than its size.
+    pub fn min_filter(mut self, filter: MinFilter) -> Self {
+        self.min_filter = filter;
+        self
+    }
+
+    /// Sets the filter for when the texture is drawn bigger than its size.
+    pub fn mag_filter(mut self, filter: MagFilter) -> Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    /// Sets the maximum anisotropy, for sharper textures at steep angles.
+    ///
+    /// This is clamped to what the driver allows, and skipped when anisotropic
+    /// filtering isn't supported at all.
+    pub fn anisotropy(mut self, max: f32) -> Self {
+        self.anisotropy = Some(max);
+        self
+    }
+
+    /// Sets the color used by [`TextureWrap::ClampToBorder`].
+    pub fn border_color(mut self, color: [f32; 4]) -> Self {
+        self.border_color = Some(color);
+        self
+    }
+
+    /// Sets how the texels are stored, instead of picking from the pixel data.
+    pub fn internal_format(mut self, format: InternalFormat) -> Self {
+        self.internal_format = Some(format);
+        self
+    }
+
+    /// Sets if mipmaps are generated after the image is uploaded.
+    ///
+    /// Without mipmaps the texture only has its base level, so a mipmapped
+    /// [`MinFilter`] just reads from that.
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// Sets if images (not raw pixels) are flipped upside down on upload.
+    ///
+    /// Image files store the top row first, but GL expects the bottom row
+    /// first, so without this images come out upside down.
+    pub fn flip_vertically(mut self, flip: bool) -> Self {
+        self.flip_vertically = flip;
+        self
+    }
+
+    /// Sets if 8-bit color data is treated as sRGB encoded, which is how most
+    /// image files are stored.
+    ///
+    /// With this on, the texture gets an sRGB internal format (unless one was
+    /// set with [`internal_format`](Self::internal_format)), and GL converts
+    /// the colors to linear values when the texture is sampled. Grayscale,
+    /// 16-bit and float data are always linear.
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Sets where each channel (red, green, blue, alpha) of a sample comes
+    /// from.
+    ///
+    /// Grayscale images get a swizzle that copies the gray into every color
+    /// channel unless another one is set here.
+    pub fn swizzle(mut self, swizzle: [Swizzle; 4]) -> Self {
+        self.swizzle = Some(swizzle);
+        self
+    }
+
+    /// Picks the internal format for pixel data, from the one that was set
+    /// or else from the data itself.
+    pub(crate) fn internal_format_for(&self, format: PixelFormat, ty: PixelType) -> InternalFormat {
+        self.internal_format.unwrap_or_else(|| {
+            let internal = format.default_internal_format(ty);
+            if self.srgb {
+                internal.to_srgb()
+            } else {
+                internal
+            }
+        })
+    }
+
+    /// Flips the image if that was asked for, and converts it to something
+    /// that [`PixelData::from_image`] takes if it isn't already.
+    pub(crate) fn prepare_image<'a>(&self, image: &'a DynamicImage) -> Cow<'a, DynamicImage> {
+        let image = if self.flip_vertically {
+            Cow::Owned(image.flipv())
+        } else {
+            Cow::Borrowed(image)
+        };
+        if PixelData::from_image(&image).is_some() {
+            image
+        } else {
+            Cow::Owned(DynamicImage::ImageRgba32F(image.to_rgba32f()))
+        }
+    }
+
+    /// Sets the swizzle on the texture bound to `target`, using `fallback`
+    /// if none was chosen.
+    pub(crate) fn apply_swizzle(&self, target: GLenum, fallback: Option<[Swizzle; 4]>) {
+        if let Some(swizzle) = self.swizzle.or(fallback) {
+            let swizzle = swizzle.map(|s| s as GLint);
+            unsafe { gl::TexParameteriv(target, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr()) };
+        }
+    }
+
+    /// Sets the parameters on the texture bound to `target`, before its
+    /// image is uploaded.
+    pub(crate) fn apply_params(&self, target: GLenum) {
+        let wrap_names = [gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R];
+        unsafe {
+            for (name, wrap) in wrap_names.into_iter().zip(self.wrap) {
+                gl::TexParameteri(target, name, wrap as GLint);
+            }
+            gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, self.min_filter as GLint);
+            gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, self.mag_filter as GLint);
+            if let Some(color) = self.border_color {
+                gl::TexParameterfv(target, gl::TEXTURE_BORDER_COLOR, color.as_ptr());
+            }
+            if !self.mipmaps {
+                gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, 0);
+            }
+            if let Some(max) = self.anisotropy.and_then(clamp_anisotropy) {
+                gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, max);
+            }
+        }
+    }
+
+    /// Makes a texture of type `ty` (an array or 3D texture) and gives it a
+    /// `width` by `height` by `depth` image, with parameters set but no
+    /// mipmaps yet.
+    pub(crate) fn upload_3d(
+        &self,
+        ty: TextureType,
+        [width, height, depth]: [u32; 3],
+        pixels: Option<PixelData<'_>>,
+        (format, pixel_type): (PixelFormat, PixelType),
+        swizzle: Option<[Swizzle; 4]>,
+    ) -> Result<(Texture, InternalFormat), TextureError> {
+        let texture = Texture::new().ok_or(TextureError::Allocation)?;
+        let internal_format = self.internal_format_for(format, pixel_type);
+        let target = ty as GLenum;
+        texture.bind(ty);
+        self.apply_params(target);
+        self.apply_swizzle(target, swizzle);
+        if let Some(pixels) = pixels {
+            pixels.set_unpack_alignment(width);
+        }
+        unsafe {
+            gl::TexImage3D(
+                target,
+                0,
+                internal_format as GLint,
+                GLsizei::try_from(width).unwrap(),
+                GLsizei::try_from(height).unwrap(),
+                GLsizei::try_from(depth).unwrap(),
+                0,
+                format as GLenum,
+                pixel_type as GLenum,
+                pixels.map_or(core::ptr::null(), |p| p.bytes.as_ptr().cast()),
+            );
+        }
+        Ok((texture, internal_format))
+    }
+
+    /// Makes a texture of type `ty` (an array or 3D texture) with one image
+    /// per layer, and mipmaps if they're wanted.
+    ///
+    /// The images must all be the same size and format once prepared.
+    pub(crate) fn upload_layers(
+        &self,
+        ty: TextureType,
+        images: &[DynamicImage],
+    ) -> Result<(Texture, InternalFormat, [u32; 3]), TextureError> {
+        let images: Vec<_> = images.iter().map(|i| self.prepare_image(i)).collect();
+        let first = images.first().ok_or(TextureError::NoImages)?;
+        let (width, height) = (first.width(), first.height());
+        let layers: Vec<_> = images
+            .iter()
+            .map(|image| PixelData::from_image(image).unwrap())
+            .collect();
+        let (format, pixel_type) = (layers[0].format, layers[0].ty);
+        let same = |(image, layer): (&Cow<'_, DynamicImage>, &PixelData<'_>)| {
+            image.width() == width
+                && image.height() == height
+                && layer.format == format
+                && layer.ty == pixel_type
+        };
+        if !images.iter().zip(&layers).all(same) {
+            return Err(TextureError::SizeMismatch);
+        }
+        let depth = u32::try_from(layers.len()).unwrap();
+        let size = [width, height, depth];
+        let (texture, internal_format) =
+            self.upload_3d(ty, size, None, (format, pixel_type), image_swizzle(format))?;
+        layers[0].set_unpack_alignment(width);
+        for (layer, pixels) in (0..).zip(&layers) {
+            unsafe {
+                gl::TexSubImage3D(
+                    ty as GLenum,
+                    0,
+                    0,
+                    0,
+                    layer,
+                    GLsizei::try_from(width).unwrap(),
+                    GLsizei::try_from(height).unwrap(),
+                    1,
+                    format as GLenum,
+                    pixel_type as GLenum,
+                    pixels.bytes.as_ptr().cast(),
+                );
+            }
+        }
+        self.finish(ty as GLenum);
+        Ok((texture, internal_format, size))
+    }
+
+    /// Generates mipmaps for the texture bound to `target`, if they're wanted,
+    /// after its image is uploaded.
+    pub(crate) fn finish(&self, target: GLenum) {
+        if self.mipmaps {
+            unsafe { gl::GenerateMipmap(target) };
+        }
+    }
+}
+
+/// Checks that there's enough pixel data for a `width` by `height` by
+/// `depth` image.
+pub(crate) fn check_len(
+    pixels: &PixelData<'_>,
+    width: u32,
+    height: u32,
+    depth: u32,
+) -> Result<(), TextureError> {
+    let needed = pixels.required_len(width, height, depth);
+    if pixels.bytes.len() < needed {
+        Err(TextureError::DataTooShort {
+            needed,
+            given: pixels.bytes.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that a region starting at `start` with size `len` fits within
+/// `size` on every axis.
+pub(crate) fn region_fits<const N: usize>(start: [u32; N], len: [u32; N], size: [u32; N]) -> bool {
+    (0..N).all(|i| {
+        start[i]
+            .checked_add(len[i])
+            .is_some_and(|end| end <= size[i])
+    })
+}
+
+/// Replaces a region of the base level of the array or 3D texture bound to
+/// `target`, which has the size `texture_size`.
+pub(crate) fn sub_image_3d(
+    target: GLenum,
+    offset: [u32; 3],
+    size: [u32; 3],
+    texture_size: [u32; 3],
+    pixels: PixelData<'_>,
+) -> Result<(), TextureError> {
+    if !region_fits(offset, size, texture_size) {
+        return Err(TextureError::OutOfBounds);
+    }
+    let [width, height, depth] = size;
+    check_len(&pixels, width, height, depth)?;
+    pixels.set_unpack_alignment(width);
+    let [x, y, z] = offset.map(|n| GLint::try_from(n).unwrap());
+    let [width, height, depth] = size.map(|n| GLsizei::try_from(n).unwrap());
+    unsafe {
+        gl::TexSubImage3D(
+            target,
+            0,
+            x,
+            y,
+            z,
+            width,
+            height,
+            depth,
+            pixels.format as GLenum,
+            pixels.ty as GLenum,
+            pixels.bytes.as_ptr().cast(),
+        );
+    }
+    Ok(())
+}
+
+/// The swizzle that makes image data of the given format look right, which
+/// is copying the gray into every color channel for grayscale images.
+pub(crate) fn image_swizzle(format: PixelFormat) -> Option<[Swizzle; 4]> {
+    use Swizzle::*;
+    match format {
+        PixelFormat::Red => Some([Red, Red, Red, One]),
+        PixelFormat::Rg => Some([Red, Red, Red, Green]),
+        _ => None,
+    }
+}
+
+/// Clamps an anisotropy to what the driver allows, or gives `None` if
+/// anisotropic filtering isn't supported.
+pub(crate) fn clamp_anisotropy(max: f32) -> Option<f32> {
+    let supported = gl_version().at_least(4, 6)
+        || has_extension("GL_ARB_texture_filter_anisotropic")
+        || has_extension("GL_EXT_texture_filter_anisotropic");
+    if !supported {
+        return None;
+    }
+    let mut limit: GLfloat = 1.0;
+    unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut limit) };
+    Some(max.clamp(1.0, limit))
+}
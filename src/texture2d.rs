@@ -0,0 +1,232 @@
+//! Two dimensional textures, made from pixels or images.
+
+use crate::texture::{check_len, image_swizzle, region_fits};
+use crate::{
+    InternalFormat, PixelData, PixelFormat, PixelType, Swizzle, Texture, TextureBuilder,
+    TextureContainer, TextureError, TextureType,
+};
+use gl::types::{GLenum, GLint, GLsizei};
+use image::DynamicImage;
+use std::path::Path;
+
+/// A two dimensional texture, along with its size and format.
+///
+/// The texture is deleted when this value is dropped.
+pub struct Texture2D {
+    texture: Texture,
+    width: u32,
+    height: u32,
+    internal_format: InternalFormat,
+}
+impl Texture2D {
+    /// Starts building a new texture.
+    pub fn builder() -> TextureBuilder<Texture2D> {
+        TextureBuilder::default()
+    }
+
+    /// Bind this texture to the active texture unit.
+    pub fn bind(&self) {
+        self.texture.bind(TextureType::Texture2D);
+    }
+
+    /// Clear the 2D texture binding of the active texture unit.
+    pub fn clear_binding() {
+        Texture::clear_binding(TextureType::Texture2D);
+    }
+
+    /// The underlying texture object.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Gives up the size and format, keeping just the texture object.
+    pub fn into_texture(self) -> Texture {
+        self.texture
+    }
+
+    /// The width in texels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height in texels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// How the texels are stored.
+    pub fn internal_format(&self) -> InternalFormat {
+        self.internal_format
+    }
+
+    /// Replaces a `width` by `height` region of the base level, with its
+    /// bottom left corner at `(x, y)`.
+    ///
+    /// Mipmaps aren't updated, so call [`generate_mipmaps`](Self::generate_mipmaps)
+    /// after this if the texture has them. This leaves the texture bound to the
+    /// active texture unit.
+    pub fn sub_image(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: PixelData<'_>,
+    ) -> Result<(), TextureError> {
+        if !region_fits([x, y], [width, height], [self.width, self.height]) {
+            return Err(TextureError::OutOfBounds);
+        }
+        check_len(&pixels, width, height, 1)?;
+        self.bind();
+        pixels.set_unpack_alignment(width);
+        unsafe {
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                GLint::try_from(x).unwrap(),
+                GLint::try_from(y).unwrap(),
+                GLsizei::try_from(width).unwrap(),
+                GLsizei::try_from(height).unwrap(),
+                pixels.format as GLenum,
+                pixels.ty as GLenum,
+                pixels.bytes.as_ptr().cast(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Regenerates the mipmaps from the base level.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn generate_mipmaps(&self) {
+        self.bind();
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_2D) };
+    }
+}
+
+impl TextureBuilder<Texture2D> {
+    /// Makes the texture from raw pixel data.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_pixels(
+        self,
+        width: u32,
+        height: u32,
+        pixels: PixelData<'_>,
+    ) -> Result<Texture2D, TextureError> {
+        check_len(&pixels, width, height, 1)?;
+        self.upload(width, height, Some(pixels), pixels.format, pixels.ty, None)
+    }
+
+    /// Makes the texture with room for a `width` by `height` image, but
+    /// without any contents, such as for rendering into.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn empty(self, width: u32, height: u32) -> Result<Texture2D, TextureError> {
+        let (format, ty) = self
+            .internal_format
+            .map_or((PixelFormat::Rgba, PixelType::UnsignedByte), |f| {
+                f.compatible_pixels()
+            });
+        self.upload(width, height, None, format, ty, None)
+    }
+
+    /// Makes the texture from an image.
+    ///
+    /// The texture gets a format matching the image's, so grayscale images
+    /// stay one channel and 16-bit and float images keep their precision.
+    /// Image types that GL has no format for are converted to float RGBA.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_image(self, image: &DynamicImage) -> Result<Texture2D, TextureError> {
+        let image = self.prepare_image(image);
+        let pixels = PixelData::from_image(&image).unwrap();
+        let swizzle = image_swizzle(pixels.format);
+        self.upload(
+            image.width(),
+            image.height(),
+            Some(pixels),
+            pixels.format,
+            pixels.ty,
+            swizzle,
+        )
+    }
+
+    /// Opens an image file and makes the texture from it.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn open(self, path: impl AsRef<Path>) -> Result<Texture2D, TextureError> {
+        let image = image::open(path)?;
+        self.from_image(&image)
+    }
+
+    /// Makes the texture from a [`TextureContainer`], uploading its mip
+    /// levels as they are (or generating them if it only has the base level
+    /// and isn't compressed).
+    ///
+    /// Compressed formats keep their own format, apart from being switched to
+    /// their sRGB version by [`srgb`](Self::srgb). Fails with
+    /// [`TextureError::UnsupportedFormat`] if the context can't use the format.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_container(
+        self,
+        container: &TextureContainer<'_>,
+    ) -> Result<Texture2D, TextureError> {
+        let (texture, internal_format) =
+            self.upload_container(container, TextureType::Texture2D)?;
+        Ok(Texture2D {
+            texture,
+            width: container.width(),
+            height: container.height(),
+            internal_format,
+        })
+    }
+
+    /// Opens a KTX, KTX2 or DDS file and makes the texture from it.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn open_container(self, path: impl AsRef<Path>) -> Result<Texture2D, TextureError> {
+        let bytes = std::fs::read(path)?;
+        self.from_container(&TextureContainer::parse(&bytes)?)
+    }
+
+    fn upload(
+        self,
+        width: u32,
+        height: u32,
+        pixels: Option<PixelData<'_>>,
+        format: PixelFormat,
+        ty: PixelType,
+        swizzle: Option<[Swizzle; 4]>,
+    ) -> Result<Texture2D, TextureError> {
+        let texture = Texture::new().ok_or(TextureError::Allocation)?;
+        let internal_format = self.internal_format_for(format, ty);
+        texture.bind(TextureType::Texture2D);
+        self.apply_params(gl::TEXTURE_2D);
+        self.apply_swizzle(gl::TEXTURE_2D, swizzle);
+        if let Some(pixels) = pixels {
+            pixels.set_unpack_alignment(width);
+        }
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as GLint,
+                GLsizei::try_from(width).unwrap(),
+                GLsizei::try_from(height).unwrap(),
+                0,
+                format as GLenum,
+                ty as GLenum,
+                pixels.map_or(core::ptr::null(), |p| p.bytes.as_ptr().cast()),
+            );
+        }
+        self.finish(gl::TEXTURE_2D);
+        Ok(Texture2D {
+            texture,
+            width,
+            height,
+            internal_format,
+        })
+    }
+}
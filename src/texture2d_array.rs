@@ -0,0 +1,260 @@
+//! Arrays of two dimensional textures, such as terrain layers or sprite
+//! sheets.
+
+use crate::texture::{check_len, sub_image_3d};
+use crate::{
+    InternalFormat, PixelData, PixelFormat, PixelType, Texture, TextureBuilder, TextureContainer,
+    TextureError, TextureType,
+};
+use image::DynamicImage;
+use std::path::Path;
+
+/// A stack of same-sized two dimensional textures, along with its size and
+/// format.
+///
+/// Shaders read these with a `sampler2DArray`, picking the layer with the
+/// third texture coordinate. Unlike a 3D texture, there's no filtering
+/// between layers.
+///
+/// The texture is deleted when this value is dropped.
+pub struct Texture2DArray {
+    texture: Texture,
+    width: u32,
+    height: u32,
+    layers: u32,
+    internal_format: InternalFormat,
+}
+impl Texture2DArray {
+    /// Starts building a new texture.
+    pub fn builder() -> TextureBuilder<Texture2DArray> {
+        TextureBuilder::default()
+    }
+
+    /// Bind this texture to the active texture unit.
+    pub fn bind(&self) {
+        self.texture.bind(TextureType::Texture2DArray);
+    }
+
+    /// Clear the 2D array texture binding of the active texture unit.
+    pub fn clear_binding() {
+        Texture::clear_binding(TextureType::Texture2DArray);
+    }
+
+    /// The underlying texture object.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Gives up the size and format, keeping just the texture object.
+    pub fn into_texture(self) -> Texture {
+        self.texture
+    }
+
+    /// The width of each layer in texels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of each layer in texels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The number of layers.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// How the texels are stored.
+    pub fn internal_format(&self) -> InternalFormat {
+        self.internal_format
+    }
+
+    /// Replaces a `width` by `height` region of the base level of one layer,
+    /// with its bottom left corner at `(x, y)`.
+    ///
+    /// Mipmaps aren't updated, so call [`generate_mipmaps`](Self::generate_mipmaps)
+    /// after this if the texture has them. This leaves the texture bound to the
+    /// active texture unit.
+    pub fn sub_image(
+        &self,
+        layer: u32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: PixelData<'_>,
+    ) -> Result<(), TextureError> {
+        self.bind();
+        sub_image_3d(
+            gl::TEXTURE_2D_ARRAY,
+            [x, y, layer],
+            [width, height, 1],
+            [self.width, self.height, self.layers],
+            pixels,
+        )
+    }
+
+    /// Regenerates the mipmaps from the base level.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn generate_mipmaps(&self) {
+        self.bind();
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY) };
+    }
+}
+
+impl TextureBuilder<Texture2DArray> {
+    /// Makes the texture from raw pixel data, with the layers one after
+    /// another.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_pixels(
+        self,
+        width: u32,
+        height: u32,
+        layers: u32,
+        pixels: PixelData<'_>,
+    ) -> Result<Texture2DArray, TextureError> {
+        check_len(&pixels, width, height, layers)?;
+        self.upload(
+            width,
+            height,
+            layers,
+            Some(pixels),
+            pixels.format,
+            pixels.ty,
+        )
+    }
+
+    /// Makes the texture with room for `layers` images of `width` by
+    /// `height`, but without any contents.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn empty(
+        self,
+        width: u32,
+        height: u32,
+        layers: u32,
+    ) -> Result<Texture2DArray, TextureError> {
+        let (format, ty) = self
+            .internal_format
+            .map_or((PixelFormat::Rgba, PixelType::UnsignedByte), |f| {
+                f.compatible_pixels()
+            });
+        self.upload(width, height, layers, None, format, ty)
+    }
+
+    /// Makes the texture from a list of images, one per layer.
+    ///
+    /// The images must all be the same size and type. As with
+    /// [`Texture2D`](crate::Texture2D)s, the texture gets a format matching
+    /// the images'.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_images(self, images: &[DynamicImage]) -> Result<Texture2DArray, TextureError> {
+        let (texture, internal_format, [width, height, layers]) =
+            self.upload_layers(TextureType::Texture2DArray, images)?;
+        Ok(Texture2DArray {
+            texture,
+            width,
+            height,
+            layers,
+            internal_format,
+        })
+    }
+
+    /// Makes the texture from a sprite sheet, split into a grid of `columns`
+    /// by `rows` same-sized sprites.
+    ///
+    /// The sprites become the layers in reading order, starting with the top
+    /// left sprite and going along each row.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_sprite_sheet(
+        self,
+        image: &DynamicImage,
+        columns: u32,
+        rows: u32,
+    ) -> Result<Texture2DArray, TextureError> {
+        let (width, height) = (image.width(), image.height());
+        if columns == 0
+            || rows == 0
+            || !width.is_multiple_of(columns)
+            || !height.is_multiple_of(rows)
+        {
+            return Err(TextureError::InvalidLayout { width, height });
+        }
+        let (sprite_width, sprite_height) = (width / columns, height / rows);
+        let sprites: Vec<_> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                image.crop_imm(
+                    column * sprite_width,
+                    row * sprite_height,
+                    sprite_width,
+                    sprite_height,
+                )
+            })
+            .collect();
+        self.from_images(&sprites)
+    }
+
+    /// Makes the texture from a [`TextureContainer`], uploading its mip
+    /// levels as they are (or generating them if it only has the base level
+    /// and isn't compressed).
+    ///
+    /// Compressed formats keep their own format, apart from being switched to
+    /// their sRGB version by [`srgb`](Self::srgb). Fails with
+    /// [`TextureError::UnsupportedFormat`] if the context can't use the format.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_container(
+        self,
+        container: &TextureContainer<'_>,
+    ) -> Result<Texture2DArray, TextureError> {
+        let (texture, internal_format) =
+            self.upload_container(container, TextureType::Texture2DArray)?;
+        Ok(Texture2DArray {
+            texture,
+            width: container.width(),
+            height: container.height(),
+            layers: container.layers(),
+            internal_format,
+        })
+    }
+
+    /// Opens a KTX, KTX2 or DDS file and makes the texture from it.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn open_container(self, path: impl AsRef<Path>) -> Result<Texture2DArray, TextureError> {
+        let bytes = std::fs::read(path)?;
+        self.from_container(&TextureContainer::parse(&bytes)?)
+    }
+
+    fn upload(
+        self,
+        width: u32,
+        height: u32,
+        layers: u32,
+        pixels: Option<PixelData<'_>>,
+        format: PixelFormat,
+        ty: PixelType,
+    ) -> Result<Texture2DArray, TextureError> {
+        let (texture, internal_format) = self.upload_3d(
+            TextureType::Texture2DArray,
+            [width, height, layers],
+            pixels,
+            (format, ty),
+            None,
+        )?;
+        self.finish(gl::TEXTURE_2D_ARRAY);
+        Ok(Texture2DArray {
+            texture,
+            width,
+            height,
+            layers,
+            internal_format,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+//! Three dimensional textures, for volume data.
+
+use crate::texture::{check_len, sub_image_3d};
+use crate::{
+    InternalFormat, PixelData, PixelFormat, PixelType, Texture, TextureBuilder, TextureContainer,
+    TextureError, TextureType,
+};
+use image::DynamicImage;
+use std::path::Path;
+
+/// A three dimensional texture, along with its size and format.
+///
+/// Shaders read these with a `sampler3D`, and samples are filtered between
+/// slices as well as within them.
+///
+/// The texture is deleted when this value is dropped.
+pub struct Texture3D {
+    texture: Texture,
+    width: u32,
+    height: u32,
+    depth: u32,
+    internal_format: InternalFormat,
+}
+impl Texture3D {
+    /// Starts building a new texture.
+    pub fn builder() -> TextureBuilder<Texture3D> {
+        TextureBuilder::default()
+    }
+
+    /// Bind this texture to the active texture unit.
+    pub fn bind(&self) {
+        self.texture.bind(TextureType::Texture3D);
+    }
+
+    /// Clear the 3D texture binding of the active texture unit.
+    pub fn clear_binding() {
+        Texture::clear_binding(TextureType::Texture3D);
+    }
+
+    /// The underlying texture object.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Gives up the size and format, keeping just the texture object.
+    pub fn into_texture(self) -> Texture {
+        self.texture
+    }
+
+    /// The width in texels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height in texels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The depth in texels.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// How the texels are stored.
+    pub fn internal_format(&self) -> InternalFormat {
+        self.internal_format
+    }
+
+    /// Replaces a region of the base level, with its nearest bottom left
+    /// corner at `offset` and the given `[width, height, depth]` size.
+    ///
+    /// Mipmaps aren't updated, so call [`generate_mipmaps`](Self::generate_mipmaps)
+    /// after this if the texture has them. This leaves the texture bound to the
+    /// active texture unit.
+    pub fn sub_image(
+        &self,
+        offset: [u32; 3],
+        size: [u32; 3],
+        pixels: PixelData<'_>,
+    ) -> Result<(), TextureError> {
+        self.bind();
+        sub_image_3d(
+            gl::TEXTURE_3D,
+            offset,
+            size,
+            [self.width, self.height, self.depth],
+            pixels,
+        )
+    }
+
+    /// Regenerates the mipmaps from the base level.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn generate_mipmaps(&self) {
+        self.bind();
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_3D) };
+    }
+}
+
+impl TextureBuilder<Texture3D> {
+    /// Makes the texture from raw pixel data, with the slices one after
+    /// another.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_pixels(
+        self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        pixels: PixelData<'_>,
+    ) -> Result<Texture3D, TextureError> {
+        check_len(&pixels, width, height, depth)?;
+        self.upload(width, height, depth, Some(pixels), pixels.format, pixels.ty)
+    }
+
+    /// Makes the texture with room for a `width` by `height` by `depth`
+    /// volume, but without any contents.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn empty(self, width: u32, height: u32, depth: u32) -> Result<Texture3D, TextureError> {
+        let (format, ty) = self
+            .internal_format
+            .map_or((PixelFormat::Rgba, PixelType::UnsignedByte), |f| {
+                f.compatible_pixels()
+            });
+        self.upload(width, height, depth, None, format, ty)
+    }
+
+    /// Makes the texture from a list of images, one per slice, starting from
+    /// `z = 0`.
+    ///
+    /// The images must all be the same size and type. As with
+    /// [`Texture2D`](crate::Texture2D)s, the texture gets a format matching
+    /// the images'.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_images(self, images: &[DynamicImage]) -> Result<Texture3D, TextureError> {
+        let (texture, internal_format, [width, height, depth]) =
+            self.upload_layers(TextureType::Texture3D, images)?;
+        Ok(Texture3D {
+            texture,
+            width,
+            height,
+            depth,
+            internal_format,
+        })
+    }
+
+    /// Makes the texture from a [`TextureContainer`], uploading its mip
+    /// levels as they are (or generating them if it only has the base level
+    /// and isn't compressed).
+    ///
+    /// Compressed formats keep their own format, apart from being switched to
+    /// their sRGB version by [`srgb`](Self::srgb). Fails with
+    /// [`TextureError::UnsupportedFormat`] if the context can't use the format.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_container(
+        self,
+        container: &TextureContainer<'_>,
+    ) -> Result<Texture3D, TextureError> {
+        let (texture, internal_format) =
+            self.upload_container(container, TextureType::Texture3D)?;
+        Ok(Texture3D {
+            texture,
+            width: container.width(),
+            height: container.height(),
+            depth: container.depth(),
+            internal_format,
+        })
+    }
+
+    /// Opens a KTX, KTX2 or DDS file and makes the texture from it.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn open_container(self, path: impl AsRef<Path>) -> Result<Texture3D, TextureError> {
+        let bytes = std::fs::read(path)?;
+        self.from_container(&TextureContainer::parse(&bytes)?)
+    }
+
+    fn upload(
+        self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        pixels: Option<PixelData<'_>>,
+        format: PixelFormat,
+        ty: PixelType,
+    ) -> Result<Texture3D, TextureError> {
+        let (texture, internal_format) = self.upload_3d(
+            TextureType::Texture3D,
+            [width, height, depth],
+            pixels,
+            (format, ty),
+            None,
+        )?;
+        self.finish(gl::TEXTURE_3D);
+        Ok(Texture3D {
+            texture,
+            width,
+            height,
+            depth,
+            internal_format,
+        })
+    }
+}
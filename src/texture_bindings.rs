@@ -0,0 +1,206 @@
+//! Pointing a program's samplers at textures without tracking texture units.
+
+use crate::{
+    active_texture, GlslType, Sampler, ShaderProgram, Texture, Texture2D, Texture2DArray,
+    Texture3D, TextureCube, TextureType, TextureUnit, UniformError,
+};
+use core::fmt;
+use gl::types::GLint;
+
+/// A texture that can be bound for a program to sample.
+pub trait BindableTexture {
+    /// The texture object.
+    fn texture(&self) -> &Texture;
+
+    /// The target the texture is bound to.
+    fn texture_type(&self) -> TextureType;
+}
+impl BindableTexture for Texture2D {
+    fn texture(&self) -> &Texture {
+        Texture2D::texture(self)
+    }
+    fn texture_type(&self) -> TextureType {
+        TextureType::Texture2D
+    }
+}
+impl BindableTexture for Texture2DArray {
+    fn texture(&self) -> &Texture {
+        Texture2DArray::texture(self)
+    }
+    fn texture_type(&self) -> TextureType {
+        TextureType::Texture2DArray
+    }
+}
+impl BindableTexture for Texture3D {
+    fn texture(&self) -> &Texture {
+        Texture3D::texture(self)
+    }
+    fn texture_type(&self) -> TextureType {
+        TextureType::Texture3D
+    }
+}
+impl BindableTexture for TextureCube {
+    fn texture(&self) -> &Texture {
+        TextureCube::texture(self)
+    }
+    fn texture_type(&self) -> TextureType {
+        TextureType::CubeMap
+    }
+}
+
+impl GlslType {
+    /// The texture type that a sampler of this type reads, or `None` for
+    /// other types and samplers that this crate has no texture type for.
+    pub fn sampler_target(self) -> Option<TextureType> {
+        let name = self.name();
+        let name = name.strip_prefix(['i', 'u']).unwrap_or(name);
+        let shape = name.strip_prefix("sampler")?;
+        let shape = shape.strip_suffix("Shadow").unwrap_or(shape);
+        match shape {
+            "2D" => Some(TextureType::Texture2D),
+            "2DArray" => Some(TextureType::Texture2DArray),
+            "3D" => Some(TextureType::Texture3D),
+            "Cube" => Some(TextureType::CubeMap),
+            _ => None,
+        }
+    }
+}
+
+/// The textures (and optionally samplers) that a program's sampler uniforms
+/// should read, for [`ShaderProgram::bind_textures`].
+#[derive(Default)]
+pub struct TextureBindings<'a> {
+    entries: Vec<(&'a str, &'a dyn BindableTexture, Option<&'a Sampler>)>,
+}
+impl<'a> TextureBindings<'a> {
+    /// Makes an empty set of bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Has the named sampler uniform read `texture`, with the texture's own
+    /// parameters.
+    pub fn texture(mut self, uniform: &'a str, texture: &'a dyn BindableTexture) -> Self {
+        self.entries.push((uniform, texture, None));
+        self
+    }
+
+    /// Has the named sampler uniform read `texture`, with the parameters of
+    /// `sampler`.
+    pub fn texture_with_sampler(
+        mut self,
+        uniform: &'a str,
+        texture: &'a dyn BindableTexture,
+        sampler: &'a Sampler,
+    ) -> Self {
+        self.entries.push((uniform, texture, Some(sampler)));
+        self
+    }
+
+    /// The number of textures.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// If there are no textures.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// The ways that binding textures for a program can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureBindingError {
+    /// A sampler uniform couldn't be set.
+    Uniform(UniformError),
+    /// There are more textures than the driver has texture units.
+    TooManyTextures {
+        /// The number of texture units.
+        units: usize,
+        /// The number of textures given.
+        given: usize,
+    },
+}
+impl fmt::Display for TextureBindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureBindingError::Uniform(e) => e.fmt(f),
+            TextureBindingError::TooManyTextures { units, given } => {
+                write!(
+                    f,
+                    "{given} textures were given, but there are only {units} texture units"
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for TextureBindingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureBindingError::Uniform(e) => Some(e),
+            TextureBindingError::TooManyTextures { .. } => None,
+        }
+    }
+}
+impl From<UniformError> for TextureBindingError {
+    fn from(e: UniformError) -> Self {
+        TextureBindingError::Uniform(e)
+    }
+}
+
+fn type_name(ty: TextureType) -> &'static str {
+    match ty {
+        TextureType::Texture2D => "2D texture",
+        TextureType::Texture2DArray => "2D array texture",
+        TextureType::Texture3D => "3D texture",
+        TextureType::CubeMap => "cube map texture",
+    }
+}
+
+impl ShaderProgram {
+    /// Binds each texture to a texture unit of its own, and sets each sampler
+    /// uniform to the unit its texture is on.
+    ///
+    /// Units are handed out in order starting from unit `0`, so do this before
+    /// each draw that uses the textures, since other draws may have bound other
+    /// textures to those units. Textures without a sampler get the sampler
+    /// binding of their unit cleared.
+    ///
+    /// Note: This makes the program the program in use, and leaves the last
+    /// unit as the active texture unit.
+    pub fn bind_textures(&self, bindings: &TextureBindings<'_>) -> Result<(), TextureBindingError> {
+        let mut units: GLint = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut units) };
+        let units = usize::try_from(units).unwrap_or(0);
+        if bindings.len() > units {
+            return Err(TextureBindingError::TooManyTextures {
+                units,
+                given: bindings.len(),
+            });
+        }
+        for (unit, &(uniform, texture, sampler)) in (0_u32..).zip(&bindings.entries) {
+            let slot = self
+                .uniform_slot(uniform)
+                .ok_or_else(|| UniformError::NotFound {
+                    name: uniform.to_string(),
+                })?;
+            let ty = texture.texture_type();
+            if slot.ty.sampler_target().is_some_and(|target| target != ty) {
+                return Err(UniformError::TypeMismatch {
+                    name: uniform.to_string(),
+                    declared: slot.ty,
+                    given: type_name(ty),
+                }
+                .into());
+            }
+            active_texture(unit);
+            texture.texture().bind(ty);
+            match sampler {
+                Some(sampler) => sampler.bind(unit),
+                None => Sampler::clear_binding(unit),
+            }
+            self.set_uniform(uniform, TextureUnit(unit))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+//! Cube map textures, such as for skyboxes and environment maps.
+
+use crate::texture::{check_len, image_swizzle, region_fits};
+use crate::{
+    InternalFormat, PixelData, PixelFormat, PixelType, Swizzle, Texture, TextureBuilder,
+    TextureContainer, TextureError, TextureType,
+};
+use core::f32::consts::{PI, TAU};
+use gl::types::{GLenum, GLint, GLsizei};
+use image::imageops::sample_bilinear;
+use image::{DynamicImage, Rgba32FImage};
+use std::path::Path;
+
+/// The faces of a cube map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CubeFace {
+    /// The face in the `+x` direction.
+    PositiveX = gl::TEXTURE_CUBE_MAP_POSITIVE_X as isize,
+    /// The face in the `-x` direction.
+    NegativeX = gl::TEXTURE_CUBE_MAP_NEGATIVE_X as isize,
+    /// The face in the `+y` direction.
+    PositiveY = gl::TEXTURE_CUBE_MAP_POSITIVE_Y as isize,
+    /// The face in the `-y` direction.
+    NegativeY = gl::TEXTURE_CUBE_MAP_NEGATIVE_Y as isize,
+    /// The face in the `+z` direction.
+    PositiveZ = gl::TEXTURE_CUBE_MAP_POSITIVE_Z as isize,
+    /// The face in the `-z` direction.
+    NegativeZ = gl::TEXTURE_CUBE_MAP_NEGATIVE_Z as isize,
+}
+impl CubeFace {
+    /// Every face, in the order GL numbers them (and the order that lists of
+    /// faces are given in).
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    /// The direction through a point on this face, where `u` and `v` go from
+    /// `-1` to `1` along the face's rows and columns as they're stored.
+    fn direction(self, u: f32, v: f32) -> [f32; 3] {
+        match self {
+            CubeFace::PositiveX => [1.0, -v, -u],
+            CubeFace::NegativeX => [-1.0, -v, u],
+            CubeFace::PositiveY => [u, 1.0, v],
+            CubeFace::NegativeY => [u, -1.0, -v],
+            CubeFace::PositiveZ => [u, -v, 1.0],
+            CubeFace::NegativeZ => [-u, -v, -1.0],
+        }
+    }
+}
+
+/// A cube map texture, along with its size and format.
+///
+/// Shaders read these with a `samplerCube`, using a direction rather than
+/// texture coordinates.
+///
+/// Unlike other textures, GL expects cube map faces with the top row first,
+/// which is how image files are stored, so they shouldn't usually be flipped.
+/// Filtering across the edges between faces also needs
+/// `GL_TEXTURE_CUBE_MAP_SEAMLESS` to be enabled.
+///
+/// The texture is deleted when this value is dropped.
+pub struct TextureCube {
+    texture: Texture,
+    size: u32,
+    internal_format: InternalFormat,
+}
+impl TextureCube {
+    /// Starts building a new texture.
+    pub fn builder() -> TextureBuilder<TextureCube> {
+        TextureBuilder::default()
+    }
+
+    /// Bind this texture to the active texture unit.
+    pub fn bind(&self) {
+        self.texture.bind(TextureType::CubeMap);
+    }
+
+    /// Clear the cube map binding of the active texture unit.
+    pub fn clear_binding() {
+        Texture::clear_binding(TextureType::CubeMap);
+    }
+
+    /// The underlying texture object.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Gives up the size and format, keeping just the texture object.
+    pub fn into_texture(self) -> Texture {
+        self.texture
+    }
+
+    /// The width and height of each face in texels.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// How the texels are stored.
+    pub fn internal_format(&self) -> InternalFormat {
+        self.internal_format
+    }
+
+    /// Replaces a `width` by `height` region of the base level of one face,
+    /// starting at `(x, y)`.
+    ///
+    /// Mipmaps aren't updated, so call [`generate_mipmaps`](Self::generate_mipmaps)
+    /// after this if the texture has them. This leaves the texture bound to the
+    /// active texture unit.
+    pub fn sub_image(
+        &self,
+        face: CubeFace,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: PixelData<'_>,
+    ) -> Result<(), TextureError> {
+        if !region_fits([x, y], [width, height], [self.size; 2]) {
+            return Err(TextureError::OutOfBounds);
+        }
+        check_len(&pixels, width, height, 1)?;
+        self.bind();
+        pixels.set_unpack_alignment(width);
+        unsafe {
+            gl::TexSubImage2D(
+                face as GLenum,
+                0,
+                GLint::try_from(x).unwrap(),
+                GLint::try_from(y).unwrap(),
+                GLsizei::try_from(width).unwrap(),
+                GLsizei::try_from(height).unwrap(),
+                pixels.format as GLenum,
+                pixels.ty as GLenum,
+                pixels.bytes.as_ptr().cast(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Regenerates the mipmaps from the base level.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn generate_mipmaps(&self) {
+        self.bind();
+        unsafe { gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP) };
+    }
+}
+
+impl TextureBuilder<TextureCube> {
+    /// Makes the texture from raw pixel data for each face, in the order of
+    /// [`CubeFace::ALL`].
+    ///
+    /// The faces must all have the same format.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_pixels(
+        self,
+        size: u32,
+        faces: [PixelData<'_>; 6],
+    ) -> Result<TextureCube, TextureError> {
+        let (format, ty) = (faces[0].format, faces[0].ty);
+        if faces.iter().any(|f| f.format != format || f.ty != ty) {
+            return Err(TextureError::SizeMismatch);
+        }
+        for face in &faces {
+            check_len(face, size, size, 1)?;
+        }
+        self.upload(size, Some(faces), format, ty, None)
+    }
+
+    /// Makes the texture with room for faces of `size` by `size`, but without
+    /// any contents.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn empty(self, size: u32) -> Result<TextureCube, TextureError> {
+        let (format, ty) = self
+            .internal_format
+            .map_or((PixelFormat::Rgba, PixelType::UnsignedByte), |f| {
+                f.compatible_pixels()
+            });
+        self.upload(size, None, format, ty, None)
+    }
+
+    /// Makes the texture from an image for each face, in the order of
+    /// [`CubeFace::ALL`].
+    ///
+    /// The images must all be square and have the same size and type. As
+    /// with [`Texture2D`](crate::Texture2D)s, the texture gets a format
+    /// matching the images'.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_faces(self, faces: &[DynamicImage; 6]) -> Result<TextureCube, TextureError> {
+        let faces = faces.each_ref().map(|f| self.prepare_image(f));
+        let pixels = faces.each_ref().map(|f| PixelData::from_image(f).unwrap());
+        let size = faces[0].width();
+        let (format, ty) = (pixels[0].format, pixels[0].ty);
+        let same = faces.iter().zip(&pixels).all(|(face, pixels)| {
+            face.width() == size
+                && face.height() == size
+                && pixels.format == format
+                && pixels.ty == ty
+        });
+        if !same {
+            return Err(TextureError::SizeMismatch);
+        }
+        self.upload(size, Some(pixels), format, ty, image_swizzle(format))
+    }
+
+    /// Opens an image file for each face, in the order of [`CubeFace::ALL`],
+    /// and makes the texture from them.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn open_faces(self, paths: [impl AsRef<Path>; 6]) -> Result<TextureCube, TextureError> {
+        let mut faces = Vec::with_capacity(6);
+        for path in paths {
+            faces.push(image::open(path)?);
+        }
+        let faces: [DynamicImage; 6] = faces.try_into().unwrap();
+        self.from_faces(&faces)
+    }
+
+    /// Makes the texture from a single image with the faces laid out in a
+    /// cross.
+    ///
+    /// A horizontal cross is 4 faces wide and 3 high, with `-x`, `+z`, `+x`
+    /// and `-z` across the middle, `+y` above `+z` and `-y` below it. A
+    /// vertical cross is 3 faces wide and 4 high, with `-x`, `+z` and `+x`
+    /// across, and `+y`, `+z`, `-y` and then `-z` (upside down) going down.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_cross(self, image: &DynamicImage) -> Result<TextureCube, TextureError> {
+        let (width, height) = (image.width(), image.height());
+        // The cell (column, row) of each face, in the order of `CubeFace::ALL`.
+        // Compared as `u64`, so that huge sizes can't overflow.
+        let (wide, high) = (u64::from(width), u64::from(height));
+        let (size, cells) = if wide * 3 == high * 4 && width.is_multiple_of(4) {
+            (width / 4, [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)])
+        } else if wide * 4 == high * 3 && width.is_multiple_of(3) {
+            (width / 3, [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)])
+        } else {
+            return Err(TextureError::InvalidLayout { width, height });
+        };
+        let vertical = height > width;
+        let faces = core::array::from_fn(|i| {
+            let (column, row) = cells[i];
+            let image = image.crop_imm(column * size, row * size, size, size);
+            if vertical && CubeFace::ALL[i] == CubeFace::NegativeZ {
+                image.rotate180()
+            } else {
+                image
+            }
+        });
+        self.from_faces(&faces)
+    }
+
+    /// Makes the texture from an equirectangular (latitude and longitude)
+    /// panorama, which is twice as wide as it is high.
+    ///
+    /// Each face is a quarter of the image's width across, and is resampled
+    /// from the panorama, keeping 16-bit and float precision. The middle of
+    /// the panorama ends up facing `+x`, with its top facing `+y`. Faces are
+    /// made in the orientation GL expects, so flipping doesn't apply. Fails
+    /// with [`TextureError::InvalidLayout`] if the image isn't exactly twice
+    /// as wide as it is high, since the faces would come out stretched.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_equirectangular(self, image: &DynamicImage) -> Result<TextureCube, TextureError> {
+        let (width, height) = (image.width(), image.height());
+        if width < 4 || u64::from(width) != 2 * u64::from(height) {
+            return Err(TextureError::InvalidLayout { width, height });
+        }
+        let size = width / 4;
+        let panorama = image.to_rgba32f();
+        let color = image.color();
+        let faces = CubeFace::ALL.map(|face| {
+            let face = equirectangular_face(&panorama, face, size);
+            match color.bytes_per_pixel() / color.channel_count() {
+                1 => DynamicImage::ImageRgba8(DynamicImage::from(face).into_rgba8()),
+                2 => DynamicImage::ImageRgba16(DynamicImage::from(face).into_rgba16()),
+                _ => DynamicImage::ImageRgba32F(face),
+            }
+        });
+        self.flip_vertically(false).from_faces(&faces)
+    }
+
+    /// Makes the texture from a [`TextureContainer`], uploading its mip
+    /// levels as they are (or generating them if it only has the base level
+    /// and isn't compressed).
+    ///
+    /// Compressed formats keep their own format, apart from being switched to
+    /// their sRGB version by [`srgb`](Self::srgb). Fails with
+    /// [`TextureError::UnsupportedFormat`] if the context can't use the format.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn from_container(
+        self,
+        container: &TextureContainer<'_>,
+    ) -> Result<TextureCube, TextureError> {
+        let (texture, internal_format) = self.upload_container(container, TextureType::CubeMap)?;
+        Ok(TextureCube {
+            texture,
+            size: container.width(),
+            internal_format,
+        })
+    }
+
+    /// Opens a KTX, KTX2 or DDS file and makes the texture from it.
+    ///
+    /// This leaves the texture bound to the active texture unit.
+    pub fn open_container(self, path: impl AsRef<Path>) -> Result<TextureCube, TextureError> {
+        let bytes = std::fs::read(path)?;
+        self.from_container(&TextureContainer::parse(&bytes)?)
+    }
+
+    fn upload(
+        self,
+        size: u32,
+        faces: Option<[PixelData<'_>; 6]>,
+        format: PixelFormat,
+        ty: PixelType,
+        swizzle: Option<[Swizzle; 4]>,
+    ) -> Result<TextureCube, TextureError> {
+        let texture = Texture::new().ok_or(TextureError::Allocation)?;
+        let internal_format = self.internal_format_for(format, ty);
+        texture.bind(TextureType::CubeMap);
+        self.apply_params(gl::TEXTURE_CUBE_MAP);
+        self.apply_swizzle(gl::TEXTURE_CUBE_MAP, swizzle);
+        if let Some(faces) = &faces {
+            faces[0].set_unpack_alignment(size);
+        }
+        for (i, face) in CubeFace::ALL.into_iter().enumerate() {
+            let pixels = faces.as_ref().map(|faces| faces[i]);
+            unsafe {
+                gl::TexImage2D(
+                    face as GLenum,
+                    0,
+                    internal_format as GLint,
+                    GLsizei::try_from(size).unwrap(),
+                    GLsizei::try_from(size).unwrap(),
+                    0,
+                    format as GLenum,
+                    ty as GLenum,
+                    pixels.map_or(core::ptr::null(), |p| p.bytes.as_ptr().cast()),
+                );
+            }
+        }
+        self.finish(gl::TEXTURE_CUBE_MAP);
+        Ok(TextureCube {
+            texture,
+            size,
+            internal_format,
+        })
+    }
+}
+
+/// Resamples one face of a cube map from an equirectangular panorama.
+fn equirectangular_face(panorama: &Rgba32FImage, face: CubeFace, size: u32) -> Rgba32FImage {
+    let to_unit = |n: u32| (n as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    Rgba32FImage::from_fn(size, size, |column, row| {
+        let [x, y, z] = face.direction(to_unit(column), to_unit(row));
+        let length = (x * x + y * y + z * z).sqrt();
+        let u = z.atan2(x) / TAU + 0.5;
+        let v = (y / length).acos() / PI;
+        sample_bilinear(panorama, u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)).unwrap()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::new_rgba8(width, height)
+    }
+
+    // These fail before anything is uploaded, so they don't need a context.
+
+    #[test]
+    fn cross_must_be_4x3_or_3x4_faces() {
+        for (width, height) in [(8, 8), (8, 5), (7, 4), (5, 4), (6, 9)] {
+            let result = TextureCube::builder().from_cross(&image(width, height));
+            assert!(
+                matches!(result, Err(TextureError::InvalidLayout { width: w, height: h }) if (w, h) == (width, height)),
+                "{width}x{height}"
+            );
+        }
+    }
+
+    #[test]
+    fn equirectangular_must_be_twice_as_wide_as_high() {
+        for (width, height) in [(8, 8), (8, 3), (9, 4), (2, 1), (0, 0)] {
+            let result = TextureCube::builder().from_equirectangular(&image(width, height));
+            assert!(
+                matches!(result, Err(TextureError::InvalidLayout { width: w, height: h }) if (w, h) == (width, height)),
+                "{width}x{height}"
+            );
+        }
+    }
+}
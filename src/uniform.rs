@@ -0,0 +1,296 @@
+//! Setting uniform values on a program by name.
+
+use crate::{GlslType, Mat3, Mat4, ShaderProgram, Vec2, Vec3, Vec4};
+use core::fmt;
+use gl::types::{GLint, GLsizei};
+use std::collections::HashMap;
+
+/// A texture unit number, for setting `sampler*` uniforms.
+///
+/// Unit `0` is `GL_TEXTURE0`, unit `1` is `GL_TEXTURE1`, and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureUnit(pub u32);
+
+/// A single value that can be sent to a uniform.
+///
+/// Matrices are given as arrays of columns, the same as GLSL stores them, or
+/// as [`Mat3`] and [`Mat4`].
+pub trait UniformPrimitive: Copy {
+    /// If a uniform declared with the given GLSL type can take this value.
+    fn accepts(ty: GlslType) -> bool;
+
+    /// Uploads `values` to consecutive array elements, starting at `location`.
+    ///
+    /// ## Safety
+    /// The program owning the location must be the program in use.
+    unsafe fn upload(location: GLint, values: &[Self]);
+}
+
+fn count<T>(values: &[T]) -> GLsizei {
+    GLsizei::try_from(values.len()).unwrap()
+}
+
+macro_rules! uniform_primitive {
+    ($t:ty, $f:ident, [$($glsl:ident),+]) => {
+        impl UniformPrimitive for $t {
+            fn accepts(ty: GlslType) -> bool {
+                $(ty == GlslType::$glsl)||+
+            }
+            unsafe fn upload(location: GLint, values: &[Self]) {
+                gl::$f(location, count(values), values.as_ptr().cast());
+            }
+        }
+    };
+}
+macro_rules! uniform_matrix {
+    ($t:ty, $f:ident, $glsl:ident) => {
+        impl UniformPrimitive for $t {
+            fn accepts(ty: GlslType) -> bool {
+                ty == GlslType::$glsl
+            }
+            unsafe fn upload(location: GLint, values: &[Self]) {
+                gl::$f(location, count(values), gl::FALSE, values.as_ptr().cast());
+            }
+        }
+    };
+}
+
+uniform_primitive!(f32, Uniform1fv, [FLOAT]);
+uniform_primitive!([f32; 2], Uniform2fv, [VEC2]);
+uniform_primitive!([f32; 3], Uniform3fv, [VEC3]);
+uniform_primitive!([f32; 4], Uniform4fv, [VEC4]);
+uniform_primitive!(i32, Uniform1iv, [INT, BOOL]);
+uniform_primitive!([i32; 2], Uniform2iv, [IVEC2, BVEC2]);
+uniform_primitive!([i32; 3], Uniform3iv, [IVEC3, BVEC3]);
+uniform_primitive!([i32; 4], Uniform4iv, [IVEC4, BVEC4]);
+uniform_primitive!(u32, Uniform1uiv, [UINT, BOOL]);
+uniform_primitive!([u32; 2], Uniform2uiv, [UVEC2, BVEC2]);
+uniform_primitive!([u32; 3], Uniform3uiv, [UVEC3, BVEC3]);
+uniform_primitive!([u32; 4], Uniform4uiv, [UVEC4, BVEC4]);
+uniform_matrix!([[f32; 2]; 2], UniformMatrix2fv, MAT2);
+uniform_matrix!([[f32; 3]; 3], UniformMatrix3fv, MAT3);
+uniform_matrix!([[f32; 4]; 4], UniformMatrix4fv, MAT4);
+uniform_primitive!(Vec2, Uniform2fv, [VEC2]);
+uniform_primitive!(Vec3, Uniform3fv, [VEC3]);
+uniform_primitive!(Vec4, Uniform4fv, [VEC4]);
+uniform_matrix!(Mat3, UniformMatrix3fv, MAT3);
+uniform_matrix!(Mat4, UniformMatrix4fv, MAT4);
+
+impl UniformPrimitive for bool {
+    fn accepts(ty: GlslType) -> bool {
+        ty == GlslType::BOOL
+    }
+    unsafe fn upload(location: GLint, values: &[Self]) {
+        let ints: Vec<i32> = values.iter().map(|&b| i32::from(b)).collect();
+        <i32 as UniformPrimitive>::upload(location, &ints);
+    }
+}
+impl UniformPrimitive for TextureUnit {
+    fn accepts(ty: GlslType) -> bool {
+        ty.is_sampler()
+    }
+    unsafe fn upload(location: GLint, values: &[Self]) {
+        let units: Vec<i32> = values
+            .iter()
+            .map(|u| GLint::try_from(u.0).unwrap())
+            .collect();
+        <i32 as UniformPrimitive>::upload(location, &units);
+    }
+}
+
+/// Something that [`ShaderProgram::set_uniform`] can send to a uniform.
+///
+/// This is any [`UniformPrimitive`], or a slice of them for array uniforms.
+pub trait UniformValue {
+    /// If a uniform declared with the given GLSL type can take this value.
+    fn accepts(ty: GlslType) -> bool;
+
+    /// How many array elements this value fills.
+    fn array_len(&self) -> usize;
+
+    /// Uploads the value to the location.
+    ///
+    /// ## Safety
+    /// The program owning the location must be the program in use.
+    unsafe fn upload(&self, location: GLint);
+}
+impl<T: UniformPrimitive> UniformValue for T {
+    fn accepts(ty: GlslType) -> bool {
+        T::accepts(ty)
+    }
+    fn array_len(&self) -> usize {
+        1
+    }
+    unsafe fn upload(&self, location: GLint) {
+        T::upload(location, core::slice::from_ref(self))
+    }
+}
+impl<T: UniformPrimitive> UniformValue for &[T] {
+    fn accepts(ty: GlslType) -> bool {
+        T::accepts(ty)
+    }
+    fn array_len(&self) -> usize {
+        <[T]>::len(self)
+    }
+    unsafe fn upload(&self, location: GLint) {
+        T::upload(location, self)
+    }
+}
+
+/// The ways that setting a uniform can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UniformError {
+    /// The program has no active uniform with this name.
+    ///
+    /// Remember that the GLSL compiler removes uniforms that don't affect the
+    /// output, so this also happens with uniforms that are declared but unused.
+    NotFound {
+        /// The name that was looked up.
+        name: String,
+    },
+    /// The uniform exists, but the value given can't be sent to its type.
+    TypeMismatch {
+        /// The name of the uniform.
+        name: String,
+        /// The type the uniform is declared with in GLSL.
+        declared: GlslType,
+        /// The Rust type of the value given.
+        given: &'static str,
+    },
+    /// More array elements were given than the uniform has room for.
+    TooManyElements {
+        /// The name of the uniform.
+        name: String,
+        /// How many elements are left in the uniform, from the named element on.
+        capacity: usize,
+        /// How many elements were given.
+        given: usize,
+    },
+}
+impl fmt::Display for UniformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniformError::NotFound { name } => {
+                write!(f, "no active uniform named `{name}`")
+            }
+            UniformError::TypeMismatch {
+                name,
+                declared,
+                given,
+            } => {
+                write!(
+                    f,
+                    "uniform `{name}` is a `{declared}`, which can't be set from a `{given}`"
+                )
+            }
+            UniformError::TooManyElements {
+                name,
+                capacity,
+                given,
+            } => {
+                write!(
+                    f,
+                    "uniform `{name}` has room for {capacity} elements, but {given} were given"
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for UniformError {}
+
+impl ShaderProgram {
+    /// Sets the named uniform of this program to a value.
+    ///
+    /// The value's type is checked against the type that the uniform has in the
+    /// GLSL source, so passing an `f32` to a `vec4` is an error rather than a
+    /// silent no-op. Array uniforms take a slice, and can be set from a given
+    /// element on by naming it (such as `"lights[2]"`).
+    ///
+    /// Uniform locations are looked up once and then cached in the program.
+    ///
+    /// Note: This makes the program the program in use, since that's what
+    /// `glUniform*` calls affect.
+    pub fn set_uniform<V: UniformValue>(&self, name: &str, value: V) -> Result<(), UniformError> {
+        let slot = self
+            .uniform_slot(name)
+            .ok_or_else(|| UniformError::NotFound {
+                name: name.to_string(),
+            })?;
+        if !V::accepts(slot.ty) {
+            return Err(UniformError::TypeMismatch {
+                name: name.to_string(),
+                declared: slot.ty,
+                given: core::any::type_name::<V>(),
+            });
+        }
+        if value.array_len() > slot.capacity {
+            return Err(UniformError::TooManyElements {
+                name: name.to_string(),
+                capacity: slot.capacity,
+                given: value.array_len(),
+            });
+        }
+        self.use_program();
+        unsafe { value.upload(slot.location) };
+        Ok(())
+    }
+}
+
+/// Where to send a uniform value, and what it accepts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct UniformSlot {
+    pub location: GLint,
+    pub ty: GlslType,
+    /// Array elements from this location to the end of the array.
+    pub capacity: usize,
+}
+
+impl ShaderProgram {
+    /// Looks up a uniform in the cache, filling the cache the first time.
+    pub(crate) fn uniform_slot(&self, name: &str) -> Option<UniformSlot> {
+        let mut cache = self.uniforms.borrow_mut();
+        let slots = cache.get_or_insert_with(|| self.query_uniform_slots());
+        if let Some(slot) = slots.get(name) {
+            return Some(*slot);
+        }
+        // Elements past the first aren't listed by GL, so find `name[i]` via
+        // the array itself and remember the answer.
+        let (base, index) = name.strip_suffix(']')?.rsplit_once('[')?;
+        let index: usize = index.parse().ok()?;
+        let array = *slots.get(base)?;
+        let location = self.raw_uniform_location(name)?;
+        let slot = UniformSlot {
+            location,
+            ty: array.ty,
+            capacity: array.capacity.checked_sub(index)?,
+        };
+        slots.insert(name.to_string(), slot);
+        Some(slot)
+    }
+
+    fn raw_uniform_location(&self, name: &str) -> Option<GLint> {
+        let name = std::ffi::CString::new(name).ok()?;
+        let location = unsafe { gl::GetUniformLocation(self.id(), name.as_ptr()) };
+        (location >= 0).then_some(location)
+    }
+
+    fn query_uniform_slots(&self) -> HashMap<String, UniformSlot> {
+        let mut slots = HashMap::new();
+        for uniform in self.active_uniforms() {
+            // Uniforms inside of uniform blocks don't have a location.
+            let Some(location) = uniform.location else {
+                continue;
+            };
+            let slot = UniformSlot {
+                location,
+                ty: uniform.ty,
+                capacity: uniform.array_size,
+            };
+            if let Some(base) = uniform.name.strip_suffix("[0]") {
+                slots.insert(base.to_string(), slot);
+            }
+            slots.insert(uniform.name, slot);
+        }
+        slots
+    }
+}
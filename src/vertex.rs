@@ -0,0 +1,422 @@
+//! Describing the layout of vertex data so that a vertex array can be set up
+//! from it.
+
+use crate::{Buffer, BufferType, GlslType, ScalarType, ShaderProgram, VertexArray};
+use crate::{Vec2, Vec3, Vec4};
+use core::fmt;
+use gl::types::{GLenum, GLint, GLsizei};
+
+/// The component types that vertex attribute data can be stored as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    /// `i8`
+    Byte = gl::BYTE as isize,
+    /// `u8`
+    UnsignedByte = gl::UNSIGNED_BYTE as isize,
+    /// `i16`
+    Short = gl::SHORT as isize,
+    /// `u16`
+    UnsignedShort = gl::UNSIGNED_SHORT as isize,
+    /// `i32`
+    Int = gl::INT as isize,
+    /// `u32`
+    UnsignedInt = gl::UNSIGNED_INT as isize,
+    /// `f32`
+    Float = gl::FLOAT as isize,
+}
+impl AttributeType {
+    /// If this is one of the integer types.
+    pub fn is_integer(self) -> bool {
+        self != AttributeType::Float
+    }
+}
+
+/// A Rust type that can be the data of a single vertex attribute.
+pub trait VertexAttributeType {
+    /// The type of each component.
+    const TYPE: AttributeType;
+    /// The number of components, from 1 to 4.
+    const COMPONENTS: GLint;
+}
+
+macro_rules! vertex_attribute_type {
+    ($($t:ty => $ty:ident),* $(,)?) => {
+        $(
+            impl VertexAttributeType for $t {
+                const TYPE: AttributeType = AttributeType::$ty;
+                const COMPONENTS: GLint = 1;
+            }
+            impl VertexAttributeType for [$t; 1] {
+                const TYPE: AttributeType = AttributeType::$ty;
+                const COMPONENTS: GLint = 1;
+            }
+            impl VertexAttributeType for [$t; 2] {
+                const TYPE: AttributeType = AttributeType::$ty;
+                const COMPONENTS: GLint = 2;
+            }
+            impl VertexAttributeType for [$t; 3] {
+                const TYPE: AttributeType = AttributeType::$ty;
+                const COMPONENTS: GLint = 3;
+            }
+            impl VertexAttributeType for [$t; 4] {
+                const TYPE: AttributeType = AttributeType::$ty;
+                const COMPONENTS: GLint = 4;
+            }
+        )*
+    };
+}
+vertex_attribute_type! {
+    i8 => Byte,
+    u8 => UnsignedByte,
+    i16 => Short,
+    u16 => UnsignedShort,
+    i32 => Int,
+    u32 => UnsignedInt,
+    f32 => Float,
+}
+impl VertexAttributeType for Vec2 {
+    const TYPE: AttributeType = AttributeType::Float;
+    const COMPONENTS: GLint = 2;
+}
+impl VertexAttributeType for Vec3 {
+    const TYPE: AttributeType = AttributeType::Float;
+    const COMPONENTS: GLint = 3;
+}
+impl VertexAttributeType for Vec4 {
+    const TYPE: AttributeType = AttributeType::Float;
+    const COMPONENTS: GLint = 4;
+}
+
+/// Where and how one attribute is stored within a vertex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    /// The name of the field this came from.
+    pub name: &'static str,
+    /// The attribute location in the shader (the `layout (location = N)`).
+    pub location: u32,
+    /// The number of components, from 1 to 4.
+    pub components: GLint,
+    /// The type of each component.
+    pub ty: AttributeType,
+    /// If integer data is turned into `0.0..=1.0` (or `-1.0..=1.0`) floats.
+    pub normalized: bool,
+    /// The byte offset from the start of the vertex.
+    pub offset: usize,
+}
+impl VertexAttribute {
+    /// Describes an attribute holding a `T` at the given offset.
+    pub const fn new<T: VertexAttributeType>(
+        name: &'static str,
+        location: u32,
+        offset: usize,
+    ) -> Self {
+        Self {
+            name,
+            location,
+            components: T::COMPONENTS,
+            ty: T::TYPE,
+            normalized: false,
+            offset,
+        }
+    }
+
+    /// Marks the attribute as normalized.
+    pub const fn normalized(self) -> Self {
+        Self {
+            normalized: true,
+            ..self
+        }
+    }
+
+    /// If the shader sees this as an integer input (`int`, `uvec2`, etc)
+    /// rather than a float input.
+    pub fn is_integer(&self) -> bool {
+        self.ty.is_integer() && !self.normalized
+    }
+
+    /// Sets this attribute's pointer within the currently bound vertex array,
+    /// reading from the currently bound array buffer, and enables it.
+    pub fn apply(&self, stride: usize) {
+        let stride = GLsizei::try_from(stride).unwrap();
+        let offset = self.offset as *const _;
+        unsafe {
+            if self.is_integer() {
+                gl::VertexAttribIPointer(
+                    self.location,
+                    self.components,
+                    self.ty as GLenum,
+                    stride,
+                    offset,
+                );
+            } else {
+                gl::VertexAttribPointer(
+                    self.location,
+                    self.components,
+                    self.ty as GLenum,
+                    if self.normalized { gl::TRUE } else { gl::FALSE },
+                    stride,
+                    offset,
+                );
+            }
+            gl::EnableVertexAttribArray(self.location);
+        }
+    }
+}
+
+/// A type that can be used as a vertex in a vertex buffer.
+///
+/// Usually you derive this (with the `derive` feature) on a `#[repr(C)]`
+/// struct with one field per attribute.
+///
+/// ## Safety
+/// The type must have no padding bytes, and the attributes must describe data
+/// that's actually within the type.
+pub unsafe trait Vertex: Copy {
+    /// The attributes that make up each vertex.
+    fn attributes() -> Vec<VertexAttribute>;
+
+    /// Views a slice of vertices as bytes, for uploading to a buffer.
+    fn slice_as_bytes(vertices: &[Self]) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(vertices.as_ptr().cast(), core::mem::size_of_val(vertices))
+        }
+    }
+}
+
+macro_rules! single_attribute_vertex {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl Vertex for $t {
+                fn attributes() -> Vec<VertexAttribute> {
+                    vec![VertexAttribute::new::<$t>("position", 0, 0)]
+                }
+            }
+        )*
+    };
+}
+single_attribute_vertex!([f32; 2], [f32; 3], [f32; 4]);
+
+impl VertexArray {
+    /// Sets up this vertex array to read `V` vertices out of `buffer`.
+    ///
+    /// This leaves both the vertex array and the buffer bound.
+    pub fn set_vertex_layout<V: Vertex>(&self, buffer: &Buffer) {
+        self.bind();
+        buffer.bind(BufferType::Array);
+        let stride = core::mem::size_of::<V>();
+        for attribute in V::attributes() {
+            attribute.apply(stride);
+        }
+    }
+}
+
+/// One way that a vertex layout and a program's inputs disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutMismatch {
+    /// The program reads an input that the layout doesn't provide, so it would
+    /// only ever see the attribute's default value.
+    Missing {
+        /// The name of the input in the GLSL source.
+        name: String,
+        /// The location of the input.
+        location: u32,
+        /// The type the input is declared with.
+        declared: GlslType,
+    },
+    /// The layout provides an attribute that the program never reads.
+    ///
+    /// This is often a typo in the location, though it's also what happens
+    /// when the compiler removes an input that doesn't affect the output, so
+    /// it's only reported by the strict checks.
+    Extra {
+        /// The name of the attribute in the layout.
+        name: &'static str,
+        /// The location of the attribute.
+        location: u32,
+    },
+    /// The program reads the input as a different type than the layout gives.
+    TypeMismatch {
+        /// The name of the input in the GLSL source.
+        name: String,
+        /// The location of the input.
+        location: u32,
+        /// The type the input is declared with.
+        declared: GlslType,
+        /// The attribute the layout provides for it.
+        given: VertexAttribute,
+    },
+}
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutMismatch::Missing {
+                name,
+                location,
+                declared,
+            } => {
+                write!(
+                    f,
+                    "input `{declared} {name}` at location {location} isn't in the vertex layout"
+                )
+            }
+            LayoutMismatch::Extra { name, location } => {
+                write!(
+                    f,
+                    "vertex attribute `{name}` at location {location} isn't read by the program"
+                )
+            }
+            LayoutMismatch::TypeMismatch {
+                name,
+                location,
+                declared,
+                given,
+            } => {
+                let kind = if given.is_integer() {
+                    "integer"
+                } else {
+                    "float"
+                };
+                write!(
+                    f,
+                    "input `{declared} {name}` at location {location} is given {} {kind} \
+                     components of type {:?} by vertex attribute `{}`",
+                    given.components, given.ty, given.name
+                )
+            }
+        }
+    }
+}
+
+/// The problems found when checking a vertex layout against a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexLayoutError(pub Vec<LayoutMismatch>);
+impl fmt::Display for VertexLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the vertex layout doesn't match the program's inputs:")?;
+        for mismatch in &self.0 {
+            write!(f, "\n  {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for VertexLayoutError {}
+
+/// If an attribute can feed one location's worth of an input of type `ty`.
+///
+/// The base types have to match, since GL doesn't convert between float and
+/// integer inputs, but the attribute is allowed to have fewer components than
+/// the input: the missing ones are filled in from `(0, 0, 0, 1)`.
+fn attribute_fits(attribute: &VertexAttribute, ty: GlslType) -> bool {
+    let scalar_fits = match ty.scalar() {
+        Some(ScalarType::Float) => !attribute.is_integer(),
+        Some(ScalarType::Int) => {
+            attribute.is_integer()
+                && matches!(
+                    attribute.ty,
+                    AttributeType::Byte | AttributeType::Short | AttributeType::Int
+                )
+        }
+        Some(ScalarType::UInt) => {
+            attribute.is_integer()
+                && matches!(
+                    attribute.ty,
+                    AttributeType::UnsignedByte
+                        | AttributeType::UnsignedShort
+                        | AttributeType::UnsignedInt
+                )
+        }
+        // Double inputs need `glVertexAttribLPointer`, which layouts don't use.
+        _ => false,
+    };
+    let (_, rows) = ty.dimensions();
+    scalar_fits && u32::try_from(attribute.components).is_ok_and(|c| c <= rows)
+}
+
+impl ShaderProgram {
+    /// Checks that vertices of type `V` give this program the inputs it reads.
+    ///
+    /// Do this once after linking, before the first draw call, to catch
+    /// locations that were changed in the shader but not in the vertex type
+    /// (or the other way around).
+    ///
+    /// Attributes that the program doesn't read aren't counted, since the
+    /// compiler removes inputs that don't affect the output; see
+    /// [`check_vertex_layout_strict`](Self::check_vertex_layout_strict).
+    pub fn check_vertex_layout<V: Vertex>(&self) -> Result<(), VertexLayoutError> {
+        self.check_vertex_attributes(&V::attributes())
+    }
+
+    /// Checks that vertices of type `V` give this program the inputs it
+    /// reads, and that the program reads every attribute they have.
+    pub fn check_vertex_layout_strict<V: Vertex>(&self) -> Result<(), VertexLayoutError> {
+        self.check_vertex_attributes_strict(&V::attributes())
+    }
+
+    /// Checks a list of vertex attributes against the inputs this program
+    /// reads, reporting every input that's missing or has the wrong type.
+    ///
+    /// Built-in inputs such as `gl_VertexID` are ignored. Matrix and array
+    /// inputs take one attribute per location, one for each column or element.
+    pub fn check_vertex_attributes(
+        &self,
+        attributes: &[VertexAttribute],
+    ) -> Result<(), VertexLayoutError> {
+        self.vertex_attribute_mismatches(attributes, false)
+    }
+
+    /// Checks a list of vertex attributes like
+    /// [`check_vertex_attributes`](Self::check_vertex_attributes), also
+    /// reporting every attribute that isn't read.
+    pub fn check_vertex_attributes_strict(
+        &self,
+        attributes: &[VertexAttribute],
+    ) -> Result<(), VertexLayoutError> {
+        self.vertex_attribute_mismatches(attributes, true)
+    }
+
+    fn vertex_attribute_mismatches(
+        &self,
+        attributes: &[VertexAttribute],
+        strict: bool,
+    ) -> Result<(), VertexLayoutError> {
+        let mut unread: Vec<&VertexAttribute> = attributes.iter().collect();
+        let mut mismatches = Vec::new();
+        for input in self.active_attributes() {
+            let Some(first) = input.location else {
+                continue;
+            };
+            let (cols, _) = input.ty.dimensions();
+            let slots = cols as usize * input.array_size;
+            for location in (first..).take(slots) {
+                let Some(i) = unread.iter().position(|a| a.location == location) else {
+                    mismatches.push(LayoutMismatch::Missing {
+                        name: input.name.clone(),
+                        location,
+                        declared: input.ty,
+                    });
+                    continue;
+                };
+                let attribute = unread.remove(i);
+                if !attribute_fits(attribute, input.ty) {
+                    mismatches.push(LayoutMismatch::TypeMismatch {
+                        name: input.name.clone(),
+                        location,
+                        declared: input.ty,
+                        given: *attribute,
+                    });
+                }
+            }
+        }
+        if strict {
+            mismatches.extend(unread.into_iter().map(|a| LayoutMismatch::Extra {
+                name: a.name,
+                location: a.location,
+            }));
+        }
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(VertexLayoutError(mismatches))
+        }
+    }
+}
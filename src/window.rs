@@ -0,0 +1,402 @@
+//! Making an SDL window with a GL context, ready to draw with.
+
+use crate::{gl_version, has_extension, read_default_pixels};
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+use image::RgbaImage;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::video::{GLContext, GLProfile, SwapInterval, Window};
+use sdl2::{EventPump, Sdl, VideoSubsystem};
+use std::ffi::{c_void, CStr};
+use std::path::Path;
+
+/// Builds a [`GlWindow`] with the chosen options.
+///
+/// The defaults are an 800 by 600 window with a GL 3.3 core context, a
+/// 24-bit depth buffer, an 8-bit stencil buffer and vsync, which is drawn at
+/// full resolution on high-DPI displays and keeps its viewport in sync with
+/// its size.
+#[derive(Debug, Clone)]
+pub struct GlWindowBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    resizable: bool,
+    fullscreen: bool,
+    version: (u8, u8),
+    profile: GLProfile,
+    debug: bool,
+    samples: u8,
+    depth_bits: u8,
+    stencil_bits: u8,
+    srgb: bool,
+    vsync: bool,
+    high_dpi: bool,
+    auto_viewport: bool,
+}
+impl Default for GlWindowBuilder {
+    fn default() -> Self {
+        Self {
+            title: String::from("OpenGL"),
+            width: 800,
+            height: 600,
+            resizable: false,
+            fullscreen: false,
+            version: (3, 3),
+            profile: GLProfile::Core,
+            debug: false,
+            samples: 0,
+            depth_bits: 24,
+            stencil_bits: 8,
+            srgb: false,
+            vsync: true,
+            high_dpi: true,
+            auto_viewport: true,
+        }
+    }
+}
+impl GlWindowBuilder {
+    /// Sets the window title.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the size of the window, in screen coordinates.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets if the user can resize the window.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Sets if the window covers the whole desktop.
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Sets the GL version to ask for.
+    pub fn gl_version(mut self, major: u8, minor: u8) -> Self {
+        self.version = (major, minor);
+        self
+    }
+
+    /// Sets the GL profile to ask for.
+    pub fn profile(mut self, profile: GLProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Sets if the context is a debug context.
+    ///
+    /// When the driver supports debug output, its messages are printed to
+    /// standard error as they happen.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Sets the number of samples per pixel for multisample anti-aliasing, or
+    /// `0` to turn it off.
+    pub fn samples(mut self, samples: u8) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Sets the size in bits of each value in the depth buffer, or `0` for
+    /// no depth buffer.
+    pub fn depth_bits(mut self, bits: u8) -> Self {
+        self.depth_bits = bits;
+        self
+    }
+
+    /// Sets the size in bits of each value in the stencil buffer, or `0` for
+    /// no stencil buffer.
+    pub fn stencil_bits(mut self, bits: u8) -> Self {
+        self.stencil_bits = bits;
+        self
+    }
+
+    /// Sets if the window's framebuffer is sRGB encoded.
+    ///
+    /// With this on, `GL_FRAMEBUFFER_SRGB` is enabled, so the linear colors
+    /// written by shaders are encoded when they're stored.
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Sets if buffer swaps wait for the display to refresh.
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Sets if the window is drawn at the full resolution of high-DPI
+    /// displays, rather than being scaled up.
+    ///
+    /// When it is, the size in pixels that GL draws to can be bigger than the
+    /// size of the window; see [`GlWindow::drawable_size`].
+    pub fn high_dpi(mut self, high_dpi: bool) -> Self {
+        self.high_dpi = high_dpi;
+        self
+    }
+
+    /// Sets if the viewport is set to cover the whole window whenever its size
+    /// changes, in [`GlWindow::handle_event`].
+    pub fn auto_viewport(mut self, auto_viewport: bool) -> Self {
+        self.auto_viewport = auto_viewport;
+        self
+    }
+
+    /// Starts SDL, makes the window and its GL context, and loads the GL
+    /// functions.
+    ///
+    /// Vsync is only asked for, so a driver that won't do it isn't an error;
+    /// check [`GlWindow::vsync`] to see if it's on.
+    pub fn build(self) -> Result<GlWindow, String> {
+        let sdl = sdl2::init()?;
+        let video = sdl.video()?;
+
+        let gl_attr = video.gl_attr();
+        gl_attr.set_context_profile(self.profile);
+        gl_attr.set_context_version(self.version.0, self.version.1);
+        if self.debug {
+            gl_attr.set_context_flags().debug().set();
+        }
+        gl_attr.set_double_buffer(true);
+        gl_attr.set_depth_size(self.depth_bits);
+        gl_attr.set_stencil_size(self.stencil_bits);
+        if self.samples > 0 {
+            gl_attr.set_multisample_buffers(1);
+            gl_attr.set_multisample_samples(self.samples);
+        }
+        gl_attr.set_framebuffer_srgb_compatible(self.srgb);
+
+        let mut window = video.window(&self.title, self.width, self.height);
+        window.opengl();
+        if self.resizable {
+            window.resizable();
+        }
+        if self.fullscreen {
+            window.fullscreen_desktop();
+        }
+        if self.high_dpi {
+            window.allow_highdpi();
+        }
+        let window = window.build().map_err(|e| e.to_string())?;
+
+        let gl_context = window.gl_create_context()?;
+        gl::load_with(|s| video.gl_get_proc_address(s) as *const c_void);
+
+        let interval = if self.vsync {
+            SwapInterval::VSync
+        } else {
+            SwapInterval::Immediate
+        };
+        // Not every driver lets the swap interval be set, which isn't worth
+        // failing over.
+        let _ = video.gl_set_swap_interval(interval);
+
+        unsafe {
+            if self.srgb {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+            if self.debug && (gl_version().at_least(4, 3) || has_extension("GL_KHR_debug")) {
+                gl::Enable(gl::DEBUG_OUTPUT);
+                gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+                gl::DebugMessageCallback(Some(print_debug_message), core::ptr::null());
+            }
+        }
+
+        let mut window = GlWindow {
+            gl_context,
+            window,
+            video,
+            sdl,
+            drawable_size: (0, 0),
+            auto_viewport: self.auto_viewport,
+        };
+        window.refresh_size();
+        Ok(window)
+    }
+}
+
+/// Prints a message from GL's debug output to standard error.
+pub(crate) extern "system" fn print_debug_message(
+    _source: GLenum,
+    _ty: GLenum,
+    _id: GLuint,
+    severity: GLenum,
+    _length: GLsizei,
+    message: *const GLchar,
+    _user: *mut c_void,
+) {
+    let severity = match severity {
+        gl::DEBUG_SEVERITY_HIGH => "error",
+        gl::DEBUG_SEVERITY_MEDIUM => "warning",
+        gl::DEBUG_SEVERITY_LOW => "note",
+        _ => return,
+    };
+    let message = unsafe { CStr::from_ptr(message) };
+    eprintln!("GL {severity}: {}", message.to_string_lossy());
+}
+
+/// An SDL window and its GL context, along with SDL itself.
+///
+/// These are kept together so that the GL context stays alive (and current)
+/// for as long as the window, and SDL for as long as both. They're dropped in
+/// the right order when this is.
+pub struct GlWindow {
+    // The fields are dropped in order, so the context goes before the window,
+    // and the window before SDL.
+    gl_context: GLContext,
+    window: Window,
+    video: VideoSubsystem,
+    sdl: Sdl,
+    drawable_size: (u32, u32),
+    auto_viewport: bool,
+}
+impl GlWindow {
+    /// Starts building a new window.
+    pub fn builder() -> GlWindowBuilder {
+        GlWindowBuilder::default()
+    }
+
+    /// The SDL context.
+    pub fn sdl(&self) -> &Sdl {
+        &self.sdl
+    }
+
+    /// The SDL video subsystem.
+    pub fn video(&self) -> &VideoSubsystem {
+        &self.video
+    }
+
+    /// The SDL window.
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// The SDL window, for changing it.
+    pub fn window_mut(&mut self) -> &mut Window {
+        &mut self.window
+    }
+
+    /// The GL context.
+    pub fn gl_context(&self) -> &GLContext {
+        &self.gl_context
+    }
+
+    /// Gets the event pump, for reading input and window events.
+    ///
+    /// SDL only allows one event pump at a time.
+    pub fn event_pump(&self) -> Result<EventPump, String> {
+        self.sdl.event_pump()
+    }
+
+    /// Shows what has been drawn, by swapping the window's buffers.
+    pub fn swap(&self) {
+        self.window.gl_swap_window();
+    }
+
+    /// The size of the window, in screen coordinates.
+    pub fn size(&self) -> (u32, u32) {
+        self.window.size()
+    }
+
+    /// The size of the window's framebuffer, in pixels.
+    ///
+    /// On high-DPI displays this is bigger than [`size`](Self::size), and
+    /// it's what the viewport and anything sized to match the screen (like
+    /// framebuffers for post-processing) should use.
+    pub fn drawable_size(&self) -> (u32, u32) {
+        self.drawable_size
+    }
+
+    /// The width of the framebuffer divided by its height, for making
+    /// projection matrices.
+    pub fn aspect_ratio(&self) -> f32 {
+        let (width, height) = self.drawable_size;
+        width as f32 / height.max(1) as f32
+    }
+
+    /// How many pixels there are per screen coordinate, such as `2.0` on
+    /// many high-DPI displays.
+    pub fn dpi_scale(&self) -> f32 {
+        let (width, _) = self.window.size();
+        self.drawable_size.0 as f32 / width.max(1) as f32
+    }
+
+    /// Hides the cursor and keeps it in the window, so that mouse motion is
+    /// reported without stopping at the edges, such as for turning a
+    /// camera.
+    pub fn set_relative_mouse_mode(&self, relative: bool) {
+        self.sdl.mouse().set_relative_mouse_mode(relative);
+    }
+
+    /// If relative mouse mode is on.
+    pub fn relative_mouse_mode(&self) -> bool {
+        self.sdl.mouse().relative_mouse_mode()
+    }
+
+    /// Reads what has been drawn to the whole window.
+    ///
+    /// This reads the back buffer, so it has to be called after drawing and
+    /// before [`swap`](Self::swap).
+    pub fn read_pixels(&self) -> RgbaImage {
+        let (width, height) = self.drawable_size;
+        read_default_pixels(0, 0, width, height)
+    }
+
+    /// Saves what has been drawn to the window as an image, in the format
+    /// that the path's extension names (such as PNG).
+    ///
+    /// Like [`read_pixels`](Self::read_pixels), this has to be called before
+    /// swapping.
+    pub fn save_screenshot(&self, path: impl AsRef<Path>) -> image::ImageResult<()> {
+        self.read_pixels().save(path)
+    }
+
+    /// Keeps track of the window's size, given each event from the event
+    /// pump. [`Runner`](crate::Runner) does this by itself.
+    ///
+    /// Returns `true` if the size changed, in which case the viewport was
+    /// also set to cover the window (unless that was turned off with
+    /// [`GlWindowBuilder::auto_viewport`]).
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Window {
+                window_id,
+                win_event: WindowEvent::SizeChanged(..) | WindowEvent::DisplayChanged(..),
+                ..
+            } if *window_id == self.window.id() => self.refresh_size(),
+            _ => false,
+        }
+    }
+
+    /// Reads the drawable size again, updating the viewport if it changed.
+    fn refresh_size(&mut self) -> bool {
+        let size = self.window.drawable_size();
+        if size == self.drawable_size {
+            return false;
+        }
+        self.drawable_size = size;
+        if self.auto_viewport {
+            unsafe {
+                gl::Viewport(0, 0, size.0 as GLsizei, size.1 as GLsizei);
+            }
+        }
+        true
+    }
+
+    /// If buffer swaps wait for the display to refresh.
+    pub fn vsync(&self) -> bool {
+        self.video.gl_get_swap_interval() != SwapInterval::Immediate
+    }
+}
@@ -0,0 +1,80 @@
+//! Drawing with a headless context, so that rendering can be checked on
+//! machines with no display or GPU.
+
+use rust_opengl::{clear_color, HeadlessContext, Mesh, ShaderProgram, Topology};
+
+const VERTEX_SHADER_SOURCE: &str = r#"
+      #version 330 core
+      layout (location = 0) in vec3 aPos;
+      void main() {
+        gl_Position = vec4(aPos.x, aPos.y, aPos.z, 1.0);
+      }
+    "#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+      #version 330 core
+      out vec4 FragColor;
+      void main() {
+        FragColor = vec4(1.0, 0.5, 0.2, 1.0);
+      }
+    "#;
+
+/// Makes a context, or gives `None` (so that the test is skipped) on machines
+/// with no EGL display to make one with.
+fn context(width: u32, height: u32) -> Option<HeadlessContext> {
+    match HeadlessContext::builder().size(width, height).build() {
+        Ok(context) => Some(context),
+        Err(e) => {
+            eprintln!("skipping, since there's no headless context: {e}");
+            None
+        }
+    }
+}
+
+#[test]
+fn clear_fills_the_framebuffer() {
+    let Some(context) = context(7, 5) else {
+        return;
+    };
+    clear_color(0.0, 0.0, 1.0, 1.0);
+    unsafe {
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+
+    let image = context.read_pixels();
+    assert_eq!(image.dimensions(), (7, 5));
+    assert!(image.pixels().all(|p| p.0 == [0, 0, 255, 255]));
+}
+
+#[test]
+fn triangle_is_drawn_upright() {
+    let Some(context) = context(64, 64) else {
+        return;
+    };
+    let shader_program =
+        ShaderProgram::from_vert_frag(VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE).unwrap();
+    let vertices: [[f32; 3]; 3] = [[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.0, 0.5, 0.0]];
+    let mesh = Mesh::new(&vertices, Topology::Triangles).unwrap();
+
+    clear_color(0.0, 0.0, 0.0, 1.0);
+    unsafe {
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+    shader_program.use_program();
+    mesh.draw();
+
+    // The image has its top row first, so the point of the triangle is near
+    // the top and the wide part near the bottom.
+    let image = context.read_pixels();
+    let black = [0, 0, 0, 255];
+    let drawn = |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        r == 255 && g.abs_diff(128) <= 1 && b.abs_diff(51) <= 1 && a == 255
+    };
+    assert!(drawn(32, 32));
+    assert!(drawn(32, 20));
+    assert!(drawn(18, 46));
+    assert!(!drawn(18, 20));
+    assert_eq!(image.get_pixel(0, 0).0, black);
+    assert_eq!(image.get_pixel(63, 63).0, black);
+}
@@ -0,0 +1,123 @@
+//! Checking the `std140` layout of derived structs against offsets worked
+//! out by hand from the rules in the GL spec.
+
+use rust_opengl::{to_std140_bytes, GlslType, Std140, Std140Member};
+
+#[derive(Std140)]
+struct Light {
+    position: [f32; 3],
+    enabled: bool,
+    color: [f32; 3],
+    intensity: f32,
+    transform: [[f32; 3]; 3],
+    #[std140(array)]
+    weights: [f32; 2],
+}
+
+#[derive(Std140)]
+struct Outer {
+    count: u32,
+    #[std140(array)]
+    lights: [Light; 3],
+    model: [[f32; 4]; 4],
+    enabled: bool,
+}
+
+fn light(i: u8) -> Light {
+    let n = f32::from(i);
+    Light {
+        position: [n; 3],
+        enabled: i.is_multiple_of(2),
+        color: [n + 0.5; 3],
+        intensity: n + 0.25,
+        transform: [[n; 3]; 3],
+        weights: [n, n + 1.0],
+    }
+}
+
+fn member(name: &str, offset: usize, ty: GlslType, array_size: usize) -> Std140Member {
+    Std140Member {
+        name: name.to_string(),
+        offset,
+        ty,
+        array_size,
+    }
+}
+
+#[test]
+fn sizes_and_alignment() {
+    // A vec3 is padded out by the bool after it, a mat3 is three vec4
+    // columns, and each float of an array takes 16 bytes.
+    assert_eq!(Light::ALIGN, 16);
+    assert_eq!(Light::SIZE, 112);
+    assert_eq!(Light::ARRAY_STRIDE, 112);
+    // The struct array starts on a 16 byte boundary after the uint, and the
+    // size is rounded up to the struct's alignment after the last bool.
+    assert_eq!(Outer::ALIGN, 16);
+    assert_eq!(Outer::SIZE, 432);
+}
+
+#[test]
+fn member_offsets() {
+    let mut members = Vec::new();
+    Outer::std140_members("", 0, &mut members);
+
+    let mut expected = vec![member("count", 0, GlslType::UINT, 1)];
+    for (i, base) in [16, 128, 240].into_iter().enumerate() {
+        let name = |field: &str| format!("lights[{i}].{field}");
+        expected.extend([
+            member(&name("position"), base, GlslType::VEC3, 1),
+            member(&name("enabled"), base + 12, GlslType::BOOL, 1),
+            member(&name("color"), base + 16, GlslType::VEC3, 1),
+            member(&name("intensity"), base + 28, GlslType::FLOAT, 1),
+            member(&name("transform"), base + 32, GlslType::MAT3, 1),
+            member(&name("weights[0]"), base + 80, GlslType::FLOAT, 2),
+        ]);
+    }
+    expected.extend([
+        member("model", 352, GlslType::MAT4, 1),
+        member("enabled", 416, GlslType::BOOL, 1),
+    ]);
+    assert_eq!(members, expected);
+}
+
+#[test]
+fn nested_members_are_named_after_the_block_member() {
+    let mut members = Vec::new();
+    Light::std140_members("sun", 64, &mut members);
+    assert_eq!(members[0], member("sun.position", 64, GlslType::VEC3, 1));
+    assert_eq!(
+        members[5],
+        member("sun.weights[0]", 144, GlslType::FLOAT, 2)
+    );
+}
+
+#[test]
+fn bytes_are_written_at_the_offsets() {
+    let outer = Outer {
+        count: 7,
+        lights: [light(0), light(1), light(2)],
+        model: [[9.0; 4]; 4],
+        enabled: true,
+    };
+    let bytes = to_std140_bytes(&outer);
+    assert_eq!(bytes.len(), Outer::SIZE);
+
+    let u32_at = |offset: usize| u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let f32_at = |offset: usize| f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    assert_eq!(u32_at(0), 7);
+    // lights[1] starts at 128.
+    assert_eq!(f32_at(128), 1.0);
+    assert_eq!(u32_at(140), 0);
+    assert_eq!(f32_at(144), 1.5);
+    assert_eq!(f32_at(156), 1.25);
+    // The second column of the mat3, after the first one's padding.
+    assert_eq!(f32_at(160 + 16), 1.0);
+    // The second weight is a whole vec4 after the first.
+    assert_eq!(f32_at(208), 1.0);
+    assert_eq!(f32_at(208 + 16), 2.0);
+    // lights[2] is enabled.
+    assert_eq!(u32_at(252), 1);
+    assert_eq!(f32_at(352 + 60), 9.0);
+    assert_eq!(u32_at(416), 1);
+}
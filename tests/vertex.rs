@@ -0,0 +1,32 @@
+//! Deriving `Vertex` for structs with more than `#[repr(C)]` on them.
+
+use rust_opengl::{AttributeType, Vertex};
+
+#[derive(Clone, Copy, Vertex)]
+#[repr(C, align(16))]
+struct Aligned {
+    pos: [f32; 3],
+    color: [u8; 4],
+}
+
+#[derive(Clone, Copy, Vertex)]
+#[repr(align(8), C)]
+struct AlignedFirst {
+    pos: [f32; 2],
+}
+
+#[test]
+fn repr_with_other_items_derives() {
+    let attributes = Aligned::attributes();
+    assert_eq!(attributes.len(), 2);
+    assert_eq!(attributes[0].name, "pos");
+    assert_eq!(attributes[0].location, 0);
+    assert_eq!(attributes[0].ty, AttributeType::Float);
+    assert_eq!(attributes[0].components, 3);
+    assert_eq!(attributes[1].name, "color");
+    assert_eq!(attributes[1].location, 1);
+    assert_eq!(attributes[1].offset, 12);
+    assert_eq!(core::mem::size_of::<Aligned>(), 16);
+
+    assert_eq!(AlignedFirst::attributes().len(), 1);
+}